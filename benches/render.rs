@@ -0,0 +1,67 @@
+//! Compares redrawing every frame with only redrawing when something changed, on an idle screen.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ratatui::{backend::TestBackend, Terminal};
+use stash::{
+    action::Action,
+    components::{
+        log_view::LogView, login::LoginComponent, status_bar::StatusBar, tab_bar::TabBar, Component,
+    },
+    config::Config,
+};
+
+fn components() -> Vec<Box<dyn Component>> {
+    let mut components: Vec<Box<dyn Component>> = vec![
+        Box::new(LoginComponent::new()),
+        Box::new(LogView::default()),
+        Box::new(StatusBar::new()),
+        Box::new(TabBar::new()),
+    ];
+    for component in components.iter_mut() {
+        component
+            .register_config_handler(Config::default())
+            .unwrap();
+    }
+    // Let the splash animation finish so the screen is idle.
+    for _ in 0..100 {
+        for component in components.iter_mut() {
+            component.update(Action::Tick).unwrap();
+        }
+    }
+    components
+}
+
+/// A tick followed by a frame, drawing only if `always` or a component is dirty.
+fn frame(
+    terminal: &mut Terminal<TestBackend>,
+    components: &mut [Box<dyn Component>],
+    always: bool,
+) {
+    for component in components.iter_mut() {
+        component.update(Action::Tick).unwrap();
+    }
+    if always || components.iter().any(|c| c.is_dirty()) {
+        terminal
+            .draw(|f| {
+                for component in components.iter_mut() {
+                    component.draw(f, f.size()).unwrap();
+                }
+            })
+            .unwrap();
+    }
+}
+
+fn render(c: &mut Criterion) {
+    let mut group = c.benchmark_group("idle frame");
+    for (name, always) in [("every frame", true), ("when dirty", false)] {
+        let mut terminal = Terminal::new(TestBackend::new(160, 50)).unwrap();
+        let mut components = components();
+        group.bench_function(name, |b| {
+            b.iter(|| frame(&mut terminal, &mut components, always))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, render);
+criterion_main!(benches);
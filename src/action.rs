@@ -1,5 +1,5 @@
-use std::collections::HashMap;
-use std::{fmt, string::ToString};
+use crossterm::event::KeyEvent;
+use std::{fmt, path::PathBuf, string::ToString};
 
 use serde::{
     de::{self, Deserializer, Visitor},
@@ -7,6 +7,41 @@ use serde::{
 };
 use strum::Display;
 
+use crate::{
+    components::confirm::Prompt,
+    config::View,
+    fiverr::{Conversation, Gig, Order, OrderRequirements, Thread},
+    history::GigSnapshot,
+    mode::Mode,
+};
+
+/// Where a status message is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    /// On the splash screen, while the app starts up.
+    Startup,
+    /// In the status bar, once the app is running.
+    Running,
+}
+
+/// Whether the browser session is logged in to Fiverr.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuthState {
+    #[default]
+    LoggedOut,
+    LoggedIn,
+}
+
+/// What became of the message to one thread of a bulk message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SendOutcome {
+    Sent,
+    /// Held back until the active hours start, see `active_hours` in the config.
+    Queued,
+    /// Not sent, for the given reason.
+    Failed(String),
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Display, Deserialize)]
 pub enum Action {
     Tick,
@@ -14,10 +49,243 @@ pub enum Action {
     Resize(u16, u16),
     Suspend,
     Resume,
+    /// Quit right away, see `Action::RequestQuit`.
     Quit,
+    /// Quit, asking first if `confirm_quit` is set.
+    RequestQuit,
+    /// Quit but leave geckodriver running for the next launch.
+    Detach,
     Refresh,
     Error(String),
+    /// The URL the browser was on when the scrape behind the `Action::Error` before it failed. Not
+    /// sent while navigations are paused after a rate limit page.
+    ErrorPage(String),
+    /// Open the page of the last error again and save its HTML and a screenshot.
+    ReplayLastError,
+    /// Save a screenshot of the current browser page as a PNG. A relative path is taken from the
+    /// `dumps` directory under the data directory; an existing file is replaced.
+    Screenshot(PathBuf),
+    /// Write the loaded orders (`"orders"`) or conversations (`"messages"`) to a CSV file in the
+    /// `exports` directory under the data directory, see `export_screenshots` in the config.
+    Export(View),
+    /// Show or hide the keybindings of the current mode.
     Help,
-    Message(HashMap<String, String>),
+    Status {
+        phase: Phase,
+        text: String,
+    },
     Fetch,
+    PendingKeys(Vec<KeyEvent>),
+    /// How far a background scrape or send got, e.g. `done: 3, total: Some(10)`.
+    Progress {
+        label: String,
+        done: usize,
+        total: Option<usize>,
+    },
+    Orders(Vec<Order>),
+    Conversations(Vec<Conversation>),
+    Gigs(Vec<Gig>),
+    /// Scraping the view failed or couldn't start; the error, if any, follows as Action::Error.
+    ViewFailed(View),
+    /// Switch to the screen of `Mode`, which also picks the keybindings that apply.
+    SwitchMode(Mode),
+    /// Switch back to the screen shown before the current one, if there was one.
+    Back,
+    /// Show the page at the URL in the browser.
+    OpenPage(String),
+    /// Pause the gig with the given id if it is active, or activate it.
+    ToggleGigStatus(String),
+    /// Scrape the gig with the given id again, e.g. after `GigFailed`.
+    RetryGig(String),
+    GigLoaded(Gig),
+    /// Every stored snapshot of the gigs, oldest first, sent after a scrape was added to it.
+    GigHistory(Vec<GigSnapshot>),
+    GigFailed {
+        id: String,
+        error: String,
+    },
+    ViewRequirements(String),
+    /// Ask to message the buyer of the selected order with the named template from the config's
+    /// `templates`, filled in for the order, as with `Action::BulkMessage`.
+    MessageBuyer(String),
+    RequirementsLoaded(OrderRequirements),
+    /// Scraping the requirements of the order with the given id failed. `R` on the order tries
+    /// again with `ViewRequirements`.
+    RequirementsFailed {
+        order_id: String,
+        error: String,
+    },
+    OpenConversation(String),
+    ThreadLoaded(Thread),
+    /// Ask for a message to send to every thread in the list, to be confirmed as with
+    /// `Action::BulkMessage`.
+    ComposeBulkMessage(Vec<String>),
+    /// Ask for confirmation before sending `text` to every thread in `thread_ids`.
+    BulkMessage {
+        thread_ids: Vec<String>,
+        text: String,
+    },
+    /// Send a confirmed bulk message. Only the confirmation `BulkMessage` asks for sends it, it
+    /// can't be bound to a key.
+    #[serde(skip_deserializing)]
+    SendBulkMessage {
+        thread_ids: Vec<String>,
+        text: String,
+    },
+    BulkMessageSent {
+        thread_id: String,
+        outcome: SendOutcome,
+    },
+    /// Whether it is within the configured active hours, sent when that changes.
+    AutomationActive(bool),
+    /// Arm accepting new orders on their own after a confirmation, or disarm it.
+    ToggleAutoAccept,
+    /// Arm accepting new orders on their own. Only the confirmation `ToggleAutoAccept` asks for
+    /// sends it, it can't be bound to a key.
+    #[serde(skip_deserializing)]
+    ArmAutoAccept,
+    /// Whether new orders are being accepted on their own, see `auto_accept` in the config. Sent
+    /// when that changes, it can't be bound to a key either.
+    #[serde(skip_deserializing)]
+    AutoAcceptArmed(bool),
+    /// Set the splash screen gauge to `percent`, and its message to `text` if given, for
+    /// recording demos. Only works with `demo` set in the config, and isn't listed as a command.
+    DemoProgress {
+        percent: u8,
+        #[serde(default)]
+        text: Option<String>,
+    },
+    /// Switch the seller between online and away on Fiverr.
+    ToggleOnline,
+    /// Whether the seller shows as online on Fiverr, scraped on login and after toggling.
+    Online(bool),
+    OpenCommandPalette,
+    /// Ask for an id to jump to.
+    OpenJump,
+    /// Select the order, conversation or gig with the given id if it is loaded, otherwise open it
+    /// from Fiverr.
+    JumpTo(String),
+    /// Select the item with the given id in the list that has it.
+    SelectItem(String),
+    ToggleLogView,
+    /// Pin the selected item of the focused list to its top, or unpin it.
+    TogglePin,
+    ToggleDensity,
+    /// Show the current view a second time next to itself, or close the second pane.
+    ToggleSplit,
+    EditConfig,
+    ReloadConfig,
+    FocusNext,
+    FocusPrevious,
+    Confirm(Prompt),
+    /// Ask whether to export the current URL and cookies.
+    SnapshotSession,
+    /// Export the current URL and cookies, replacing the values of sensitive cookies if `redact`
+    /// is set. Only the confirmation `SnapshotSession` asks for sends it, it can't be bound to a
+    /// key.
+    #[serde(skip_deserializing)]
+    WriteSessionSnapshot {
+        redact: bool,
+    },
+    /// Load the stored session of the active profile into the browser.
+    RestoreSession,
+    /// The heartbeat found the WebDriver session of the named profile dead.
+    ConnectionLost(String),
+    /// Whether the active profile has a working WebDriver session.
+    WebDriverStatus(bool),
+    /// Fiverr rejected the stored session, or it couldn't be read.
+    SessionExpired,
+    Login,
+    /// Fiverr asked for a one-time code after the credentials were submitted.
+    OtpRequired,
+    /// Enter the one-time code into the login form and finish logging in.
+    SubmitOtp(String),
+    /// Fiverr showed a CAPTCHA on the page with the given URL during login, which has to be
+    /// solved by hand in the browser window.
+    CaptchaDetected(String),
+    /// Log out of Fiverr, keeping the browser running.
+    Logout,
+    Auth(AuthState),
+}
+
+impl Action {
+    /// A progress message for the splash screen.
+    pub fn startup(text: impl Into<String>) -> Self {
+        Action::Status {
+            phase: Phase::Startup,
+            text: text.into(),
+        }
+    }
+
+    /// The actions that can be run on their own, e.g. from the command palette, with a
+    /// description of each.
+    pub fn commands() -> Vec<(Action, &'static str)> {
+        vec![
+            (Action::RequestQuit, "Quit the application"),
+            (Action::Detach, "Quit but keep geckodriver running"),
+            (Action::Suspend, "Suspend the application"),
+            (Action::ToggleLogView, "Show or hide the log"),
+            (Action::Help, "Show or hide the keybindings of this screen"),
+            (Action::Back, "Go back to the previous screen"),
+            (
+                Action::OpenJump,
+                "Jump to an order, conversation or gig by id",
+            ),
+            (Action::TogglePin, "Pin or unpin the selected item"),
+            (
+                Action::ToggleDensity,
+                "Switch tables between one line per item and details",
+            ),
+            (
+                Action::ToggleSplit,
+                "Split the view into two panes, or close the second",
+            ),
+            (Action::Logout, "Log out of Fiverr"),
+            (Action::ToggleOnline, "Switch between online and away"),
+            (
+                Action::ToggleAutoAccept,
+                "Arm or disarm accepting new orders automatically",
+            ),
+            (Action::EditConfig, "Edit the config file"),
+            (Action::ReloadConfig, "Reload the config file"),
+            (Action::FocusNext, "Move focus to the next pane"),
+            (Action::FocusPrevious, "Move focus to the previous pane"),
+            (
+                Action::SnapshotSession,
+                "Export the current page and cookies",
+            ),
+            (
+                Action::ReplayLastError,
+                "Open the page of the last error and save its HTML and a screenshot",
+            ),
+        ]
+    }
+
+    /// What the action does, for lists of keybindings: the description from
+    /// [`Action::commands`], or else its name.
+    pub fn description(&self) -> String {
+        match self {
+            Action::SwitchMode(mode) => return format!("Show {mode:?}"),
+            Action::Export(view) => return format!("Export the {view} to a CSV file"),
+            Action::MessageBuyer(name) => {
+                return format!("Message the buyer of the order with the {name} template")
+            }
+            _ => {}
+        }
+        Action::commands()
+            .into_iter()
+            .find(|(command, _)| command == self)
+            .map_or_else(
+                || self.to_string(),
+                |(_, description)| description.to_string(),
+            )
+    }
+
+    /// A message for the status bar.
+    pub fn status(text: impl Into<String>) -> Self {
+        Action::Status {
+            phase: Phase::Running,
+            text: text.into(),
+        }
+    }
 }
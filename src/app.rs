@@ -1,72 +1,294 @@
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result, WrapErr};
 use crossterm::event::KeyEvent;
-use fantoccini::{Client, ClientBuilder, Locator};
-use futures::sink::Send;
-use ratatui::{layout::Constraint, prelude::*};
+use fantoccini::{Client, Locator};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use ratatui::{
+    layout::Constraint,
+    prelude::*,
+    widgets::{Clear, Paragraph, Wrap},
+};
 use serde::{Deserialize, Serialize};
-use std::process::{Command, Stdio};
-use std::{collections::HashMap, env, sync::Arc};
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    env,
+    sync::Arc,
+};
 use tokio::sync::{
     mpsc::{self, UnboundedSender},
     Mutex,
 };
-use tokio::time::{sleep, Duration};
+use tokio::time::{sleep, Duration, Instant};
 
 use crate::{
-    action::Action,
-    components::{login::LoginComponent, Component},
-    config::Config,
+    action::{Action, AuthState, SendOutcome},
+    browser::{write_creating_dir, Browser, Session},
+    components::{
+        bulk_message::BulkMessageComponent,
+        command_palette::CommandPalette,
+        confirm::{Choice, ConfirmComponent, Prompt},
+        gigs::GigsComponent,
+        help::HelpComponent,
+        home::Home,
+        jump::JumpPrompt,
+        log_view::LogView,
+        login::LoginComponent,
+        messages::MessagesComponent,
+        notifications::Notifications,
+        orders::OrdersComponent,
+        otp::OtpPrompt,
+        requirements::RequirementsComponent,
+        settings::SettingsComponent,
+        status_bar::StatusBar,
+        tab_bar::TabBar,
+        thread::ThreadComponent,
+        Component, Region,
+    },
+    config::{
+        read_env_file, AutoAccept, Config, ExpiredSession, Feedback, FeedbackEvent, OutsideHours,
+        Secret, Theme, View, WebDriverKind,
+    },
+    driver::{self, BrowserOptions, Driver},
+    export::{self, Export},
+    fiverr::{self, Gig, LoginOutcome, Order, StoredSession},
+    history,
     mode::Mode,
-    tui,
+    tasks::Tasks,
+    templates,
+    tui::{self, EventSource},
+    utils::{self, get_config_dir, get_data_dir},
 };
 
+/// How long `Action::Refresh` of the same view is ignored after one, so holding the key doesn't
+/// queue a scrape per key repeat.
+const REFRESH_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// How many screens `Action::Back` can go back.
+const MAX_HISTORY: usize = 32;
+
+/// The profile used when no `--profile` is given.
+pub const DEFAULT_PROFILE: &str = "default";
+
 pub struct App {
     pub config: Config,
+    /// The `--config` file, if one was given.
+    pub config_path: Option<PathBuf>,
     pub tick_rate: f64,
     pub frame_rate: f64,
     pub components: Vec<Box<dyn Component>>,
     pub should_quit: bool,
     pub should_suspend: bool,
     pub mode: Mode,
+    /// The screens shown before the current one, most recent last, see `Action::Back`.
+    pub history: Vec<Mode>,
+    /// Whether the next `Action::SwitchMode` comes from `Action::Back`, so it isn't recorded.
+    going_back: bool,
+    pub auth_state: AuthState,
     pub last_tick_key_events: Vec<KeyEvent>,
+    pub last_key_event_at: Instant,
+    /// Index into `components` of the focusable component that receives key events.
+    pub focused: Option<usize>,
     pub fivver_username: String,
     pub fivver_password: String,
-    pub web_client: Option<Arc<Mutex<Option<Client>>>>,
+    /// The account profile the UI shows.
+    pub profile: String,
+    /// The geckodriver and session of every profile that has been started, by profile name.
+    pub drivers: HashMap<String, Driver>,
+    pub tasks: Tasks,
+    /// Sends `Action::ReloadConfig` when the config file changes; watching stops when dropped.
+    pub config_watcher: Option<RecommendedWatcher>,
+    /// Whether anything happened since the last draw that may have changed the screen.
+    pub dirty: bool,
+    /// Whether the next frame is drawn inverted, see [`Feedback::Flash`].
+    pub flash: bool,
+    /// Ids of the orders in the last scrape, to tell which are new. `None` before the first one.
+    pub known_orders: Option<HashSet<String>>,
+    /// The last scrape of each view, sent again when switching back to it while it is fresh.
+    pub view_cache: HashMap<View, CachedView>,
+    /// Whether it is within `active_hours`, so messages may be sent.
+    pub automation_active: bool,
+    /// Messages held back until the active hours start, see [`OutsideHours::Queue`].
+    pub queued_messages: VecDeque<Action>,
+    /// Whether the app has yet to switch to `startup_mode`, which happens on the first login.
+    pub startup_mode_pending: bool,
+    /// The second pane of the current view, see `Action::ToggleSplit`.
+    pub split: Option<Split>,
+    /// The URL of the page the last failed scrape was on, see `Action::ReplayLastError`.
+    pub last_error_page: Option<String>,
+    /// The view last refreshed with `Action::Refresh`, and when.
+    pub last_refresh: Option<(View, Instant)>,
+    /// Whether new orders are accepted on their own, see [`AutoAccept`].
+    pub auto_accept_armed: bool,
+    /// Set by `--no-color` to draw without colors whatever the config says.
+    pub no_color: bool,
+    /// Set by `--headless` to run the browser without a window whatever the config says.
+    pub headless: bool,
+}
+
+/// A second instance of the current mode's view, drawn to the right of the original with its own
+/// selection and scroll position.
+pub struct Split {
+    pub pane: Box<dyn Component>,
+    /// Whether keys go to the pane rather than the original view.
+    pub pane_focused: bool,
+}
+
+/// What has the keys: one of the focusable components, or the second pane of a split view.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FocusTarget {
+    Component(usize),
+    Pane,
+}
+
+pub struct CachedView {
+    pub scraped_at: Instant,
+    /// The action that delivered the scrape, e.g. `Action::Gigs`.
+    pub action: Action,
 }
 
 impl App {
-    pub fn new(tick_rate: f64, frame_rate: f64) -> Result<Self> {
-        let fivver_username =
-            env::var("FIVVER_USERNAME").expect("FIVER_USERNAME environment variable is not set");
-        let fivver_password =
-            env::var("FIVVER_PASSWORD").expect("FIVVER_PASSWORD environment variable is not set");
+    pub fn new(
+        tick_rate: f64,
+        frame_rate: f64,
+        config: Config,
+        config_path: Option<PathBuf>,
+        profile: String,
+    ) -> Result<Self> {
+        let env_file = get_config_dir().join(".env");
+        let env = env::vars().collect();
+        Self::with_env(
+            tick_rate,
+            frame_rate,
+            config,
+            config_path,
+            profile,
+            &env,
+            &env_file,
+        )
+    }
+
+    /// [`App::new`] with the environment variables `env` and the `.env` file at `env_file`, where
+    /// the Fiverr login is looked up.
+    fn with_env(
+        tick_rate: f64,
+        frame_rate: f64,
+        config: Config,
+        config_path: Option<PathBuf>,
+        profile: String,
+        env: &HashMap<String, String>,
+        env_file: &Path,
+    ) -> Result<Self> {
+        let env_vars = read_env_file(env_file)?;
+        let fivver_username = credential(
+            "FIVVER_USERNAME",
+            env,
+            config.config.fivver_username.as_ref(),
+            &env_vars,
+            env_file,
+        )?;
+        let fivver_password = credential(
+            "FIVVER_PASSWORD",
+            env,
+            config.config.fivver_password.as_ref(),
+            &env_vars,
+            env_file,
+        )?;
         let login = LoginComponent::new();
-        let config = Config::new()?;
+        let home = Home::new();
+        let gigs = GigsComponent::new();
+        let orders = OrdersComponent::new();
+        let messages = MessagesComponent::new();
+        let settings = SettingsComponent::new();
+        let requirements = RequirementsComponent::new();
+        let thread = ThreadComponent::new();
+        let bulk_message = BulkMessageComponent::new();
+        let confirm = ConfirmComponent::new();
+        let command_palette = CommandPalette::new();
+        let jump = JumpPrompt::new();
+        let help = HelpComponent::new();
+        let otp = OtpPrompt::new();
+        let log_view = LogView::new();
+        let status_bar = StatusBar::new();
+        let notifications = Notifications::new();
+        let tab_bar = TabBar::new();
+        let automation_active = config.config.automation_active();
         let mode = Mode::Home;
-        let web_client = None;
 
         Ok(Self {
-            tick_rate,
-            frame_rate,
-            components: vec![Box::new(login)],
+            tick_rate: config.config.tick_rate.unwrap_or(tick_rate),
+            frame_rate: config.config.frame_rate.unwrap_or(frame_rate),
+            components: vec![
+                Box::new(login),
+                Box::new(home),
+                Box::new(gigs),
+                Box::new(orders),
+                Box::new(messages),
+                Box::new(settings),
+                Box::new(log_view),
+                Box::new(requirements),
+                Box::new(thread),
+                Box::new(bulk_message),
+                Box::new(confirm),
+                Box::new(command_palette),
+                Box::new(jump),
+                Box::new(help),
+                Box::new(otp),
+                Box::new(status_bar),
+                Box::new(tab_bar),
+                Box::new(notifications),
+            ],
             should_quit: false,
             should_suspend: false,
             config,
+            config_path,
             mode,
+            history: Vec::new(),
+            going_back: false,
+            auth_state: AuthState::default(),
             last_tick_key_events: Vec::new(),
+            last_key_event_at: Instant::now(),
+            focused: None,
             fivver_username,
             fivver_password,
-            web_client,
+            profile,
+            drivers: HashMap::new(),
+            tasks: Tasks::new(),
+            config_watcher: None,
+            dirty: true,
+            flash: false,
+            known_orders: None,
+            view_cache: HashMap::new(),
+            automation_active,
+            queued_messages: VecDeque::new(),
+            startup_mode_pending: true,
+            split: None,
+            last_error_page: None,
+            last_refresh: None,
+            auto_accept_armed: false,
+            no_color: false,
+            headless: false,
         })
     }
 
+    /// The browser of the active profile, if its driver has been started.
+    pub fn web_client(&self) -> Option<Browser> {
+        self.drivers
+            .get(&self.profile)
+            .map(|driver| driver.browser.clone())
+    }
+
     pub async fn run(&mut self) -> Result<()> {
         let (action_tx, mut action_rx) = mpsc::unbounded_channel();
 
         let mut tui = tui::Tui::new()?
             .tick_rate(self.tick_rate)
-            .frame_rate(self.frame_rate);
-        // tui.mouse(true);
+            .frame_rate(self.frame_rate)
+            .mouse(self.config.config.mouse)
+            .paste(true);
         tui.enter()?;
 
         for component in self.components.iter_mut() {
@@ -80,83 +302,372 @@ impl App {
         for component in self.components.iter_mut() {
             component.init(tui.size()?)?;
         }
+        self.cycle_focus(true);
+        if let Err(e) = self.watch_config(action_tx.clone()) {
+            log::warn!("Not watching the config for changes: {e:#}");
+        }
+        action_tx.send(Action::AutomationActive(self.automation_active))?;
 
         self.fetch_data(action_tx.clone()).await?;
 
         loop {
-            if let Some(e) = tui.next().await {
-                match e {
-                    tui::Event::Quit => action_tx.send(Action::Quit)?,
-                    tui::Event::Tick => action_tx.send(Action::Tick)?,
-                    tui::Event::Render => action_tx.send(Action::Render)?,
-                    tui::Event::Resize(x, y) => action_tx.send(Action::Resize(x, y))?,
-                    tui::Event::Key(key) => {
-                        if let Some(keymap) = self.config.keybindings.get(&self.mode) {
-                            if let Some(action) = keymap.get(&vec![key]) {
-                                log::info!("Got action: {action:?}");
-                                action_tx.send(action.clone())?;
-                            } else {
-                                // If the key was not handled as a single key action,
-                                // then consider it for multi-key combinations.
-                                self.last_tick_key_events.push(key);
-
-                                // Check for multi-key combinations
-                                if let Some(action) = keymap.get(&self.last_tick_key_events) {
-                                    log::info!("Got action: {action:?}");
-                                    action_tx.send(action.clone())?;
-                                }
-                            }
-                        };
-                    }
-                    _ => {}
-                }
-                for component in self.components.iter_mut() {
-                    if let Some(action) = component.handle_events(Some(e.clone()))? {
-                        action_tx.send(action)?;
-                    }
-                }
-            }
+            self.pump(&mut tui, &action_tx).await?;
 
             while let Ok(action) = action_rx.try_recv() {
                 if action != Action::Tick && action != Action::Render {
                     log::debug!("{action:?}");
+                    self.dirty = true;
+                }
+                if let Some(event) = self.feedback_event(&action) {
+                    self.give_feedback(event);
                 }
                 match action {
                     Action::Tick => {
-                        self.last_tick_key_events.drain(..);
+                        let chord_timeout =
+                            Duration::from_millis(self.config.config.chord_timeout_ms);
+                        if !self.last_tick_key_events.is_empty()
+                            && self.last_key_event_at.elapsed() >= chord_timeout
+                        {
+                            self.last_tick_key_events.clear();
+                            action_tx.send(Action::PendingKeys(Vec::new()))?;
+                        }
+                        let active = self.config.config.automation_active();
+                        if active != self.automation_active {
+                            self.set_automation_active(active, &action_tx)?;
+                        }
                     }
-                    Action::Quit => {
+                    Action::Quit | Action::Detach => {
                         self.should_quit = true;
-                        self.close_web_client()
-                            .await
-                            .expect("Failed to close WebDriver client");
+                        // Stop anything still driving the browser before the session goes away.
+                        self.tasks.shutdown().await;
+                        self.close_web_clients(action == Action::Detach).await;
                     }
-                    Action::Suspend => self.should_suspend = true,
-                    Action::Resume => self.should_suspend = false,
-                    Action::Resize(w, h) => {
-                        tui.resize(Rect::new(0, 0, w, h))?;
-                        tui.draw(|f| {
-                            for component in self.components.iter_mut() {
-                                let r = component.draw(f, f.size());
-                                if let Err(e) = r {
-                                    action_tx
-                                        .send(Action::Error(format!("Failed to draw: {:?}", e)))
-                                        .unwrap();
+                    Action::ViewRequirements(ref order_id) => {
+                        let order_id = order_id.clone();
+                        let tx = action_tx.clone();
+                        self.spawn_scrape(action_tx.clone(), move |session| async move {
+                            let requirements = fiverr::fetch_requirements(&session, &order_id)
+                                .await
+                                .wrap_err_with(|| {
+                                    format!("Failed to load requirements for order {order_id}")
+                                });
+                            if let Err(e) = &requirements {
+                                tx.send(Action::RequirementsFailed {
+                                    order_id,
+                                    error: format!("{e:#}"),
+                                })?;
+                            }
+                            Ok(Some(Action::RequirementsLoaded(requirements?)))
+                        });
+                    }
+                    Action::OpenConversation(ref username) => {
+                        let username = username.clone();
+                        self.spawn_scrape(action_tx.clone(), move |session| async move {
+                            let thread = fiverr::fetch_thread(&session, &username)
+                                .await
+                                .wrap_err_with(|| {
+                                    format!("Failed to load the conversation with {username}")
+                                })?;
+                            Ok(Some(Action::ThreadLoaded(thread)))
+                        });
+                    }
+                    Action::SendBulkMessage { .. } if !self.automation_active => {
+                        self.hold_back_message(action.clone(), &action_tx)?
+                    }
+                    Action::SendBulkMessage {
+                        ref thread_ids,
+                        ref text,
+                    } => {
+                        self.send_bulk_message(thread_ids.clone(), text.clone(), action_tx.clone())
+                    }
+                    Action::RequestQuit if self.config.config.confirm_quit => {
+                        action_tx.send(Action::Confirm(Prompt {
+                            title: "Quit".to_string(),
+                            text: "Quit stash and close the browser session?".to_string(),
+                            choices: vec![
+                                Choice::new('y', "quit", Some(Action::Quit)),
+                                Choice::new('n', "stay", None),
+                            ],
+                        }))?;
+                    }
+                    Action::RequestQuit => action_tx.send(Action::Quit)?,
+                    Action::SnapshotSession => {
+                        action_tx.send(Action::Confirm(Prompt {
+                            title: "Session snapshot".to_string(),
+                            text: "Export the current URL and cookies to the data directory? \
+                                   Cookies grant access to your Fiverr account."
+                                .to_string(),
+                            choices: vec![
+                                Choice::new(
+                                    'y',
+                                    "export",
+                                    Some(Action::WriteSessionSnapshot { redact: false }),
+                                ),
+                                Choice::new(
+                                    'r',
+                                    "export redacted",
+                                    Some(Action::WriteSessionSnapshot { redact: true }),
+                                ),
+                                Choice::new('n', "cancel", None),
+                            ],
+                        }))?;
+                    }
+                    Action::WriteSessionSnapshot { redact } => {
+                        let data_dir = self.config.config._data_dir.clone();
+                        self.spawn_scrape(action_tx.clone(), move |session| async move {
+                            let snapshot = session.snapshot(redact).await?;
+                            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+                            let path = data_dir.join(format!("session-{timestamp}.json"));
+                            std::fs::create_dir_all(&data_dir)?;
+                            std::fs::write(&path, serde_json::to_string_pretty(&snapshot)?)?;
+                            log::info!("Saved session snapshot to {}", path.display());
+                            Ok(Some(Action::status(format!(
+                                "Saved session snapshot to {}",
+                                path.display()
+                            ))))
+                        });
+                    }
+                    Action::ErrorPage(ref url) => self.last_error_page = Some(url.clone()),
+                    Action::ReplayLastError => self.replay_last_error(&action_tx)?,
+                    Action::Screenshot(ref path) => self.screenshot(path, &action_tx),
+                    Action::Export(view) => self.export(view, &action_tx)?,
+                    Action::RestoreSession => {
+                        let path = self.session_file();
+                        self.spawn_scrape(action_tx.clone(), move |session| async move {
+                            let snapshot = match fiverr::load_session(&path) {
+                                StoredSession::Found(snapshot) => snapshot,
+                                StoredSession::Missing => {
+                                    // A persistent browser profile may still be logged in.
+                                    session.navigate(&fiverr::seller_dashboard_url()).await?;
+                                    return Ok(Some(if fiverr::is_logged_in(&session).await? {
+                                        log::info!("Still logged in from the browser profile");
+                                        Action::Auth(AuthState::LoggedIn)
+                                    } else {
+                                        Action::Login
+                                    }));
+                                }
+                                StoredSession::Invalid(reason) => {
+                                    log::warn!(
+                                        "Can't use stored session {}: {reason}",
+                                        path.display()
+                                    );
+                                    return Ok(Some(Action::SessionExpired));
                                 }
+                            };
+                            session.restore(&snapshot).await?;
+                            if fiverr::is_logged_in(&session).await? {
+                                log::info!("Restored session from {}", path.display());
+                                Ok(Some(Action::Auth(AuthState::LoggedIn)))
+                            } else {
+                                log::warn!("Fiverr rejected the stored session {}", path.display());
+                                Ok(Some(Action::SessionExpired))
                             }
-                        })?;
-                    }
-                    Action::Render => {
-                        tui.draw(|f| {
-                            for component in self.components.iter_mut() {
-                                let r = component.draw(f, f.size());
-                                if let Err(e) = r {
-                                    action_tx
-                                        .send(Action::Error(format!("Failed to draw: {:?}", e)))
-                                        .unwrap();
+                        });
+                    }
+                    Action::SessionExpired => self.expire_session(&action_tx)?,
+                    Action::Login => {
+                        let headless = self.headless();
+                        let username = self.fivver_username.clone();
+                        let password = self.fivver_password.clone();
+                        let timeout = Duration::from_secs(self.config.config.login_timeout_secs);
+                        let tx = action_tx.clone();
+                        self.spawn_scrape(action_tx.clone(), move |session| async move {
+                            let outcome =
+                                fiverr::login(&session, &username, &password, timeout, |step| {
+                                    let _ = tx.send(Action::startup(step));
+                                })
+                                .await?;
+                            Ok(Some(login_action(outcome, headless, &tx)))
+                        });
+                    }
+                    Action::CaptchaDetected(ref url) => {
+                        log::warn!("Fiverr shows a CAPTCHA at {url}");
+                        let headless = self.headless();
+                        let timeout = Duration::from_secs(self.config.config.captcha_timeout_secs);
+                        let tx = action_tx.clone();
+                        self.spawn_scrape(action_tx.clone(), move |session| async move {
+                            let outcome = fiverr::wait_for_captcha(&session, timeout).await?;
+                            Ok(Some(login_action(outcome, headless, &tx)))
+                        });
+                    }
+                    Action::SubmitOtp(ref code) => {
+                        let code = code.clone();
+                        let timeout = Duration::from_secs(self.config.config.login_timeout_secs);
+                        let tx = action_tx.clone();
+                        self.spawn_scrape(action_tx.clone(), move |session| async move {
+                            let _ = tx.send(Action::startup("Submitting the one-time code..."));
+                            fiverr::submit_otp(&session, &code, timeout).await?;
+                            log::info!("Logged in with a one-time code");
+                            Ok(Some(Action::Auth(AuthState::LoggedIn)))
+                        });
+                    }
+                    Action::Logout => {
+                        self.remove_session_file();
+                        self.spawn_scrape(action_tx.clone(), |session| async move {
+                            fiverr::logout(&session).await?;
+                            log::info!("Logged out");
+                            Ok(Some(Action::Auth(AuthState::LoggedOut)))
+                        });
+                    }
+                    Action::Auth(auth_state) => {
+                        self.auth_state = auth_state;
+                        let status = match auth_state {
+                            AuthState::LoggedIn => "Logged in",
+                            AuthState::LoggedOut => "Logged out",
+                        };
+                        action_tx.send(Action::status(status))?;
+                        if auth_state == AuthState::LoggedIn {
+                            // So the next launch doesn't have to log in again.
+                            let path = self.session_file();
+                            self.spawn_scrape(action_tx.clone(), |session| async move {
+                                match fiverr::save_session(&session, &path).await {
+                                    Ok(()) => log::info!("Saved the session to {}", path.display()),
+                                    Err(e) => log::error!(
+                                        "Failed to save the session to {}: {e:#}",
+                                        path.display()
+                                    ),
                                 }
+                                Ok(Some(Action::Online(fiverr::fetch_online(&session).await?)))
+                            });
+                        }
+                        if auth_state == AuthState::LoggedOut {
+                            // Nothing scraped for the previous account may show or act for the
+                            // next one.
+                            self.view_cache.clear();
+                            self.known_orders = None;
+                            if self.auto_accept_armed {
+                                self.set_auto_accept_armed(false, &action_tx)?;
+                            }
+                            self.startup_mode_pending = true;
+                            self.history.clear();
+                            if self.mode != Mode::Home {
+                                action_tx.send(Action::SwitchMode(Mode::Home))?;
                             }
-                        })?;
+                        }
+                        if auth_state == AuthState::LoggedIn && self.startup_mode_pending {
+                            self.startup_mode_pending = false;
+                            let mode = self.config.config.startup_mode;
+                            if mode != self.mode {
+                                action_tx.send(Action::SwitchMode(mode))?;
+                            }
+                        }
+                    }
+                    Action::SwitchMode(mode) => {
+                        self.record_history(mode);
+                        self.close_split();
+                        self.enter_mode(mode, &action_tx)?;
+                        self.last_tick_key_events.clear();
+                    }
+                    Action::Back => {
+                        if let Some(mode) = self.history.pop() {
+                            self.going_back = true;
+                            action_tx.send(Action::SwitchMode(mode))?;
+                        }
+                    }
+                    Action::Refresh => {
+                        if let Some(view) = self.mode.view() {
+                            self.refresh(view, &action_tx)?;
+                        }
+                    }
+                    Action::Gigs(ref gigs) => {
+                        self.record_gig_history(gigs, &action_tx);
+                        self.cache_view(View::Gigs, action.clone())
+                    }
+                    Action::Orders(ref orders) => {
+                        self.accept_new_orders(orders, &action_tx);
+                        self.cache_view(View::Orders, action.clone())
+                    }
+                    Action::ToggleOnline => {
+                        self.spawn_scrape(action_tx.clone(), |session| async move {
+                            let online = fiverr::toggle_online(&session).await?;
+                            log::info!("Now {}", if online { "online" } else { "away" });
+                            Ok(Some(Action::Online(online)))
+                        });
+                    }
+                    Action::ToggleAutoAccept => self.toggle_auto_accept(&action_tx)?,
+                    Action::ArmAutoAccept => self.arm_auto_accept(&action_tx)?,
+                    Action::Conversations(_) => self.cache_view(View::Messages, action.clone()),
+                    Action::GigLoaded(_) => {
+                        // The cached list has the old version of the gig.
+                        self.view_cache.remove(&View::Gigs);
+                    }
+                    Action::OpenPage(ref url) => {
+                        let url = url.clone();
+                        self.spawn_scrape(action_tx.clone(), move |session| async move {
+                            session.navigate(&url).await?;
+                            Ok(None)
+                        });
+                    }
+                    Action::ToggleGigStatus(ref gig_id) => {
+                        let username = self.fivver_username.clone();
+                        let id = gig_id.clone();
+                        self.spawn_scrape(action_tx.clone(), move |session| async move {
+                            let action =
+                                match fiverr::toggle_gig_status(&session, &username, &id).await {
+                                    Ok(gigs) => Action::Gigs(gigs),
+                                    Err(e) => gig_failed(id, "toggle", e),
+                                };
+                            Ok(Some(action))
+                        });
+                    }
+                    Action::RetryGig(ref gig_id) => {
+                        let username = self.fivver_username.clone();
+                        let id = gig_id.clone();
+                        self.spawn_scrape(action_tx.clone(), move |session| async move {
+                            let action = match fiverr::fetch_gig(&session, &username, &id).await {
+                                Ok(gig) => Action::GigLoaded(gig),
+                                Err(e) => gig_failed(id, "load", e),
+                            };
+                            Ok(Some(action))
+                        });
+                    }
+                    Action::ConnectionLost(ref profile) => {
+                        if *profile == self.profile {
+                            action_tx.send(Action::WebDriverStatus(false))?;
+                        }
+                        action_tx.send(Action::Error(format!(
+                            "Lost the connection to WebDriver (profile {profile})"
+                        )))?;
+                    }
+                    Action::ToggleSplit => self.toggle_split(&action_tx)?,
+                    Action::JumpTo(ref id) => self.jump_to(id, &action_tx)?,
+                    Action::FocusNext => self.cycle_focus(true),
+                    Action::FocusPrevious => self.cycle_focus(false),
+                    Action::EditConfig => {
+                        tui.exit()?;
+                        let result = self.edit_config();
+                        tui.resume()?;
+                        match result {
+                            Ok(()) => action_tx.send(Action::ReloadConfig)?,
+                            Err(e) => action_tx.send(Action::Error(format!("{e:#}")))?,
+                        }
+                    }
+                    Action::ReloadConfig => match self.reload_config() {
+                        Ok(()) => {
+                            // A chord typed under the old keybindings may not exist anymore.
+                            self.last_tick_key_events.clear();
+                            action_tx.send(Action::PendingKeys(Vec::new()))?;
+                            action_tx.send(Action::status("Reloaded config"))?;
+                            if self.auto_accept_armed && !self.config.config.auto_accept.enabled {
+                                self.set_auto_accept_armed(false, &action_tx)?;
+                            }
+                        }
+                        Err(e) => action_tx.send(Action::Error(format!("{e:#}")))?,
+                    },
+                    Action::Suspend => self.should_suspend = true,
+                    Action::Resume => {
+                        self.should_suspend = false;
+                        self.draw(&mut tui, &action_tx)?;
+                    }
+                    Action::Resize(w, h) => {
+                        tui.resize(Rect::new(0, 0, w, h))?;
+                        self.draw(&mut tui, &action_tx)?;
+                    }
+                    // Idle frames are skipped, the monitor is often left open all day.
+                    Action::Render
+                        if self.dirty || self.components.iter().any(|c| c.is_dirty()) =>
+                    {
+                        self.draw(&mut tui, &action_tx)?;
                     }
                     _ => {}
                 }
@@ -165,15 +676,25 @@ impl App {
                         action_tx.send(action)?
                     };
                 }
+                if let Some(split) = &mut self.split {
+                    if let Some(action) = split.pane.update(action.clone())? {
+                        action_tx.send(action)?
+                    };
+                }
+                self.refocus();
+                // After the components have switched, so a cached view isn't reset by the switch.
+                if let Action::SwitchMode(mode) = action {
+                    if let Some(view) = mode.view() {
+                        self.show_view(view, action_tx.clone())?;
+                    }
+                }
             }
             if self.should_suspend {
                 tui.suspend()?;
+                // Resume the same terminal rather than a new one, so events queued meanwhile aren't
+                // dropped along with the old event channel.
+                tui.resume()?;
                 action_tx.send(Action::Resume)?;
-                tui = tui::Tui::new()?
-                    .tick_rate(self.tick_rate)
-                    .frame_rate(self.frame_rate);
-                // tui.mouse(true);
-                tui.enter()?;
             } else if self.should_quit {
                 tui.stop()?;
                 break;
@@ -183,61 +704,1692 @@ impl App {
         Ok(())
     }
 
-    async fn fetch_data(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
-        let mut message1 = HashMap::new();
-        message1.insert("startup".to_string(), "Starting Geckodriver...".to_string());
-        tx.send(Action::Message(message1))?;
+    /// Take the next event from `events` and hand it to the key bindings and components.
+    /// Returns whether there was one.
+    pub async fn pump(
+        &mut self,
+        events: &mut impl EventSource,
+        action_tx: &UnboundedSender<Action>,
+    ) -> Result<bool> {
+        let Some(e) = events.next().await else {
+            return Ok(false);
+        };
+        // Popups are drawn in order, so the last one that captures keys is the one on top.
+        let capturing = self.components.iter().rposition(|c| c.captures_keys());
+        // Whether the keybindings used the key, so the components shouldn't handle it as well.
+        let mut consumed = false;
+        match e {
+            tui::Event::Quit => action_tx.send(Action::Quit)?,
+            tui::Event::Tick => action_tx.send(Action::Tick)?,
+            tui::Event::Render => action_tx.send(Action::Render)?,
+            tui::Event::Resize(x, y) => action_tx.send(Action::Resize(x, y))?,
+            tui::Event::Key(key) if capturing.is_none() => {
+                consumed = self.handle_key_event(key, action_tx)?
+            }
+            _ => {}
+        }
+        if !matches!(e, tui::Event::Tick | tui::Event::Render) {
+            self.dirty = true;
+        }
+        // Pasted text goes where typed keys would.
+        let is_key = matches!(e, tui::Event::Key(_) | tui::Event::Paste(_));
+        let pane_focused = self.focus_target() == Some(FocusTarget::Pane);
+        for (i, component) in self.components.iter_mut().enumerate() {
+            let receives_keys = match capturing {
+                Some(capturing) => capturing == i,
+                None => !component.is_focusable() || self.focused == Some(i),
+            };
+            if is_key && (consumed || !receives_keys) {
+                continue;
+            }
+            if let Some(action) = component.handle_events(Some(e.clone()))? {
+                action_tx.send(action)?;
+            }
+        }
+        if let Some(split) = &mut self.split {
+            if !is_key || (pane_focused && capturing.is_none() && !consumed) {
+                if let Some(action) = split.pane.handle_events(Some(e.clone()))? {
+                    action_tx.send(action)?;
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Look `key` up in the keybindings, alone or as the next key of a chord. Returns whether
+    /// it was used, i.e. it completed a binding or is part of a chord still being typed.
+    fn handle_key_event(
+        &mut self,
+        key: KeyEvent,
+        action_tx: &UnboundedSender<Action>,
+    ) -> Result<bool> {
+        let keybindings = &self.config.keybindings;
+        if let Some(action) = keybindings.action(self.mode, &[key]) {
+            log::info!("Got action: {action:?}");
+            action_tx.send(action.clone())?;
+            return Ok(true);
+        }
+
+        // If the key was not handled as a single key action,
+        // then consider it for multi-key combinations.
+        self.last_tick_key_events.push(key);
+        self.last_key_event_at = Instant::now();
+        let is_prefix = |keys: &[KeyEvent]| keybindings.is_prefix(self.mode, keys);
+
+        let mut consumed = true;
+        if let Some(action) = keybindings.action(self.mode, &self.last_tick_key_events) {
+            log::info!("Got action: {action:?}");
+            action_tx.send(action.clone())?;
+            self.last_tick_key_events.clear();
+        } else if !is_prefix(&self.last_tick_key_events) {
+            // The chord so far leads nowhere, but this key may start a new one.
+            self.last_tick_key_events.clear();
+            consumed = is_prefix(&[key]);
+            if consumed {
+                self.last_tick_key_events.push(key);
+            }
+        }
+        action_tx.send(Action::PendingKeys(self.last_tick_key_events.clone()))?;
+        Ok(consumed)
+    }
+
+    /// Whether the browser runs without a window.
+    fn headless(&self) -> bool {
+        self.headless || self.config.config.headless_enabled()
+    }
+
+    /// Where the active profile's session is stored between runs.
+    fn session_file(&self) -> PathBuf {
+        self.config
+            .config
+            ._data_dir
+            .join("sessions")
+            .join(format!("{}.json", self.profile))
+    }
+
+    /// Forget the stored session Fiverr rejected and log in again, or ask first.
+    fn expire_session(&self, tx: &UnboundedSender<Action>) -> Result<()> {
+        self.remove_session_file();
+        match self.config.config.expired_session {
+            ExpiredSession::Login => {
+                log::info!("Stored session expired, falling back to logging in");
+                tx.send(Action::Login)?;
+            }
+            ExpiredSession::Confirm => {
+                tx.send(Action::Confirm(Prompt {
+                    title: "Session expired".to_string(),
+                    text: "Fiverr no longer accepts the stored session. Log in with your \
+                           credentials?"
+                        .to_string(),
+                    choices: vec![
+                        Choice::new('y', "log in", Some(Action::Login)),
+                        Choice::new('n', "cancel", None),
+                    ],
+                }))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_session_file(&self) {
+        let path = self.session_file();
+        match std::fs::remove_file(&path) {
+            Ok(()) => log::info!("Removed stored session {}", path.display()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => log::warn!("Failed to remove {}: {e}", path.display()),
+        }
+    }
+
+    /// The file `EditConfig` opens: the `--config` file, or the first config file in the config
+    /// directory, or a new `config.json5` there.
+    fn config_file(&self) -> Result<PathBuf> {
+        if let Some(path) = &self.config_path {
+            return Ok(path.clone());
+        }
+        let config_dir = utils::get_config_dir();
+        std::fs::create_dir_all(&config_dir)?;
+        let existing = [
+            "config.json5",
+            "config.json",
+            "config.yaml",
+            "config.toml",
+            "config.ini",
+        ]
+        .iter()
+        .map(|file| config_dir.join(file))
+        .find(|path| path.exists());
+        Ok(existing.unwrap_or_else(|| config_dir.join("config.json5")))
+    }
+
+    /// Open the config file in `$VISUAL`/`$EDITOR` and wait for the editor to exit. Expects the
+    /// terminal to have been handed back to the shell.
+    fn edit_config(&mut self) -> Result<()> {
+        let path = self.config_file()?;
+        let editor = env::var("VISUAL")
+            .or_else(|_| env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".to_string());
+        let mut args = editor.split_whitespace();
+        let program = args.next().unwrap_or("vi");
+        let status = Command::new(program)
+            .args(args)
+            .arg(&path)
+            .status()
+            .wrap_err_with(|| format!("Failed to launch editor `{editor}`"))?;
+        if !status.success() {
+            return Err(eyre!("Editor `{editor}` exited with {status}"));
+        }
+        Ok(())
+    }
+
+    /// Watch the config file, or the config directory when no `--config` file was given, and send
+    /// `Action::ReloadConfig` whenever a config file in it changes.
+    fn watch_config(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        let (dir, file) = match &self.config_path {
+            Some(path) => {
+                let path = std::fs::canonicalize(path)?;
+                let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+                (dir, Some(path))
+            }
+            None => (utils::get_config_dir(), None),
+        };
+        if !dir.is_dir() {
+            return Ok(());
+        }
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+                Ok(event) if is_config_change(&event, file.as_deref()) => {
+                    let _ = tx.send(Action::ReloadConfig);
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("Error watching config: {e}"),
+            })?;
+        // Watch the directory rather than the file: editors often save by replacing the file,
+        // which would end a watch on the file itself.
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        self.config_watcher = Some(watcher);
+        Ok(())
+    }
+
+    /// Load the config again and hand it to every component. If the new config doesn't parse,
+    /// the current one stays in effect.
+    fn reload_config(&mut self) -> Result<()> {
+        let mut config = Config::new(self.config_path.clone())
+            .wrap_err("Config has errors, keeping the previous one")?;
+        config.log_load_messages();
+        for component in self.components.iter_mut() {
+            component.register_config_handler(config.clone())?;
+        }
+        if let Some(split) = &mut self.split {
+            split.pane.register_config_handler(config.clone())?;
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    /// Move focus to the next (or previous) focusable component, wrapping around. The second pane
+    /// of a split view comes right after the view it splits.
+    fn cycle_focus(&mut self, forward: bool) {
+        let mut focusable: Vec<FocusTarget> = self
+            .components
+            .iter()
+            .enumerate()
+            .filter(|(_, component)| component.is_focusable())
+            .map(|(i, _)| FocusTarget::Component(i))
+            .collect();
+        if self.split.is_some() {
+            let view = focusable.iter().position(|target| match target {
+                FocusTarget::Component(i) => self.components[*i].view_mode() == Some(self.mode),
+                FocusTarget::Pane => false,
+            });
+            focusable.insert(view.map_or(focusable.len(), |p| p + 1), FocusTarget::Pane);
+        }
+        let position = self
+            .focus_target()
+            .and_then(|focused| focusable.iter().position(|&target| target == focused));
+        let next = match position {
+            _ if focusable.is_empty() => None,
+            None => Some(focusable[0]),
+            Some(p) if forward => Some(focusable[(p + 1) % focusable.len()]),
+            Some(p) => Some(focusable[(p + focusable.len() - 1) % focusable.len()]),
+        };
+        self.focus(next);
+    }
+
+    /// The component or pane that has the keys.
+    fn focus_target(&self) -> Option<FocusTarget> {
+        match &self.split {
+            Some(split) if split.pane_focused => Some(FocusTarget::Pane),
+            _ => self.focused.map(FocusTarget::Component),
+        }
+    }
+
+    /// Give the keys to `target`, taking them from whatever had them.
+    fn focus(&mut self, target: Option<FocusTarget>) {
+        if let Some(previous) = self.focused.take() {
+            self.components[previous].set_focus(false);
+        }
+        if let Some(split) = &mut self.split {
+            split.pane_focused = false;
+            split.pane.set_focus(false);
+        }
+        match target {
+            Some(FocusTarget::Component(i)) => {
+                self.components[i].set_focus(true);
+                self.focused = Some(i);
+            }
+            Some(FocusTarget::Pane) => {
+                if let Some(split) = &mut self.split {
+                    split.pane_focused = true;
+                    split.pane.set_focus(true);
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Move focus to the first focusable component if the focused one can't have it anymore, e.g.
+    /// because its view was switched away from.
+    fn refocus(&mut self) {
+        let focusable = match self.focus_target() {
+            Some(FocusTarget::Component(i)) => self.components[i].is_focusable(),
+            Some(FocusTarget::Pane) => true,
+            None => false,
+        };
+        if !focusable {
+            self.cycle_focus(true);
+        }
+    }
+
+    fn draw(&mut self, tui: &mut tui::Tui, action_tx: &UnboundedSender<Action>) -> Result<()> {
+        self.dirty = false;
+        let color_enabled = !self.no_color && self.config.config.color_enabled();
+        tui.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(1),
+                    Constraint::Min(0),
+                    Constraint::Length(1),
+                ])
+                .split(f.size());
+            let mode = self.mode;
+            for component in self.components.iter_mut() {
+                if !is_drawn_in(component.as_ref(), mode) {
+                    continue;
+                }
+                let area = match component.region() {
+                    Region::TabLine => chunks[0],
+                    Region::Main => chunks[1],
+                    Region::StatusLine => chunks[2],
+                };
+                match &mut self.split {
+                    Some(split) if component.view_mode() == Some(self.mode) => {
+                        let [left, right] =
+                            Layout::horizontal([Constraint::Percentage(50); 2]).areas(area);
+                        draw_component(f, component.as_mut(), left, action_tx);
+                        draw_component(f, split.pane.as_mut(), right, action_tx);
+                    }
+                    _ => draw_component(f, component.as_mut(), area, action_tx),
+                }
+            }
+            if color_enabled {
+                apply_theme(f.buffer_mut(), &self.config.config.theme);
+            } else {
+                strip_colors(f.buffer_mut());
+            }
+            if self.flash {
+                let area = f.size();
+                f.buffer_mut().set_style(area, Style::new().reversed());
+            }
+        })?;
+        if self.flash {
+            // Draw the screen normally again on the next frame.
+            self.flash = false;
+            self.dirty = true;
+        }
+        Ok(())
+    }
+
+    /// The feedback event `action` stands for, if any.
+    fn feedback_event(&self, action: &Action) -> Option<FeedbackEvent> {
+        match action {
+            Action::Error(_) => Some(FeedbackEvent::Error),
+            Action::BulkMessageSent {
+                outcome: SendOutcome::Sent,
+                ..
+            } => Some(FeedbackEvent::MessageSent),
+            Action::Orders(orders) => {
+                let known = self.known_orders.as_ref()?;
+                let new = orders.iter().any(|order| !known.contains(&order.id));
+                new.then_some(FeedbackEvent::NewOrder)
+            }
+            _ => None,
+        }
+    }
+
+    /// Remember the ids of `orders` and accept the new ones that `auto_accept` allows, if armed.
+    fn accept_new_orders(&mut self, orders: &[Order], tx: &UnboundedSender<Action>) {
+        for order in self.orders_to_accept(orders) {
+            log::warn!(
+                "Auto-accepting order {} from {} for gig {:?}",
+                order.id,
+                order.buyer,
+                order.gig
+            );
+            self.spawn_scrape(tx.clone(), move |session| async move {
+                fiverr::accept_order(&session, &order.id)
+                    .await
+                    .wrap_err_with(|| format!("Failed to auto-accept order {}", order.id))?;
+                log::warn!("Auto-accepted order {}", order.id);
+                Ok(Some(Action::status(format!(
+                    "Auto-accepted order {} from {}",
+                    order.id, order.buyer
+                ))))
+            });
+        }
+    }
+
+    /// Remember the ids of `orders` and return the new ones to accept. Nothing is accepted on the
+    /// first scrape, since nothing is known to be new then, nor outside of the active hours: like
+    /// sending messages, accepting is a write. Orders that arrive then are left to the seller.
+    fn orders_to_accept(&mut self, orders: &[Order]) -> Vec<Order> {
+        let ids = orders.iter().map(|order| order.id.clone()).collect();
+        let Some(known) = self.known_orders.replace(ids) else {
+            return Vec::new();
+        };
+        if !self.auto_accept_armed {
+            return Vec::new();
+        }
+        let auto_accept = &self.config.config.auto_accept;
+        let new: Vec<Order> = orders
+            .iter()
+            .filter(|order| !known.contains(&order.id) && auto_accept.allows(order))
+            .cloned()
+            .collect();
+        if !self.automation_active && !new.is_empty() {
+            log::info!(
+                "Not auto-accepting {} new order(s) outside of the active hours",
+                new.len()
+            );
+            return Vec::new();
+        }
+        new
+    }
+
+    /// Disarm auto-accepting orders, or ask to arm it if the config enables it.
+    fn toggle_auto_accept(&mut self, tx: &UnboundedSender<Action>) -> Result<()> {
+        if self.auto_accept_armed {
+            return self.set_auto_accept_armed(false, tx);
+        }
+        let auto_accept = &self.config.config.auto_accept;
+        if !auto_accept.enabled {
+            tx.send(Action::status(
+                "Auto-accept is off, enable auto_accept in the config first",
+            ))?;
+            return Ok(());
+        }
+        tx.send(Action::Confirm(Prompt {
+            title: "Arm auto-accept".to_string(),
+            text: format!(
+                "New orders of these gigs will be accepted WITHOUT ASKING until disarmed: {}",
+                auto_accept.gigs.join(", ")
+            ),
+            choices: vec![
+                Choice::new('y', "arm", Some(Action::ArmAutoAccept)),
+                Choice::new('n', "cancel", None),
+            ],
+        }))?;
+        Ok(())
+    }
+
+    /// Arm auto-accepting orders once the prompt of `toggle_auto_accept` was confirmed, unless
+    /// the config was changed to disable it meanwhile.
+    fn arm_auto_accept(&mut self, tx: &UnboundedSender<Action>) -> Result<()> {
+        if !self.config.config.auto_accept.enabled {
+            tx.send(Action::status("Auto-accept is off, not arming it"))?;
+            return Ok(());
+        }
+        self.set_auto_accept_armed(true, tx)
+    }
+
+    fn set_auto_accept_armed(&mut self, armed: bool, tx: &UnboundedSender<Action>) -> Result<()> {
+        self.auto_accept_armed = armed;
+        log::warn!("Auto-accept {}", if armed { "armed" } else { "disarmed" });
+        tx.send(Action::AutoAcceptArmed(armed))?;
+        Ok(())
+    }
+
+    fn give_feedback(&mut self, event: FeedbackEvent) {
+        let Some(feedback) = self.config.config.feedback.get(&event) else {
+            return;
+        };
+        for feedback in feedback {
+            match feedback {
+                Feedback::Flash => {
+                    self.flash = true;
+                    self.dirty = true;
+                }
+                Feedback::Bell => {
+                    let mut stdout = std::io::stdout();
+                    let _ = stdout.write_all(b"\x07").and_then(|()| stdout.flush());
+                }
+            }
+        }
+    }
+
+    /// Run `scrape` against the browser in the background, sending the action it produces or an
+    /// `Action::Error` if it fails.
+    fn spawn_scrape<F, Fut>(&mut self, tx: UnboundedSender<Action>, scrape: F)
+    where
+        F: FnOnce(Session) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Option<Action>>> + Send,
+    {
+        self.spawn_scrape_or(tx, None, scrape);
+    }
+
+    /// [`App::spawn_scrape`] for a list view, which also hears about a scrape that failed or
+    /// couldn't start, so it stops showing itself as loading.
+    fn scrape_view<F, Fut>(&mut self, view: View, tx: UnboundedSender<Action>, scrape: F)
+    where
+        F: FnOnce(Session) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Option<Action>>> + Send,
+    {
+        self.spawn_scrape_or(tx, Some(Action::ViewFailed(view)), scrape);
+    }
+
+    /// [`App::spawn_scrape`], sending `failed` too if the scrape fails or there is no browser.
+    fn spawn_scrape_or<F, Fut>(
+        &mut self,
+        tx: UnboundedSender<Action>,
+        failed: Option<Action>,
+        scrape: F,
+    ) where
+        F: FnOnce(Session) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Option<Action>>> + Send,
+    {
+        let Some(browser) = self.web_client() else {
+            log::warn!("Ignoring scrape, WebDriver is not running");
+            if let Some(failed) = failed {
+                let _ = tx.send(failed);
+            }
+            return;
+        };
+        self.tasks.spawn(async move {
+            let result = match browser.session().await {
+                Ok(session) => scrape(session).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(Some(action)) => {
+                    let _ = tx.send(action);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    if let Some(failed) = failed {
+                        let _ = tx.send(failed);
+                    }
+                    let _ = tx.send(Action::Error(format!("{e:#}")));
+                    // Not worth waiting out a rate limit pause for.
+                    if browser.is_paused() {
+                        return;
+                    }
+                    if let Ok(session) = browser.session().await {
+                        if let Ok(url) = session.current_url().await {
+                            let _ = tx.send(Action::ErrorPage(url.to_string()));
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Open the page the last failed scrape was on and save its HTML and a screenshot to the
+    /// `dumps` directory under the data directory, to look at the state that made it fail.
+    fn replay_last_error(&mut self, tx: &UnboundedSender<Action>) -> Result<()> {
+        let Some(url) = self.last_error_page.clone() else {
+            tx.send(Action::status("No error to replay"))?;
+            return Ok(());
+        };
+        let dir = self.dumps_dir();
+        self.spawn_scrape(tx.clone(), move |session| async move {
+            session.navigate(&url).await?;
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            let (html, png) = session
+                .dump_page(&dir, &format!("error-{timestamp}"))
+                .await?;
+            log::info!("Saved {url} to {} and {}", html.display(), png.display());
+            Ok(Some(Action::status(format!(
+                "Saved the page of the last error to {}",
+                html.display()
+            ))))
+        });
+        Ok(())
+    }
+
+    /// Where screenshots and page dumps are saved.
+    fn dumps_dir(&self) -> PathBuf {
+        self.config.config._data_dir.join("dumps")
+    }
 
-        if self.web_client.is_none() {
-            self.init_web_client().await?;
+    /// Write the loaded orders or conversations of `view` to a CSV file in the `exports` directory
+    /// under the data directory.
+    fn export(&mut self, view: View, tx: &UnboundedSender<Action>) -> Result<()> {
+        match self
+            .view_cache
+            .get(&view)
+            .map(|cached| cached.action.clone())
+        {
+            Some(Action::Orders(orders)) => self.spawn_export(orders, tx.clone()),
+            Some(Action::Conversations(conversations)) => {
+                self.spawn_export(conversations, tx.clone())
+            }
+            _ => tx.send(Action::status(format!(
+                "Load the {view} before exporting them"
+            )))?,
         }
         Ok(())
     }
 
-    async fn is_geckodriver_running(&self) -> bool {
-        if let Ok(output) = Command::new("pgrep").arg("geckodriver").output() {
-            !output.stdout.is_empty()
+    /// Write `items` to `<name>-<timestamp>.csv`. With `export_screenshots` set, each item's page is
+    /// opened and saved next to it in `<name>-<timestamp>/<id>.png` first, and the CSV lists the
+    /// screenshots.
+    fn spawn_export<T: Export + Send + Sync + 'static>(
+        &mut self,
+        items: Vec<T>,
+        tx: UnboundedSender<Action>,
+    ) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let name = format!("{}-{timestamp}", T::NAME);
+        let dir = self.config.config._data_dir.join("exports");
+        let path = dir.join(format!("{name}.csv"));
+        let count = items.len();
+        let write = move |csv: String| -> Result<Action> {
+            write_creating_dir(&path, csv)?;
+            log::info!("Exported {count} {} to {}", T::NAME, path.display());
+            Ok(Action::status(format!(
+                "Exported {count} {} to {}",
+                T::NAME,
+                path.display()
+            )))
+        };
+        if !self.config.config.export_screenshots {
+            let csv = export::to_csv(&items, None);
+            self.tasks.spawn(async move {
+                let written = tokio::task::spawn_blocking(move || write(csv))
+                    .await
+                    .map_err(Into::into)
+                    .and_then(|written| written);
+                let _ = tx.send(written.unwrap_or_else(|e: color_eyre::Report| {
+                    Action::Error(format!("Failed to export the {}: {e:#}", T::NAME))
+                }));
+            });
+            return;
+        }
+        self.spawn_scrape(tx, move |session| async move {
+            let mut screenshots = Vec::new();
+            for (i, item) in items.iter().enumerate() {
+                session.progress("Taking screenshots", i, Some(items.len()));
+                session.navigate(&item.page_url()).await?;
+                let screenshot = dir.join(&name).join(format!("{}.png", item.id()));
+                session.save_screenshot(&screenshot).await?;
+                screenshots.push(screenshot);
+            }
+            session.progress("Taking screenshots", items.len(), Some(items.len()));
+            write(export::to_csv(&items, Some(&screenshots))).map(Some)
+        });
+    }
+
+    /// Save a screenshot of the page the browser shows to `path`, relative to the `dumps`
+    /// directory, e.g. to see what a login that fails without an error looks like.
+    fn screenshot(&mut self, path: &Path, tx: &UnboundedSender<Action>) {
+        let path = self.dumps_dir().join(path);
+        self.spawn_scrape(tx.clone(), move |session| async move {
+            session.save_screenshot(&path).await?;
+            log::info!("Saved a screenshot to {}", path.display());
+            Ok(Some(Action::status(format!(
+                "Saved a screenshot to {}",
+                path.display()
+            ))))
+        });
+    }
+
+    /// Send `text` to each thread in turn, waiting `message_interval_ms` between sends and
+    /// reporting every outcome with an `Action::BulkMessageSent`.
+    fn send_bulk_message(
+        &mut self,
+        thread_ids: Vec<String>,
+        text: String,
+        tx: UnboundedSender<Action>,
+    ) {
+        let Some(browser) = self.web_client() else {
+            log::warn!("Not sending a bulk message, WebDriver is not running");
+            let outcome = SendOutcome::Failed("WebDriver is not running".to_string());
+            report_bulk_message(&thread_ids, &outcome, &tx);
+            return;
+        };
+        let interval = Duration::from_millis(self.config.config.message_interval_ms);
+        self.tasks.spawn(async move {
+            let total = thread_ids.len();
+            for (i, thread_id) in thread_ids.into_iter().enumerate() {
+                let _ = tx.send(Action::Progress {
+                    label: "Sending messages".to_string(),
+                    done: i,
+                    total: Some(total),
+                });
+                if i > 0 {
+                    sleep(interval).await;
+                }
+                // Take a fresh session per message so other work can interleave with a long run.
+                let result = async {
+                    let variables = templates::conversation_variables(&thread_id);
+                    let text = templates::render(&text, &variables)?;
+                    let session = browser.session().await?;
+                    fiverr::send_message(&session, &thread_id, &text).await
+                }
+                .await;
+                let outcome = match result {
+                    Ok(()) => SendOutcome::Sent,
+                    Err(e) => {
+                        log::error!("Failed to message {thread_id}: {e:#}");
+                        SendOutcome::Failed(format!("{e:#}"))
+                    }
+                };
+                let _ = tx.send(Action::BulkMessageSent { thread_id, outcome });
+            }
+            let _ = tx.send(Action::Progress {
+                label: "Sending messages".to_string(),
+                done: total,
+                total: Some(total),
+            });
+        });
+    }
+
+    /// Queue or drop a message sent outside of the active hours, per their `outside` policy, and
+    /// report which it was for every thread of a bulk message.
+    fn hold_back_message(&mut self, action: Action, tx: &UnboundedSender<Action>) -> Result<()> {
+        let outside = self
+            .config
+            .config
+            .active_hours
+            .as_ref()
+            .map(|hours| hours.outside);
+        let skip = outside == Some(OutsideHours::Skip);
+        if let Action::SendBulkMessage { thread_ids, .. } = &action {
+            let outcome = if skip {
+                SendOutcome::Failed("outside the active hours".to_string())
+            } else {
+                SendOutcome::Queued
+            };
+            report_bulk_message(thread_ids, &outcome, tx);
+        }
+        if skip {
+            log::warn!("Not sending a message outside of the active hours");
+            tx.send(Action::status("Outside active hours, message not sent"))?;
         } else {
-            false
+            let cap = self.config.config.memory_limits.queued_messages;
+            if utils::push_bounded(&mut self.queued_messages, action, cap) > 0 {
+                log::warn!("More than {cap} messages queued, dropped the oldest");
+            }
+            let queued = self.queued_messages.len();
+            tx.send(Action::status(format!(
+                "Outside active hours, {queued} message(s) queued"
+            )))?;
         }
+        Ok(())
     }
 
-    async fn start_geckodriver(&self) -> Result<()> {
-        Command::new("geckodriver")
-            .stdout(Stdio::null())
-            .stdout(Stdio::null())
-            .spawn()
-            .expect("Failed to start geckodriver");
+    /// Record whether it is within the active hours, sending the queued messages once it is.
+    fn set_automation_active(&mut self, active: bool, tx: &UnboundedSender<Action>) -> Result<()> {
+        self.automation_active = active;
+        tx.send(Action::AutomationActive(active))?;
+        if active && !self.queued_messages.is_empty() {
+            log::info!(
+                "Active hours started, sending {} queued message(s)",
+                self.queued_messages.len()
+            );
+            for action in self.queued_messages.drain(..) {
+                tx.send(action)?;
+            }
+        }
         Ok(())
     }
 
-    async fn init_web_client(&mut self) -> Result<()> {
-        if !self.is_geckodriver_running().await {
-            self.start_geckodriver().await?;
-            sleep(Duration::from_secs(2)).await;
+    /// Add a scrape of the gigs to the stored history in the background and hand the whole
+    /// history to the components.
+    fn record_gig_history(&mut self, gigs: &[Gig], tx: &UnboundedSender<Action>) {
+        let data_dir = self.config.config._data_dir.clone();
+        let limit = self.config.config.memory_limits.gig_snapshots;
+        let gigs = gigs.to_vec();
+        let tx = tx.clone();
+        self.tasks.spawn(async move {
+            let recorded = tokio::task::spawn_blocking(move || {
+                let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+                history::record(&data_dir, &gigs, timestamp as i64, limit)
+            })
+            .await;
+            match recorded {
+                Ok(Ok(history)) => {
+                    let _ = tx.send(Action::GigHistory(history));
+                }
+                Ok(Err(e)) => log::error!("Failed to record the gig history: {e:#}"),
+                Err(e) => log::error!("Failed to record the gig history: {e}"),
+            }
+        });
+    }
+
+    /// Open a second pane of the current mode's view, or close it if it is open.
+    fn toggle_split(&mut self, tx: &UnboundedSender<Action>) -> Result<()> {
+        if self.split.is_some() {
+            self.close_split();
+            return Ok(());
         }
-        let client = ClientBuilder::native()
-            .connect("http://localhost:4444")
-            .await
-            .expect("failed to connect to WebDriver");
-        self.web_client = Some(Arc::new(Mutex::new(Some(client))));
+        let Some(mut pane) = view_component(self.mode) else {
+            tx.send(Action::status("This screen can't be split"))?;
+            return Ok(());
+        };
+        pane.register_action_handler(tx.clone())?;
+        pane.register_config_handler(self.config.clone())?;
+        pane.update(Action::SwitchMode(self.mode))?;
+        let cached = self.mode.view().and_then(|view| self.view_cache.get(&view));
+        if let Some(cached) = cached {
+            pane.update(cached.action.clone())?;
+        }
+        self.split = Some(Split {
+            pane,
+            pane_focused: false,
+        });
+        self.focus(Some(FocusTarget::Pane));
         Ok(())
     }
 
-    async fn close_web_client(&mut self) -> Result<()> {
-        if let Some(web_client) = &self.web_client {
-            let mut client = web_client.lock().await;
-            if let Some(client) = client.take() {
-                if let Err(e) = client.close().await {
-                    eprintln!("Failed to close WebDriver client: {}", e);
+    /// Switch to `mode`, letting the components of the old mode know they are hidden and those
+    /// of the new one that they are shown.
+    fn enter_mode(&mut self, mode: Mode, tx: &UnboundedSender<Action>) -> Result<()> {
+        if mode == self.mode {
+            return Ok(());
+        }
+        for component in self.components.iter_mut() {
+            if component.modes().contains(&self.mode) {
+                component.on_exit()?;
+            }
+        }
+        self.mode = mode;
+        for component in self.components.iter_mut() {
+            if component.modes().contains(&mode) {
+                if let Some(action) = component.on_enter()? {
+                    tx.send(action)?;
                 }
             }
         }
-        // kill the gecko driver process
-        Command::new("pkill")
-            .arg("geckodriver")
-            .output()
-            .expect("Failed to stop geckodriver");
         Ok(())
     }
+
+    /// Remember the current mode before switching to `mode`, unless going back or staying.
+    fn record_history(&mut self, mode: Mode) {
+        if std::mem::take(&mut self.going_back) || mode == self.mode {
+            return;
+        }
+        if self.history.len() == MAX_HISTORY {
+            self.history.remove(0);
+        }
+        self.history.push(self.mode);
+    }
+
+    /// Close the second pane of the split view, giving its keys back to the original.
+    fn close_split(&mut self) {
+        let Some(split) = self.split.take() else {
+            return;
+        };
+        if split.pane_focused {
+            let view = self.components.iter().position(|component| {
+                component.is_focusable() && component.view_mode() == Some(self.mode)
+            });
+            self.focus(view.map(FocusTarget::Component));
+        }
+    }
+
+    /// Select `id` in a loaded list, or else open it from Fiverr: as an order if it looks like an
+    /// order number and as the conversation with a buyer otherwise.
+    fn jump_to(&self, id: &str, tx: &UnboundedSender<Action>) -> Result<()> {
+        let cached = |view| self.view_cache.get(&view).map(|cached| &cached.action);
+        if let Some(Action::Gigs(gigs)) = cached(View::Gigs) {
+            if gigs.iter().any(|gig| gig.id == id) {
+                tx.send(Action::SwitchMode(Mode::Gigs))?;
+                tx.send(Action::SelectItem(id.to_string()))?;
+                return Ok(());
+            }
+        }
+        if let Some(Action::Orders(orders)) = cached(View::Orders) {
+            if let Some(order) = orders
+                .iter()
+                .find(|order| order.id.eq_ignore_ascii_case(id))
+            {
+                tx.send(Action::SwitchMode(Mode::Orders))?;
+                tx.send(Action::SelectItem(order.id.clone()))?;
+                return Ok(());
+            }
+        }
+        if let Some(Action::Conversations(conversations)) = cached(View::Messages) {
+            let conversation = conversations
+                .iter()
+                .find(|conversation| conversation.username.eq_ignore_ascii_case(id));
+            if let Some(conversation) = conversation {
+                tx.send(Action::OpenConversation(conversation.username.clone()))?;
+                return Ok(());
+            }
+        }
+        let action = if fiverr::is_order_id(id) {
+            Action::ViewRequirements(id.to_ascii_uppercase())
+        } else {
+            Action::OpenConversation(id.to_string())
+        };
+        tx.send(Action::status(format!("Looking up {id} on Fiverr...")))?;
+        tx.send(action)?;
+        Ok(())
+    }
+
+    fn cache_view(&mut self, view: View, action: Action) {
+        let scraped_at = Instant::now();
+        self.view_cache
+            .insert(view, CachedView { scraped_at, action });
+    }
+
+    /// Show `view` from the cache if it was scraped within `view_cache_ttl_secs`, otherwise
+    /// scrape it.
+    fn show_view(&mut self, view: View, tx: UnboundedSender<Action>) -> Result<()> {
+        let ttl = Duration::from_secs(self.config.config.view_cache_ttl_secs);
+        match self.view_cache.get(&view) {
+            Some(cached) if cached.scraped_at.elapsed() < ttl => {
+                log::debug!("Showing the {view} view from the cache");
+                // Straight to the components, going through the channel would cache it again.
+                for component in self.components.iter_mut() {
+                    if let Some(action) = component.update(cached.action.clone())? {
+                        tx.send(action)?;
+                    }
+                }
+            }
+            _ => self.fetch_view(view, tx),
+        }
+        Ok(())
+    }
+
+    /// Scrape `view` again, unless it was refreshed less than `REFRESH_DEBOUNCE` ago.
+    fn refresh(&mut self, view: View, tx: &UnboundedSender<Action>) -> Result<()> {
+        if self.is_refresh_debounced(view, Instant::now()) {
+            log::debug!("Ignoring a refresh of the {view} view, one just started");
+            return Ok(());
+        }
+        self.last_refresh = Some((view, Instant::now()));
+        tx.send(Action::status(format!("Refreshing the {view} view...")))?;
+        self.fetch_view(view, tx.clone());
+        Ok(())
+    }
+
+    fn is_refresh_debounced(&self, view: View, now: Instant) -> bool {
+        self.last_refresh.is_some_and(|(last, at)| {
+            last == view && now.saturating_duration_since(at) < REFRESH_DEBOUNCE
+        })
+    }
+
+    fn fetch_view(&mut self, view: View, tx: UnboundedSender<Action>) {
+        match view {
+            View::Gigs => self.fetch_gigs(tx),
+            View::Orders => self.fetch_orders(tx),
+            View::Messages => self.fetch_conversations(tx),
+            // Not a screen of its own.
+            View::Requirements | View::Thread => {}
+        }
+    }
+
+    fn fetch_orders(&mut self, tx: UnboundedSender<Action>) {
+        let username = self.fivver_username.clone();
+        self.scrape_view(View::Orders, tx, move |session| async move {
+            let orders = fiverr::fetch_orders(&session, &username)
+                .await
+                .wrap_err("Failed to load the orders")?;
+            Ok(Some(Action::Orders(orders)))
+        });
+    }
+
+    fn fetch_conversations(&mut self, tx: UnboundedSender<Action>) {
+        self.scrape_view(View::Messages, tx, move |session| async move {
+            let conversations = fiverr::fetch_conversations(&session)
+                .await
+                .wrap_err("Failed to load the inbox")?;
+            Ok(Some(Action::Conversations(conversations)))
+        });
+    }
+
+    fn fetch_gigs(&mut self, tx: UnboundedSender<Action>) {
+        let username = self.fivver_username.clone();
+        self.scrape_view(View::Gigs, tx, move |session| async move {
+            let gigs = fiverr::fetch_gigs(&session, &username)
+                .await
+                .wrap_err("Failed to load the gigs")?;
+            Ok(Some(Action::Gigs(gigs)))
+        });
+    }
+
+    async fn fetch_data(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        let status = if self.config.config.manage_webdriver {
+            "Starting Geckodriver..."
+        } else {
+            "Connecting to WebDriver..."
+        };
+        tx.send(Action::startup(status))?;
+
+        if !self.drivers.contains_key(&self.profile) {
+            self.init_web_client(self.profile.clone(), tx).await?;
+        }
+        Ok(())
+    }
+
+    /// The port `profile`'s `kind` server listens on: the one configured in `profile_ports`, the
+    /// backend's default for the default profile, or else [`profile_port`].
+    fn webdriver_port(&self, profile: &str, kind: WebDriverKind) -> u16 {
+        if let Some(port) = self.config.config.profile_ports.get(profile) {
+            return *port;
+        }
+        if profile == DEFAULT_PROFILE {
+            return kind.default_port();
+        }
+        profile_port(profile, kind)
+    }
+
+    /// Connect `profile` to the configured `webdriver_url`, or else to the first of the
+    /// `webdriver_backends` that works, in the background. Local servers that aren't running yet
+    /// are started.
+    async fn init_web_client(
+        &mut self,
+        profile: String,
+        tx: UnboundedSender<Action>,
+    ) -> Result<()> {
+        let backends = self
+            .config
+            .config
+            .webdriver_backends
+            .iter()
+            .map(|kind| (*kind, self.webdriver_port(&profile, *kind)))
+            .collect::<Vec<_>>();
+        let browser = Browser::new(self.config.config.max_concurrent_webdriver_ops)
+            .stale_element_retries(self.config.config.stale_element_retries)
+            .cookie_consent(self.config.config.cookie_consent)
+            .navigation_log(self.config.config.navigation_log)
+            .selectors(self.config.selectors.clone())
+            .lazy_load(self.config.config.lazy_load.clone())
+            .rate_limit(self.config.config.rate_limit.clone())
+            .windows(self.config.config.windows.clone())
+            .progress(tx.clone());
+        let managed = self.config.config.manage_webdriver;
+        let remote = self.config.config.webdriver_url.clone();
+        let retry = self.config.config.connect_retry.clone();
+        let options = BrowserOptions {
+            profile_dir: self
+                .config
+                .config
+                .browser_profile_dir
+                .as_ref()
+                .map(|dir| dir.join(&profile)),
+            headless: self.headless(),
+        };
+        let heartbeat = self.config.config.heartbeat_secs.map(Duration::from_secs);
+        let process = Arc::new(std::sync::Mutex::new(None));
+        self.drivers.insert(
+            profile.clone(),
+            Driver {
+                browser: browser.clone(),
+                process: process.clone(),
+            },
+        );
+        self.tasks.spawn(async move {
+            let connected = match remote {
+                Some(url) => driver::connect_url(&url, &retry, &tx)
+                    .await
+                    .map(|client| (client, url)),
+                None => driver::connect_any(&backends, managed, &options, &retry, &tx)
+                    .await
+                    .map(|(client, connected)| {
+                        let name = connected.kind.to_string();
+                        *process.lock().unwrap() = Some(connected);
+                        (client, name)
+                    }),
+            };
+            let (client, name) = match connected {
+                Ok(connected) => connected,
+                Err(e) => {
+                    let _ = tx.send(Action::Error(format!("{e:#}")));
+                    return;
+                }
+            };
+            browser.attach(client).await;
+            let _ = tx.send(Action::status(format!("Connected to {name}")));
+            let _ = tx.send(Action::WebDriverStatus(true));
+            let _ = tx.send(Action::RestoreSession);
+            if let Some(interval) = heartbeat {
+                if let Err(e) = browser.heartbeat(interval).await {
+                    log::error!("WebDriver heartbeat failed: {e:#}");
+                    let _ = tx.send(Action::ConnectionLost(profile));
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// End `profile`'s WebDriver session and stop its server, unless `keep_driver` is set. A
+    /// server that is kept running is picked up by the next launch's `init_web_client`, which
+    /// then connects right away instead of waiting for a new server to start.
+    async fn close_web_client(&mut self, profile: &str, keep_driver: bool) -> Result<()> {
+        let Some(driver) = self.drivers.remove(profile) else {
+            return Ok(());
+        };
+        // fantoccini can't attach to an existing session, so the session is ended either way
+        // and the next launch starts a new one on the warm server.
+        if let Err(e) = driver.browser.close().await {
+            log::error!("Failed to close WebDriver client: {e}");
+        }
+        let process = driver.process.lock().unwrap().take();
+        match process {
+            Some(process) if keep_driver => process.detach(&get_data_dir())?,
+            Some(mut process) => process.stop()?,
+            None => {}
+        }
+        Ok(())
+    }
+
+    async fn close_web_clients(&mut self, keep_driver: bool) {
+        let profiles: Vec<String> = self.drivers.keys().cloned().collect();
+        for profile in profiles {
+            // Quitting goes on either way.
+            if let Err(e) = self.close_web_client(&profile, keep_driver).await {
+                log::error!("Failed to shut down WebDriver for profile {profile}: {e:#}");
+            }
+        }
+    }
+}
+
+/// Draw `component` into `area`, in place of which a placeholder is shown if it panics.
+fn draw_component(
+    f: &mut Frame<'_>,
+    component: &mut dyn Component,
+    area: Rect,
+    action_tx: &UnboundedSender<Action>,
+) {
+    match utils::catch_panic(|| component.draw(f, area)) {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            action_tx
+                .send(Action::Error(format!("Failed to draw: {:?}", e)))
+                .unwrap();
+        }
+        Err(panic) => {
+            // Keep the rest of the UI alive and show what broke in place of the view.
+            let placeholder = Paragraph::new(format!("This view crashed: {panic}"))
+                .red()
+                .wrap(Wrap { trim: true });
+            f.render_widget(Clear, area);
+            f.render_widget(placeholder, area);
+        }
+    }
+}
+
+/// A new instance of the component that shows `mode`'s view, for the second pane of a split.
+fn view_component(mode: Mode) -> Option<Box<dyn Component>> {
+    match mode {
+        Mode::Home | Mode::Settings => None,
+        Mode::Orders => Some(Box::new(OrdersComponent::new())),
+        Mode::Messages => Some(Box::new(MessagesComponent::new())),
+        Mode::Gigs => Some(Box::new(GigsComponent::new())),
+    }
+}
+
+/// Send the same `outcome` for every thread of a bulk message.
+fn report_bulk_message(thread_ids: &[String], outcome: &SendOutcome, tx: &UnboundedSender<Action>) {
+    for thread_id in thread_ids {
+        let _ = tx.send(Action::BulkMessageSent {
+            thread_id: thread_id.clone(),
+            outcome: outcome.clone(),
+        });
+    }
+}
+
+/// A port for `profile`'s `kind` server that is the same on every launch, so a server left
+/// running by a detached run is found again. Hashed from the names (FNV-1a, which unlike std's
+/// hasher never changes) into 30000-39999, away from the backends' default ports.
+fn profile_port(profile: &str, kind: WebDriverKind) -> u16 {
+    let hash = format!("{kind}:{profile}")
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+    30000 + (hash % 10000) as u16
+}
+
+/// `Action::GigFailed` for a failed `what` (e.g. `"toggle"`) of the gig `id`, logging the error.
+fn gig_failed(id: String, what: &str, e: color_eyre::Report) -> Action {
+    log::error!("Failed to {what} gig {id}: {e:#}");
+    Action::GigFailed {
+        id,
+        error: format!("{e:#}"),
+    }
+}
+
+/// Whether `event` is a write to the config: `file` if given, otherwise any `config.*` file.
+fn is_config_change(event: &notify::Event, file: Option<&Path>) -> bool {
+    if !(event.kind.is_create() || event.kind.is_modify()) {
+        return false;
+    }
+    event.paths.iter().any(|path| match file {
+        Some(file) => path == file,
+        None => path.file_stem().is_some_and(|stem| stem == "config"),
+    })
+}
+
+/// Give the cells of `buffer` that kept the terminal's colors the theme's text and background.
+fn apply_theme(buffer: &mut Buffer, theme: &Theme) {
+    for cell in buffer.content.iter_mut() {
+        if cell.fg == Color::Reset {
+            cell.fg = theme.text;
+        }
+        if cell.bg == Color::Reset {
+            cell.bg = theme.background;
+        }
+    }
+}
+
+/// Take the colors out of every cell of `buffer`. Cells that stood out by their background are
+/// shown reversed instead, so e.g. a highlighted row stays visible.
+fn strip_colors(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        if cell.bg != Color::Reset {
+            cell.modifier.toggle(Modifier::REVERSED);
+        }
+        cell.fg = Color::Reset;
+        cell.bg = Color::Reset;
+    }
+}
+
+/// Whether `component` is drawn while the app is in `mode`, see [`Component::modes`].
+fn is_drawn_in(component: &dyn Component, mode: Mode) -> bool {
+    let modes = component.modes();
+    modes.is_empty() || modes.contains(&mode)
+}
+
+/// The action that continues logging in from where `outcome` got to.
+/// A CAPTCHA can't be solved without a browser window, so in `headless` mode it ends the login
+/// with an error.
+fn login_action(outcome: LoginOutcome, headless: bool, tx: &UnboundedSender<Action>) -> Action {
+    match outcome {
+        LoginOutcome::LoggedIn => {
+            log::info!("Logged in");
+            Action::Auth(AuthState::LoggedIn)
+        }
+        LoginOutcome::OtpRequired => {
+            log::info!("Fiverr asked for a one-time code");
+            let _ = tx.send(Action::startup("Waiting for the one-time code..."));
+            Action::OtpRequired
+        }
+        LoginOutcome::Captcha(url) if headless => Action::Error(format!(
+            "Fiverr shows a CAPTCHA at {url}, which needs a browser window. Log in once without \
+             headless mode, with browser_profile_dir set to keep the login"
+        )),
+        LoginOutcome::Captcha(url) => Action::CaptchaDetected(url),
+    }
+}
+
+/// The Fiverr login from the environment variable `name` in `env`, else from the config file,
+/// else from the `.env` file at `env_file`.
+fn credential(
+    name: &str,
+    env: &HashMap<String, String>,
+    configured: Option<&Secret>,
+    env_vars: &HashMap<String, String>,
+    env_file: &Path,
+) -> Result<String> {
+    env.get(name)
+        .cloned()
+        .or_else(|| configured.map(|secret| secret.expose().to_string()))
+        .or_else(|| env_vars.get(name).cloned())
+        .ok_or_else(|| {
+            eyre!(
+                "{name} is not set. Export it, set {} in the config file or add {name}=... to {}",
+                name.to_lowercase(),
+                env_file.display()
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyCode;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::tui::{Event, ScriptedEvents};
+
+    /// An app with only the default config, logged in as `seller`.
+    fn test_app() -> App {
+        let dir = env::temp_dir().join(format!("stash-test-app-{}", std::process::id()));
+        let config = Config::layered(None, &dir, &dir).unwrap();
+        let env = HashMap::from([
+            ("FIVVER_USERNAME".to_string(), "seller".to_string()),
+            ("FIVVER_PASSWORD".to_string(), "secret".to_string()),
+        ]);
+        let profile = DEFAULT_PROFILE.to_string();
+        App::with_env(4.0, 60.0, config, None, profile, &env, &dir.join(".env")).unwrap()
+    }
+
+    fn received(rx: &mut mpsc::UnboundedReceiver<Action>) -> Vec<Action> {
+        let mut actions = Vec::new();
+        while let Ok(action) = rx.try_recv() {
+            actions.push(action);
+        }
+        actions
+    }
+
+    #[test]
+    fn test_credential_precedence() {
+        let name = "STASH_TEST_CREDENTIAL";
+        let env_file = Path::new(".env");
+        let env = HashMap::from([(name.to_string(), "from-env".to_string())]);
+        let env_vars = HashMap::from([(name.to_string(), "from-env-file".to_string())]);
+        let configured: Secret = serde_json::from_str(r#""from-config""#).unwrap();
+        let none = HashMap::new();
+        assert_eq!(
+            credential(name, &env, Some(&configured), &env_vars, env_file).unwrap(),
+            "from-env"
+        );
+        assert_eq!(
+            credential(name, &none, Some(&configured), &env_vars, env_file).unwrap(),
+            "from-config"
+        );
+        assert_eq!(
+            credential(name, &none, None, &env_vars, env_file).unwrap(),
+            "from-env-file"
+        );
+        let err = credential(name, &none, None, &none, env_file).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("STASH_TEST_CREDENTIAL is not set"));
+    }
+
+    #[test]
+    fn test_components_drawn_in_their_modes() {
+        let app = test_app();
+        // The components drawn in `mode` only, by their modes and view mode.
+        let drawn_only_in = |mode| {
+            app.components
+                .iter()
+                .filter(|component| !component.modes().is_empty())
+                .filter(|component| is_drawn_in(component.as_ref(), mode))
+                .map(|component| (component.modes().to_vec(), component.view_mode()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(drawn_only_in(Mode::Home), [(vec![Mode::Home], None)]);
+        assert_eq!(
+            drawn_only_in(Mode::Settings),
+            [(vec![Mode::Settings], None)]
+        );
+        for view in [Mode::Gigs, Mode::Messages, Mode::Orders] {
+            assert_eq!(drawn_only_in(view), [(vec![view], Some(view))]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scripted_events() {
+        let mut app = test_app();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut events = ScriptedEvents::new([
+            Event::Tick,
+            Event::Resize(80, 24),
+            Event::Key(KeyEvent::from(KeyCode::Char('q'))),
+        ]);
+        while app.pump(&mut events, &tx).await.unwrap() {}
+
+        assert_eq!(
+            received(&mut rx),
+            [Action::Tick, Action::Resize(80, 24), Action::RequestQuit]
+        );
+        assert!(app.dirty);
+    }
+
+    #[tokio::test]
+    async fn test_bound_keys_are_not_passed_on() {
+        let mut app = test_app();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let enter = KeyEvent::from(KeyCode::Enter);
+        app.config
+            .keybindings
+            .modes
+            .entry(Mode::Home)
+            .or_default()
+            .insert(vec![enter], Action::Help);
+        for component in app.components.iter_mut() {
+            component.register_action_handler(tx.clone()).unwrap();
+            component
+                .register_config_handler(app.config.clone())
+                .unwrap();
+            component.update(Action::Auth(AuthState::LoggedIn)).unwrap();
+        }
+        // Enter also runs the selected binding of the home dashboard, if it gets the key.
+        let mut events = ScriptedEvents::new([Event::Key(enter)]);
+        app.pump(&mut events, &tx).await.unwrap();
+        assert_eq!(received(&mut rx), [Action::Help]);
+    }
+
+    #[tokio::test]
+    async fn test_popups_get_esc_before_the_keybindings() {
+        let mut app = test_app();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        app.mode = Mode::Messages;
+        for component in app.components.iter_mut() {
+            component
+                .update(Action::OpenConversation("buyer".to_string()))
+                .unwrap();
+        }
+        assert!(app.components.iter().any(|c| c.captures_keys()));
+
+        let mut events = ScriptedEvents::new([Event::Key(KeyEvent::from(KeyCode::Esc))]);
+        app.pump(&mut events, &tx).await.unwrap();
+        assert!(!app.components.iter().any(|c| c.captures_keys()));
+        // Esc is bound to `Back` in the Messages mode, which must not run as well.
+        assert_eq!(received(&mut rx), []);
+    }
+
+    #[tokio::test]
+    async fn test_tab_moves_the_keys_to_the_next_pane() {
+        let mut app = test_app();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        app.mode = Mode::Gigs;
+        let gig = Gig {
+            id: "logo".to_string(),
+            title: "I will design your logo".to_string(),
+            status: fiverr::GigStatus::Active,
+            price: "$25".to_string(),
+            orders_in_queue: 0,
+            impressions: 0,
+            clicks: 0,
+            edit_url: Some("https://www.fiverr.com/edit/logo".to_string()),
+            analytics_url: None,
+        };
+        for action in [
+            Action::SwitchMode(Mode::Gigs),
+            Action::ToggleLogView,
+            Action::Gigs(vec![gig]),
+        ] {
+            for component in app.components.iter_mut() {
+                component.update(action.clone()).unwrap();
+            }
+        }
+        app.refocus();
+        let edit = || Event::Key(KeyEvent::from(KeyCode::Char('e')));
+        // Without `PendingKeys`, which every key that isn't bound sends.
+        let sent = |rx: &mut mpsc::UnboundedReceiver<Action>| -> Vec<Action> {
+            received(rx)
+                .into_iter()
+                .filter(|action| !matches!(action, Action::PendingKeys(_)))
+                .collect()
+        };
+
+        app.pump(&mut ScriptedEvents::new([edit()]), &tx)
+            .await
+            .unwrap();
+        assert_eq!(
+            sent(&mut rx),
+            [Action::OpenPage(
+                "https://www.fiverr.com/edit/logo".to_string()
+            )]
+        );
+
+        let tab = Event::Key(KeyEvent::from(KeyCode::Tab));
+        app.pump(&mut ScriptedEvents::new([tab]), &tx)
+            .await
+            .unwrap();
+        assert_eq!(sent(&mut rx), [Action::FocusNext]);
+        app.cycle_focus(true);
+        // The log has the keys now, where `e` shows only errors.
+        app.pump(&mut ScriptedEvents::new([edit()]), &tx)
+            .await
+            .unwrap();
+        assert_eq!(sent(&mut rx), []);
+    }
+
+    #[test]
+    fn test_focus_cycles_through_the_split_pane() {
+        let mut app = test_app();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        app.mode = Mode::Gigs;
+        for component in app.components.iter_mut() {
+            component.update(Action::SwitchMode(Mode::Gigs)).unwrap();
+        }
+        app.refocus();
+        let gigs = app.focus_target();
+        assert!(gigs.is_some());
+
+        app.toggle_split(&tx).unwrap();
+        assert_eq!(app.focus_target(), Some(FocusTarget::Pane));
+        app.cycle_focus(true);
+        assert_eq!(app.focus_target(), gigs);
+        app.cycle_focus(false);
+        assert_eq!(app.focus_target(), Some(FocusTarget::Pane));
+
+        // Closing the split gives the keys back to the original view.
+        app.toggle_split(&tx).unwrap();
+        assert_eq!(app.focus_target(), gigs);
+    }
+
+    #[tokio::test]
+    async fn test_export_loaded_orders() {
+        let mut app = test_app();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let dir = env::temp_dir().join(format!("stash-test-export-{}", std::process::id()));
+        app.config.config._data_dir = dir.clone();
+        app.export(View::Orders, &tx).unwrap();
+        assert_eq!(
+            received(&mut rx),
+            [Action::status("Load the Orders before exporting them")]
+        );
+
+        let order = Order {
+            id: "FO1".to_string(),
+            buyer: "buyer".to_string(),
+            gig: "Logo design".to_string(),
+            due_date: "Oct 20".to_string(),
+            status: fiverr::OrderStatus::InProgress,
+            amount: "$25".to_string(),
+        };
+        app.view_cache.insert(
+            View::Orders,
+            CachedView {
+                scraped_at: Instant::now(),
+                action: Action::Orders(vec![order]),
+            },
+        );
+        app.export(View::Orders, &tx).unwrap();
+        let Some(Action::Status { text, .. }) = rx.recv().await else {
+            panic!("no status after the export");
+        };
+        let path = text.strip_prefix("Exported 1 orders to ").unwrap();
+        let csv = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            csv,
+            "id,buyer,gig,due_date,status,amount\nFO1,buyer,Logo design,Oct 20,In progress,$25\n"
+        );
+    }
+
+    #[test]
+    fn test_auto_accept_waits_for_active_hours() {
+        let mut app = test_app();
+        app.config.config.auto_accept = AutoAccept {
+            enabled: true,
+            gigs: vec!["Logo design".to_string()],
+        };
+        app.auto_accept_armed = true;
+        let order = |id: &str| Order {
+            id: id.to_string(),
+            buyer: "buyer".to_string(),
+            gig: "Logo design".to_string(),
+            due_date: String::new(),
+            status: fiverr::OrderStatus::PendingAcceptance,
+            amount: "$25".to_string(),
+        };
+        assert_eq!(app.orders_to_accept(&[order("FO1")]), []);
+
+        app.automation_active = false;
+        assert_eq!(app.orders_to_accept(&[order("FO1"), order("FO2")]), []);
+        app.automation_active = true;
+        // FO2 is known by now and left to the seller.
+        assert_eq!(
+            app.orders_to_accept(&[order("FO1"), order("FO2"), order("FO3")]),
+            [order("FO3")]
+        );
+    }
+
+    #[test]
+    fn test_rejected_session_is_removed() {
+        let mut app = test_app();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let dir = env::temp_dir().join(format!("stash-test-session-{}", std::process::id()));
+        app.config.config._data_dir = dir.clone();
+        let path = app.session_file();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "{}").unwrap();
+
+        app.expire_session(&tx).unwrap();
+        assert!(!path.exists());
+        assert_eq!(received(&mut rx), [Action::Login]);
+
+        app.config.config.expired_session = ExpiredSession::Confirm;
+        app.expire_session(&tx).unwrap();
+        assert!(matches!(received(&mut rx)[..], [Action::Confirm(_)]));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_held_back_bulk_message_reports_every_thread() {
+        let mut app = test_app();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let send = Action::SendBulkMessage {
+            thread_ids: vec!["alice".to_string(), "bob".to_string()],
+            text: "Hi".to_string(),
+        };
+        app.hold_back_message(send, &tx).unwrap();
+        let outcomes: Vec<_> = received(&mut rx)
+            .into_iter()
+            .filter_map(|action| match action {
+                Action::BulkMessageSent { thread_id, outcome } => Some((thread_id, outcome)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                ("alice".to_string(), SendOutcome::Queued),
+                ("bob".to_string(), SendOutcome::Queued)
+            ]
+        );
+        assert_eq!(app.queued_messages.len(), 1);
+    }
+
+    #[test]
+    fn test_jump_to_loaded_order() {
+        let mut app = test_app();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let order = Order {
+            id: "FO12".to_string(),
+            buyer: "buyer".to_string(),
+            gig: "I will design your logo".to_string(),
+            due_date: "soon".to_string(),
+            status: fiverr::OrderStatus::InProgress,
+            amount: "$25".to_string(),
+        };
+        app.cache_view(View::Orders, Action::Orders(vec![order]));
+        app.jump_to("fo12", &tx).unwrap();
+        assert_eq!(
+            received(&mut rx),
+            [
+                Action::SwitchMode(Mode::Orders),
+                Action::SelectItem("FO12".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_profile_port_is_stable() {
+        let port = profile_port("work", WebDriverKind::Gecko);
+        assert_eq!(port, profile_port("work", WebDriverKind::Gecko));
+        assert!((30000..40000).contains(&port));
+        assert_ne!(port, profile_port("work", WebDriverKind::Chrome));
+        assert_ne!(port, profile_port("home", WebDriverKind::Gecko));
+
+        let mut app = test_app();
+        assert_eq!(app.webdriver_port("work", WebDriverKind::Gecko), port);
+        app.config
+            .config
+            .profile_ports
+            .insert("work".to_string(), 4450);
+        assert_eq!(app.webdriver_port("work", WebDriverKind::Gecko), 4450);
+        assert_eq!(
+            app.webdriver_port(DEFAULT_PROFILE, WebDriverKind::Gecko),
+            WebDriverKind::Gecko.default_port()
+        );
+    }
+
+    #[test]
+    fn test_refresh_debounce() {
+        let mut app = test_app();
+        let now = Instant::now();
+        assert!(!app.is_refresh_debounced(View::Gigs, now));
+        app.last_refresh = Some((View::Gigs, now));
+        assert!(app.is_refresh_debounced(View::Gigs, now + Duration::from_millis(500)));
+        assert!(!app.is_refresh_debounced(View::Orders, now));
+        assert!(!app.is_refresh_debounced(View::Gigs, now + REFRESH_DEBOUNCE));
+    }
+
+    #[test]
+    fn test_history() {
+        let mut app = test_app();
+        app.record_history(Mode::Gigs);
+        app.mode = Mode::Gigs;
+        app.record_history(Mode::Gigs);
+        assert_eq!(app.history, [Mode::Home]);
+
+        app.going_back = true;
+        app.record_history(Mode::Home);
+        assert_eq!(app.history, [Mode::Home]);
+        assert!(!app.going_back);
+
+        for _ in 0..MAX_HISTORY {
+            app.record_history(Mode::Orders);
+        }
+        assert_eq!(app.history.len(), MAX_HISTORY);
+        assert_eq!(app.history.first(), Some(&Mode::Gigs));
+    }
+
+    #[test]
+    fn test_apply_theme() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 2, 1));
+        buffer.set_string(0, 0, "a", Style::new().red());
+        buffer.set_string(1, 0, "b", Style::new().on_blue());
+        apply_theme(&mut buffer, &Theme::light());
+        let mut expected = Buffer::empty(Rect::new(0, 0, 2, 1));
+        expected.set_string(0, 0, "a", Style::new().red().on_white());
+        expected.set_string(1, 0, "b", Style::new().black().on_blue());
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn test_strip_colors() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 3, 1));
+        buffer.set_string(0, 0, "a", Style::new().red().bold());
+        buffer.set_string(1, 0, "b", Style::new().white().on_blue());
+        strip_colors(&mut buffer);
+        let mut expected = Buffer::empty(Rect::new(0, 0, 3, 1));
+        expected.set_string(0, 0, "a", Style::new().bold());
+        expected.set_string(1, 0, "b", Style::new().reversed());
+        assert_eq!(buffer, expected);
+    }
 }
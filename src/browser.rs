@@ -0,0 +1,1042 @@
+use std::{
+    fmt,
+    future::Future,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex as StdMutex},
+};
+
+use color_eyre::eyre::{bail, eyre, Result, WrapErr};
+use fantoccini::{
+    cookies::Cookie, elements::Element, error::CmdError, wd::WindowHandle, Client, Locator,
+};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::{mpsc::UnboundedSender, Mutex, OwnedSemaphorePermit, Semaphore},
+    time::{sleep, Duration, Instant},
+};
+
+use crate::action::Action;
+use crate::config::{
+    CookieConsent, LazyLoad, NavigationLog, RateLimit, Selector, Selectors, StrayWindows, View,
+    Windows,
+};
+
+/// `Browser` is a cloneable handle to the single WebDriver session owned by the app.
+///
+/// fantoccini sessions are not safe to drive concurrently: every command is sent to the same
+/// browser window, so two tasks navigating at once will read each other's pages. All WebDriver
+/// work should go through [`Browser::session`], which only hands out the client while holding
+/// one of `max_concurrent_webdriver_ops` permits. With the default of one permit, operations
+/// run strictly one after another.
+#[derive(Clone)]
+pub struct Browser {
+    client: Arc<Mutex<Option<Client>>>,
+    permits: Arc<Semaphore>,
+    max_ops: u32,
+    settings: Arc<Settings>,
+    backoff: Arc<Backoff>,
+    /// The window stash drives, once a navigation has found it.
+    main_window: Arc<StdMutex<Option<WindowHandle>>>,
+}
+
+/// How sessions handed out by a `Browser` behave while scraping.
+#[derive(Clone, Debug, Default)]
+struct Settings {
+    stale_element_retries: usize,
+    cookie_consent: CookieConsent,
+    navigation_log: NavigationLog,
+    selectors: Selectors,
+    lazy_load: LazyLoad,
+    rate_limit: RateLimit,
+    windows: Windows,
+    /// Where `Session::progress` reports to.
+    progress_tx: Option<UnboundedSender<Action>>,
+}
+
+/// How long navigations wait after Fiverr rate limited the browser. Shared by all its sessions.
+#[derive(Debug, Default)]
+struct Backoff {
+    /// No navigation starts before this.
+    paused_until: StdMutex<Option<Instant>>,
+    /// Waited before every navigation. Grows with every rate limit page and stays for the session.
+    delay: StdMutex<Duration>,
+}
+
+impl Backoff {
+    /// Pause for `cooldown` from `now` and wait `delay_step` longer before navigations from then on.
+    fn hit(&self, now: Instant, cooldown: Duration, delay_step: Duration) {
+        *self.paused_until.lock().unwrap() = Some(now + cooldown);
+        *self.delay.lock().unwrap() += delay_step;
+    }
+
+    /// How much of the pause after the last rate limit is left at `now`.
+    fn pause_time(&self, now: Instant) -> Duration {
+        let paused = self
+            .paused_until
+            .lock()
+            .unwrap()
+            .map(|until| until.saturating_duration_since(now));
+        paused.unwrap_or_default()
+    }
+
+    /// How long a navigation starting at `now` has to wait.
+    fn wait_time(&self, now: Instant) -> Duration {
+        self.pause_time(now) + *self.delay.lock().unwrap()
+    }
+}
+
+/// A permit to drive the WebDriver session. The permit is released when the session is dropped, so
+/// hold on to it for the whole logical operation (e.g. a navigation and the scrape that follows).
+pub struct Session {
+    client: Client,
+    _permit: OwnedSemaphorePermit,
+    settings: Arc<Settings>,
+    backoff: Arc<Backoff>,
+    main_window: Arc<StdMutex<Option<WindowHandle>>>,
+}
+
+impl Deref for Session {
+    type Target = Client;
+
+    fn deref(&self) -> &Self::Target {
+        &self.client
+    }
+}
+
+/// Where a [`Selector`] can be looked up: the page (through a [`Session`], which logs the
+/// lookup, or a bare client) or an element of it.
+pub(crate) trait Scope {
+    async fn find_css(&self, css: &str) -> Result<Element, CmdError>;
+    async fn find_all_css(&self, css: &str) -> Result<Vec<Element>, CmdError>;
+}
+
+impl Scope for Session {
+    async fn find_css(&self, css: &str) -> Result<Element, CmdError> {
+        self.find(Locator::Css(css)).await
+    }
+
+    async fn find_all_css(&self, css: &str) -> Result<Vec<Element>, CmdError> {
+        self.find_all(Locator::Css(css)).await
+    }
+}
+
+impl Scope for Client {
+    async fn find_css(&self, css: &str) -> Result<Element, CmdError> {
+        self.find(Locator::Css(css)).await
+    }
+
+    async fn find_all_css(&self, css: &str) -> Result<Vec<Element>, CmdError> {
+        self.find_all(Locator::Css(css)).await
+    }
+}
+
+impl Scope for Element {
+    async fn find_css(&self, css: &str) -> Result<Element, CmdError> {
+        self.find(Locator::Css(css)).await
+    }
+
+    async fn find_all_css(&self, css: &str) -> Result<Vec<Element>, CmdError> {
+        self.find_all(Locator::Css(css)).await
+    }
+}
+
+impl Selector {
+    /// The first element matched by the first candidate that matches. Fails with the miss of
+    /// the last candidate if none does.
+    pub(crate) async fn find(&self, scope: &impl Scope) -> Result<Element, CommandError> {
+        let mut miss = None;
+        for (index, css) in self.candidates().iter().enumerate() {
+            match scope.find_css(css).await {
+                Ok(element) => {
+                    self.log_match(index);
+                    return Ok(element);
+                }
+                Err(e) if e.is_miss() => miss = Some(e),
+                Err(e) => return Err(CommandError::new("find", Some(self), e)),
+            }
+        }
+        let miss = miss.expect("a selector has at least one candidate");
+        Err(CommandError::new("find", Some(self), miss))
+    }
+
+    /// Like [`Selector::find`], but keeps looking while nothing matches, for up to `timeout`.
+    pub(crate) async fn wait_for(
+        &self,
+        scope: &impl Scope,
+        timeout: Duration,
+    ) -> Result<Element, CommandError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match self.find(scope).await {
+                Err(e) if e.is_miss() && Instant::now() < deadline => {
+                    sleep(Duration::from_millis(250)).await
+                }
+                Err(e) if e.is_miss() => {
+                    return Err(CommandError::new(
+                        "wait for",
+                        Some(self),
+                        CmdError::WaitTimeout,
+                    ))
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// The elements matched by the first candidate that matches any, or none.
+    pub(crate) async fn find_all(&self, scope: &impl Scope) -> Result<Vec<Element>, CommandError> {
+        for (index, css) in self.candidates().iter().enumerate() {
+            let elements = scope
+                .find_all_css(css)
+                .await
+                .map_err(|e| CommandError::new("find all of", Some(self), e))?;
+            if !elements.is_empty() {
+                self.log_match(index);
+                return Ok(elements);
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    /// Log when a fallback matched, so candidates that no longer match anywhere can be found
+    /// and removed.
+    fn log_match(&self, index: usize) {
+        if index > 0 {
+            let candidates = self.candidates();
+            log::info!(
+                "Fallback selector {:?} matched where {:?} did not",
+                candidates[index],
+                &candidates[..index]
+            );
+        }
+    }
+}
+
+/// A WebDriver command that failed, with what stash was doing and the selector involved, so a
+/// failed scrape names the step and the selector to look at.
+#[derive(Debug)]
+pub struct CommandError {
+    /// What was being done, e.g. `find` or `navigate to https://…`.
+    pub operation: String,
+    /// The selector that was looked up, with its fallbacks.
+    pub selector: Option<String>,
+    pub source: CmdError,
+}
+
+impl CommandError {
+    pub fn new(
+        operation: impl Into<String>,
+        selector: Option<&Selector>,
+        source: CmdError,
+    ) -> Self {
+        Self {
+            operation: operation.into(),
+            selector: selector.map(Selector::to_string),
+            source,
+        }
+    }
+
+    /// Whether nothing matched the selector. A stale element is not a miss, the selector matched
+    /// but the page re-rendered before the element was used.
+    pub fn is_miss(&self) -> bool {
+        self.source.is_miss()
+    }
+
+    /// Whether [`Selector::wait_for`] gave up because nothing matched in time.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self.source, CmdError::WaitTimeout)
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to {}", self.operation)?;
+        if let Some(selector) = &self.selector {
+            write!(f, " `{selector}`")?;
+        }
+        write!(f, ": {}", describe(&self.source))
+    }
+}
+
+impl std::error::Error for CommandError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// What went wrong with a WebDriver command, in plain words. The error itself has the details.
+pub fn describe(e: &CmdError) -> &'static str {
+    match e {
+        e if is_stale_element(e) => "the element went away when the page re-rendered",
+        CmdError::NoSuchElement(_) => "nothing on the page matched",
+        CmdError::NoSuchWindow(_) => "the browser window was closed",
+        CmdError::NoSuchAlert(_) => "there was no dialog to handle",
+        CmdError::Standard(_) => "the browser refused the command",
+        CmdError::BadUrl(_) => "the URL is invalid",
+        CmdError::Failed(_) | CmdError::Lost(_) => "lost the connection to WebDriver",
+        CmdError::NotJson(_) | CmdError::Json(_) | CmdError::NotW3C(_) => {
+            "WebDriver sent an answer stash doesn't understand"
+        }
+        CmdError::InvalidArgument(..) => "stash sent an invalid command",
+        CmdError::ImageDecodeError(_) => "the screenshot could not be decoded",
+        CmdError::WaitTimeout => "timed out waiting",
+    }
+}
+
+/// The current page and cookies of a session, for debugging or picking up where it left off.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub url: String,
+    pub cookies: Vec<SnapshotCookie>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotCookie {
+    pub name: String,
+    pub value: String,
+    pub domain: Option<String>,
+    pub path: Option<String>,
+    pub secure: Option<bool>,
+    pub http_only: Option<bool>,
+}
+
+pub const REDACTED: &str = "<redacted>";
+
+impl SnapshotCookie {
+    /// Cookies that would let someone else act as the logged in user.
+    fn is_sensitive(&self) -> bool {
+        let name = self.name.to_ascii_lowercase();
+        self.http_only == Some(true)
+            || ["session", "token", "auth", "login"]
+                .iter()
+                .any(|marker| name.contains(marker))
+    }
+}
+
+impl SnapshotCookie {
+    fn to_cookie(&self) -> Cookie<'static> {
+        let mut cookie = Cookie::new(self.name.clone(), self.value.clone());
+        if let Some(domain) = &self.domain {
+            cookie.set_domain(domain.clone());
+        }
+        if let Some(path) = &self.path {
+            cookie.set_path(path.clone());
+        }
+        cookie.set_secure(self.secure);
+        cookie.set_http_only(self.http_only);
+        cookie
+    }
+}
+
+impl From<&Cookie<'_>> for SnapshotCookie {
+    fn from(cookie: &Cookie<'_>) -> Self {
+        Self {
+            name: cookie.name().to_string(),
+            value: cookie.value().to_string(),
+            domain: cookie.domain().map(str::to_string),
+            path: cookie.path().map(str::to_string),
+            secure: cookie.secure(),
+            http_only: cookie.http_only(),
+        }
+    }
+}
+
+impl Session {
+    pub fn selectors(&self) -> &Selectors {
+        &self.settings.selectors
+    }
+
+    /// Report how far a long scrape got, for the progress gauge in the status bar.
+    pub fn progress(&self, label: &str, done: usize, total: Option<usize>) {
+        if let Some(tx) = &self.settings.progress_tx {
+            let label = label.to_string();
+            let _ = tx.send(Action::Progress { label, done, total });
+        }
+    }
+
+    // `goto`, `find` and `find_all` shadow the client's methods so every lookup the scrapers make
+    // shows up in the navigation log.
+
+    pub async fn goto(&self, url: &str) -> Result<(), CmdError> {
+        let result = self.client.goto(url).await;
+        if self.settings.navigation_log != NavigationLog::Off {
+            match &result {
+                Ok(()) => log::info!("Navigated to {url}"),
+                Err(e) => log::info!("Failed to navigate to {url}: {e}"),
+            }
+        }
+        result
+    }
+
+    pub async fn find(&self, locator: Locator<'_>) -> Result<Element, CmdError> {
+        let result = self.client.find(locator).await;
+        self.log_lookup(locator, result.is_ok()).await;
+        result
+    }
+
+    pub async fn find_all(&self, locator: Locator<'_>) -> Result<Vec<Element>, CmdError> {
+        let result = self.client.find_all(locator).await;
+        let matched = result.as_ref().is_ok_and(|elements| !elements.is_empty());
+        self.log_lookup(locator, matched).await;
+        result
+    }
+
+    async fn log_lookup(&self, locator: Locator<'_>, matched: bool) {
+        if self.settings.navigation_log == NavigationLog::Off {
+            return;
+        }
+        let url = match self.client.current_url().await {
+            Ok(url) => url.to_string(),
+            Err(_) => "<unknown page>".to_string(),
+        };
+        let outcome = if matched { "matched" } else { "NOT matched" };
+        if self.settings.navigation_log == NavigationLog::Counts {
+            let count = self
+                .client
+                .find_all(locator)
+                .await
+                .map_or(0, |elements| elements.len());
+            log::info!("{locator:?} {outcome} {count} element(s) on {url}");
+        } else {
+            log::info!("{locator:?} {outcome} on {url}");
+        }
+    }
+
+    /// Go to `url` and get the cookie consent banner out of the way, since it covers buttons
+    /// (like the login form's submit) that the scrapers need to click. Waits the delay that rate
+    /// limits added first (the pause after one is waited out by [`Browser::session`]), and fails
+    /// if Fiverr answers with a rate limit page.
+    pub async fn navigate(&self, url: &str) -> Result<()> {
+        let wait = self.backoff.wait_time(Instant::now());
+        if !wait.is_zero() {
+            log::debug!("Waiting {wait:?} before navigating to {url}");
+            sleep(wait).await;
+        }
+        self.return_to_main_window().await?;
+        self.goto(url)
+            .await
+            .map_err(|e| CommandError::new(format!("navigate to {url}"), None, e))?;
+        self.check_rate_limit().await?;
+        self.dismiss_cookie_banner().await?;
+        Ok(())
+    }
+
+    /// Switch back to the main window if something else has focus, and close the other windows
+    /// unless `windows.stray` keeps them. The first window seen becomes the main one, as does the
+    /// remaining window if the main one was closed.
+    async fn return_to_main_window(&self) -> Result<(), CmdError> {
+        let windows = self.client.windows().await?;
+        let known = self.main_window.lock().unwrap().clone();
+        let main = match known.filter(|main| windows.contains(main)) {
+            Some(main) => main,
+            None => {
+                let current = self.client.window().await?;
+                *self.main_window.lock().unwrap() = Some(current.clone());
+                current
+            }
+        };
+        if windows.len() == 1 {
+            return Ok(());
+        }
+        let strays = windows.into_iter().filter(|window| *window != main);
+        if self.settings.windows.stray == StrayWindows::Close {
+            for stray in strays {
+                log::debug!("Closing stray window {stray:?}");
+                self.client.switch_to_window(stray).await?;
+                self.client.close_window().await?;
+            }
+        }
+        self.client.switch_to_window(main).await
+    }
+
+    /// Run `open`, which is expected to open a new window (e.g. by clicking a link with
+    /// `target="_blank"`), then run `op` in that window. The window is closed and the one that was
+    /// active before gets focus back afterwards, also when `op` fails.
+    pub async fn with_new_window<T, O, OFut, F, Fut>(&self, open: O, op: F) -> Result<T>
+    where
+        O: FnOnce(Client) -> OFut,
+        OFut: Future<Output = Result<(), CmdError>>,
+        F: FnOnce(Client) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let previous = self.client.window().await?;
+        let before = self.client.windows().await?;
+        open(self.client.clone()).await?;
+        let deadline =
+            Instant::now() + Duration::from_millis(self.settings.windows.new_window_timeout_ms);
+        let opened = loop {
+            let after = self.client.windows().await?;
+            if let Some(opened) = new_window(&before, after) {
+                break opened;
+            }
+            if Instant::now() >= deadline {
+                bail!("No new window opened");
+            }
+            sleep(Duration::from_millis(100)).await;
+        };
+        self.client.switch_to_window(opened).await?;
+        let result = op(self.client.clone()).await;
+        self.client.close_window().await?;
+        self.client.switch_to_window(previous).await?;
+        result
+    }
+
+    async fn check_rate_limit(&self) -> Result<()> {
+        let rate_limit = &self.settings.rate_limit;
+        let title = self.client.title().await?.to_lowercase();
+        let limited = rate_limit
+            .title_markers
+            .iter()
+            .any(|marker| title.contains(&marker.to_lowercase()));
+        if !limited {
+            return Ok(());
+        }
+        let cooldown = Duration::from_secs(rate_limit.cooldown_secs);
+        self.backoff.hit(
+            Instant::now(),
+            cooldown,
+            Duration::from_millis(rate_limit.delay_step_ms),
+        );
+        log::warn!("Fiverr rate limited the browser, pausing navigation for {cooldown:?}");
+        bail!("Rate limited, backing off for {cooldown:?}")
+    }
+
+    async fn dismiss_cookie_banner(&self) -> Result<()> {
+        let button = match self.settings.cookie_consent {
+            CookieConsent::Accept => &self.settings.selectors.cookie_accept,
+            CookieConsent::Reject => &self.settings.selectors.cookie_reject,
+            CookieConsent::Ignore => return Ok(()),
+        };
+        match button.find(self).await {
+            Ok(button) => {
+                button.click().await?;
+                log::debug!(
+                    "Dismissed cookie banner ({:?})",
+                    self.settings.cookie_consent
+                );
+                Ok(())
+            }
+            Err(e) if e.is_miss() => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Capture the current URL and cookies. With `redact`, the values of sensitive cookies are
+    /// replaced so the snapshot can be shared without handing out the login.
+    pub async fn snapshot(&self, redact: bool) -> Result<SessionSnapshot> {
+        let url = self.current_url().await?.to_string();
+        let mut cookies: Vec<SnapshotCookie> = self
+            .get_all_cookies()
+            .await?
+            .iter()
+            .map(SnapshotCookie::from)
+            .collect();
+        if redact {
+            for cookie in cookies.iter_mut().filter(|cookie| cookie.is_sensitive()) {
+                cookie.value = REDACTED.to_string();
+            }
+        }
+        Ok(SessionSnapshot { url, cookies })
+    }
+
+    /// Save a screenshot of the current page to `path` as a PNG, creating its directory.
+    pub async fn save_screenshot(&self, path: &Path) -> Result<()> {
+        let png = self.screenshot().await?;
+        write_creating_dir(path, png)
+    }
+
+    /// Save the HTML of the current page and a screenshot of it to `dir` as `<name>.html` and
+    /// `<name>.png`, returning both paths.
+    pub async fn dump_page(&self, dir: &Path, name: &str) -> Result<(PathBuf, PathBuf)> {
+        let (html_path, png_path) = (
+            dir.join(format!("{name}.html")),
+            dir.join(format!("{name}.png")),
+        );
+        write_creating_dir(&html_path, self.source().await?)?;
+        self.save_screenshot(&png_path).await?;
+        Ok((html_path, png_path))
+    }
+
+    /// Load the cookies of `snapshot` into the browser and open its page with them.
+    pub async fn restore(&self, snapshot: &SessionSnapshot) -> Result<()> {
+        // Cookies can only be set for the site that is open.
+        self.goto(&snapshot.url).await?;
+        for cookie in &snapshot.cookies {
+            self.add_cookie(cookie.to_cookie()).await?;
+        }
+        self.refresh().await?;
+        Ok(())
+    }
+
+    /// Scroll to the bottom of the page until the number of elements matching `selector` is the
+    /// same on two polls in a row, if waiting is configured for `view`. Gives up after the
+    /// configured timeout and leaves the scrape to work with what has loaded.
+    pub async fn wait_for_lazy_load(&self, view: View, selector: &Selector) -> Result<()> {
+        let lazy_load = &self.settings.lazy_load;
+        if !lazy_load.views.contains(&view) {
+            return Ok(());
+        }
+        let deadline = Instant::now() + Duration::from_millis(lazy_load.timeout_ms);
+        let mut last_count = None;
+        loop {
+            self.client
+                .execute(
+                    "window.scrollTo(0, document.body.scrollHeight);",
+                    Vec::new(),
+                )
+                .await?;
+            sleep(Duration::from_millis(lazy_load.poll_ms)).await;
+            let count = selector.find_all(&self.client).await?.len();
+            self.progress(&format!("Loading {view}"), count, None);
+            if last_count == Some(count) {
+                log::debug!("{view} view settled with {count} item(s)");
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                log::warn!("{view} view still loading after {count} item(s), scraping anyway");
+                return Ok(());
+            }
+            last_count = Some(count);
+        }
+    }
+
+    /// Run `op` again when it fails because the page re-rendered between finding an element and
+    /// using it, up to `stale_element_retries` times. `op` is handed the session on every attempt
+    /// and must look its elements up again rather than reuse ones from a failed attempt.
+    pub async fn retry_on_stale<'s, T, F, Fut>(&'s self, mut op: F) -> Result<T>
+    where
+        F: FnMut(&'s Session) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match op(self).await {
+                Err(e) if is_stale(&e) && attempt < self.settings.stale_element_retries => {
+                    attempt += 1;
+                    log::debug!(
+                        "Stale element, retrying ({attempt}/{})",
+                        self.settings.stale_element_retries
+                    );
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// A window in `after` that isn't in `before`.
+fn new_window(before: &[WindowHandle], after: Vec<WindowHandle>) -> Option<WindowHandle> {
+    after.into_iter().find(|window| !before.contains(window))
+}
+
+/// Whether `e` is, or wraps, a stale element reference.
+fn is_stale(e: &color_eyre::Report) -> bool {
+    let source = e
+        .downcast_ref::<CommandError>()
+        .map(|e| &e.source)
+        .or_else(|| e.downcast_ref::<CmdError>());
+    source.is_some_and(is_stale_element)
+}
+
+fn is_stale_element(e: &CmdError) -> bool {
+    // fantoccini has no variant of its own for stale references, they come as standard errors.
+    matches!(e, CmdError::Standard(wd) if wd.error() == "stale element reference")
+}
+
+impl Browser {
+    /// Create a handle with no session attached yet; see [`Browser::attach`].
+    pub fn new(max_ops: usize) -> Self {
+        let max_ops = max_ops.max(1);
+        Self {
+            client: Arc::new(Mutex::new(None)),
+            permits: Arc::new(Semaphore::new(max_ops)),
+            max_ops: max_ops as u32,
+            settings: Arc::new(Settings::default()),
+            backoff: Arc::new(Backoff::default()),
+            main_window: Arc::new(StdMutex::new(None)),
+        }
+    }
+
+    pub fn cookie_consent(mut self, cookie_consent: CookieConsent) -> Self {
+        Arc::make_mut(&mut self.settings).cookie_consent = cookie_consent;
+        self
+    }
+
+    pub fn navigation_log(mut self, navigation_log: NavigationLog) -> Self {
+        Arc::make_mut(&mut self.settings).navigation_log = navigation_log;
+        self
+    }
+
+    pub fn selectors(mut self, selectors: Selectors) -> Self {
+        Arc::make_mut(&mut self.settings).selectors = selectors;
+        self
+    }
+
+    pub fn lazy_load(mut self, lazy_load: LazyLoad) -> Self {
+        Arc::make_mut(&mut self.settings).lazy_load = lazy_load;
+        self
+    }
+
+    pub fn rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        Arc::make_mut(&mut self.settings).rate_limit = rate_limit;
+        self
+    }
+
+    /// Send the progress sessions report as `Action::Progress` to `tx`.
+    pub fn progress(mut self, tx: UnboundedSender<Action>) -> Self {
+        Arc::make_mut(&mut self.settings).progress_tx = Some(tx);
+        self
+    }
+
+    pub fn windows(mut self, windows: Windows) -> Self {
+        Arc::make_mut(&mut self.settings).windows = windows;
+        self
+    }
+
+    pub fn stale_element_retries(mut self, stale_element_retries: usize) -> Self {
+        Arc::make_mut(&mut self.settings).stale_element_retries = stale_element_retries;
+        self
+    }
+
+    /// Hand the connected client to every clone of this handle.
+    pub async fn attach(&self, client: Client) {
+        *self.client.lock().await = Some(client);
+    }
+
+    /// Wait for a free permit and return the client to drive. While Fiverr rate limits the
+    /// browser, the pause is waited out before taking a permit, so it doesn't hold up the others.
+    pub async fn session(&self) -> Result<Session> {
+        let pause = self.backoff.pause_time(Instant::now());
+        if !pause.is_zero() {
+            log::debug!("Waiting {pause:?} for the rate limit to pass");
+            sleep(pause).await;
+        }
+        let permit = self.permits.clone().acquire_owned().await?;
+        let client = self
+            .client
+            .lock()
+            .await
+            .clone()
+            .ok_or_else(|| eyre!("Not connected to WebDriver"))?;
+        Ok(Session {
+            client,
+            _permit: permit,
+            settings: self.settings.clone(),
+            backoff: self.backoff.clone(),
+            main_window: self.main_window.clone(),
+        })
+    }
+
+    /// Whether navigations are paused after a rate limit page, so [`Browser::session`] would wait.
+    pub fn is_paused(&self) -> bool {
+        !self.backoff.pause_time(Instant::now()).is_zero()
+    }
+
+    pub async fn is_connected(&self) -> bool {
+        self.client.lock().await.is_some()
+    }
+
+    /// Issue a cheap command every `interval` to keep the session from idling out, until one
+    /// fails. Returns that error, or `Ok` once the session has been closed on purpose.
+    pub async fn heartbeat(&self, interval: Duration) -> Result<()> {
+        loop {
+            sleep(interval).await;
+            if !self.is_connected().await {
+                return Ok(());
+            }
+            self.session().await?.current_url().await?;
+        }
+    }
+
+    /// Wait for every in-flight operation to release its permit, then end the session.
+    pub async fn close(&self) -> Result<()> {
+        let _permits = self.permits.acquire_many(self.max_ops).await?;
+        if let Some(client) = self.client.lock().await.take() {
+            client.close().await?;
+        }
+        Ok(())
+    }
+}
+
+/// Write `contents` to `path`, creating the directory it is in first.
+pub fn write_creating_dir(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, contents).wrap_err_with(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex as StdMutex;
+
+    use fantoccini::ClientBuilder;
+    use serde_json::{json, Value as JsonValue};
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+        sync::oneshot,
+        time::{timeout, Duration},
+    };
+
+    use super::*;
+    use crate::tasks::Tasks;
+
+    /// A WebDriver server on a free port that answers every command with success and records
+    /// the method and path of each. The session it creates is called `fake`.
+    async fn fake_webdriver() -> (u16, Arc<StdMutex<Vec<String>>>) {
+        fake_webdriver_failing(&[]).await
+    }
+
+    /// Like [`fake_webdriver`], but answers the requests in `errors`, e.g.
+    /// `"POST /session/fake/element"`, with the WebDriver error code next to them.
+    async fn fake_webdriver_failing(
+        errors: &'static [(&'static str, &'static str)],
+    ) -> (u16, Arc<StdMutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(StdMutex::new(Vec::new()));
+        let log = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let log = log.clone();
+                tokio::spawn(async move {
+                    let mut stream = BufReader::new(stream);
+                    loop {
+                        let mut request_line = String::new();
+                        if stream.read_line(&mut request_line).await.unwrap_or(0) == 0 {
+                            return;
+                        }
+                        let mut content_length = 0;
+                        loop {
+                            let mut header = String::new();
+                            stream.read_line(&mut header).await.unwrap();
+                            if header.trim().is_empty() {
+                                break;
+                            }
+                            if let Some((name, value)) = header.split_once(':') {
+                                if name.eq_ignore_ascii_case("content-length") {
+                                    content_length = value.trim().parse().unwrap();
+                                }
+                            }
+                        }
+                        let mut body = vec![0; content_length];
+                        stream.read_exact(&mut body).await.unwrap();
+
+                        let request: Vec<&str> = request_line.split_whitespace().take(2).collect();
+                        let request = request.join(" ");
+                        let value = match request.as_str() {
+                            "POST /session" => json!({ "sessionId": "fake", "capabilities": {} }),
+                            "GET /session/fake/url" => json!("about:blank"),
+                            _ => JsonValue::Null,
+                        };
+                        let error = errors.iter().find(|(failing, _)| *failing == request);
+                        let (status, value) = match error {
+                            Some((_, error)) => (
+                                "400 Bad Request",
+                                json!({ "error": error, "message": error, "stacktrace": "" }),
+                            ),
+                            None => ("200 OK", value),
+                        };
+                        log.lock().unwrap().push(request);
+                        let body = json!({ "value": value }).to_string();
+                        let response = format!(
+                            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\n\
+                             Content-Length: {}\r\n\r\n{body}",
+                            body.len()
+                        );
+                        stream
+                            .get_mut()
+                            .write_all(response.as_bytes())
+                            .await
+                            .unwrap();
+                    }
+                });
+            }
+        });
+        (port, requests)
+    }
+
+    #[tokio::test]
+    async fn test_close_after_quit_during_fetch() {
+        let browser = Browser::new(1);
+        let mut tasks = Tasks::new();
+        let permits = browser.permits.clone();
+        let (started_tx, started_rx) = oneshot::channel();
+        // A fetch that holds the session and never completes on its own.
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await.unwrap();
+            started_tx.send(()).unwrap();
+            std::future::pending::<()>().await;
+        });
+        started_rx.await.unwrap();
+
+        tasks.shutdown().await;
+        timeout(Duration::from_secs(1), browser.close())
+            .await
+            .expect("close should not wait on a cancelled fetch")
+            .unwrap();
+        assert!(!browser.is_connected().await);
+    }
+
+    #[test]
+    fn test_new_window() {
+        let handle = |name: &str| WindowHandle::try_from(name.to_string()).unwrap();
+        let before = [handle("main")];
+        assert_eq!(new_window(&before, vec![handle("main")]), None);
+        assert_eq!(
+            new_window(&before, vec![handle("popup"), handle("main")]),
+            Some(handle("popup"))
+        );
+    }
+
+    #[test]
+    fn test_command_error_context() {
+        let selector: Selector = serde_json::from_str(r#"[".gig-title a", ".title a"]"#).unwrap();
+        let e = CommandError::new("find", Some(&selector), CmdError::WaitTimeout);
+        assert_eq!(
+            e.to_string(),
+            "Failed to find `.gig-title a | .title a`: timed out waiting"
+        );
+        assert!(e.is_timeout() && !e.is_miss());
+        let report = color_eyre::Report::from(CommandError::new(
+            "navigate to https://www.fiverr.com",
+            None,
+            CmdError::NotJson("<html>".to_string()),
+        ));
+        assert!(format!("{report:#}").starts_with(
+            "Failed to navigate to https://www.fiverr.com: WebDriver sent an answer stash \
+             doesn't understand: "
+        ));
+        assert!(!is_stale(&report));
+    }
+
+    /// The error a real WebDriver server gives for an element that went away.
+    async fn stale_element_error() -> CmdError {
+        let (port, _) =
+            fake_webdriver_failing(&[("POST /session/fake/element", "stale element reference")])
+                .await;
+        let client = ClientBuilder::native()
+            .connect(&format!("http://127.0.0.1:{port}"))
+            .await
+            .unwrap();
+        client.find(Locator::Css(".gig")).await.unwrap_err()
+    }
+
+    #[tokio::test]
+    async fn test_stale_element() {
+        let e = stale_element_error().await;
+        assert!(is_stale_element(&e));
+        assert_eq!(
+            describe(&e),
+            "the element went away when the page re-rendered"
+        );
+        assert!(is_stale(&color_eyre::Report::from(CommandError::new(
+            "find", None, e
+        ))));
+    }
+
+    #[test]
+    fn test_backoff() {
+        let backoff = Backoff::default();
+        let now = Instant::now();
+        assert_eq!(backoff.wait_time(now), Duration::ZERO);
+
+        let second = Duration::from_secs(1);
+        backoff.hit(now, Duration::from_secs(60), second);
+        assert_eq!(backoff.wait_time(now), Duration::from_secs(61));
+        assert_eq!(backoff.wait_time(now + Duration::from_secs(90)), second);
+
+        backoff.hit(now, Duration::ZERO, second);
+        assert_eq!(backoff.wait_time(now), 2 * second);
+        assert_eq!(backoff.pause_time(now), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_pause_holds_no_permit() {
+        let browser = Browser::new(1);
+        let cooldown = Duration::from_millis(200);
+        browser
+            .backoff
+            .hit(Instant::now(), cooldown, Duration::ZERO);
+        assert!(browser.is_paused());
+        let waiting = tokio::spawn({
+            let browser = browser.clone();
+            async move { browser.session().await.map(|_| ()) }
+        });
+        sleep(cooldown / 4).await;
+        assert!(!waiting.is_finished());
+        assert_eq!(browser.permits.available_permits(), 1);
+        // Not connected, but it got as far as taking the permit once the pause was over.
+        assert!(waiting.await.unwrap().is_err());
+        assert!(!browser.is_paused());
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_ends_with_session() {
+        let (port, requests) = fake_webdriver().await;
+        let client = ClientBuilder::native()
+            .connect(&format!("http://127.0.0.1:{port}"))
+            .await
+            .unwrap();
+        let browser = Browser::new(1);
+        browser.attach(client).await;
+        let heartbeat = tokio::spawn({
+            let browser = browser.clone();
+            async move { browser.heartbeat(Duration::from_millis(5)).await }
+        });
+        sleep(Duration::from_millis(50)).await;
+        browser.close().await.unwrap();
+
+        let result = timeout(Duration::from_secs(1), heartbeat).await.unwrap();
+        assert!(result.unwrap().is_ok());
+        let requests = requests.lock().unwrap();
+        assert!(requests.iter().any(|r| r == "GET /session/fake/url"));
+    }
+
+    /// The order `Action::Quit` tears things down in: background tasks first, then the session.
+    #[tokio::test]
+    async fn test_shutdown_order() {
+        let (port, requests) = fake_webdriver().await;
+        let client = ClientBuilder::native()
+            .connect(&format!("http://127.0.0.1:{port}"))
+            .await
+            .unwrap();
+        let browser = Browser::new(1);
+        browser.attach(client).await;
+        let mut tasks = Tasks::new();
+        let poller = browser.clone();
+        tasks.spawn(async move {
+            loop {
+                if let Ok(session) = poller.session().await {
+                    let _ = session.current_url().await;
+                }
+                sleep(Duration::from_millis(5)).await;
+            }
+        });
+        let count = |request: &str| {
+            let requests = requests.lock().unwrap();
+            requests.iter().filter(|r| r.as_str() == request).count()
+        };
+        timeout(Duration::from_secs(1), async {
+            while count("GET /session/fake/url") < 2 {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("the poller should be driving the browser");
+
+        tasks.shutdown().await;
+        browser.close().await.unwrap();
+        let after_close = requests.lock().unwrap().clone();
+        assert_eq!(after_close.last().unwrap(), "DELETE /session/fake");
+        assert_eq!(count("DELETE /session/fake"), 1);
+
+        // Nothing is left to talk to the server, and closing again doesn't end the session twice.
+        browser.close().await.unwrap();
+        assert!(browser.session().await.is_err());
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(*requests.lock().unwrap(), after_close);
+    }
+}
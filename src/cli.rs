@@ -2,7 +2,7 @@ use std::path::PathBuf;
 
 use clap::Parser;
 
-use crate::utils::version;
+use crate::{app::DEFAULT_PROFILE, utils::version};
 
 #[derive(Parser, Debug)]
 #[command(author, version = version(), about)]
@@ -24,4 +24,30 @@ pub struct Cli {
         default_value_t = 60.0
     )]
     pub frame_rate: f64,
+
+    #[arg(
+        short,
+        long,
+        value_name = "PATH",
+        help = "Config file to use instead of the one in the config directory"
+    )]
+    pub config: Option<PathBuf>,
+
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Account profile to use; each profile gets its own WebDriver server and session",
+        default_value = DEFAULT_PROFILE
+    )]
+    pub profile: String,
+
+    #[arg(long, help = "Draw without colors, like setting NO_COLOR")]
+    pub no_color: bool,
+
+    #[arg(
+        long,
+        help = "Run the browser without a window, like setting STASH_HEADLESS"
+    )]
+    pub headless: bool,
 }
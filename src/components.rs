@@ -1,23 +1,75 @@
 use color_eyre::eyre::Result;
 use crossterm::event::{KeyEvent, MouseEvent};
-use ratatui::layout::Rect;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use tokio::sync::mpsc::UnboundedSender;
 
 use crate::{
     action::Action,
     config::Config,
+    mode::Mode,
     tui::{Event, Frame},
 };
 
+pub mod bulk_message;
+pub mod command_palette;
+pub mod confirm;
 pub mod fps;
+pub mod gigs;
+pub mod help;
 pub mod home;
+pub mod item_list;
+pub mod jump;
+pub mod list_view;
+pub mod log_view;
 pub mod login;
+pub mod messages;
+pub mod notifications;
+pub mod orders;
+pub mod otp;
+pub mod requirements;
+pub mod settings;
+pub mod status_bar;
+pub mod tab_bar;
+pub mod thread;
+
+/// The part of the screen a component is drawn into.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Region {
+    /// The first row of the screen.
+    TabLine,
+    /// Everything between the tab line and the status line.
+    #[default]
+    Main,
+    /// The last row of the screen.
+    StatusLine,
+}
+
+/// A rectangle of `percent_x` by `percent_y` of `area`, centered inside it. Used to place popups.
+pub fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
 
 /// `Component` is a trait that represents a visual and interactive element of the user interface.
 /// Implementors of this trait can be registered with the main application loop and will be able to receive events,
 /// update state, and be rendered on the screen.
 pub trait Component {
-    /// Register an action handler that can send actions for processing if necessary.
+    /// Register an action handler that can send actions for processing if necessary. Components
+    /// that only produce actions as return values can keep the default, which drops `tx`.
     ///
     /// # Arguments
     ///
@@ -43,6 +95,84 @@ pub trait Component {
     fn register_config_handler(&mut self, config: Config) -> Result<()> {
         Ok(())
     }
+    /// The part of the screen the component should be drawn into.
+    ///
+    /// # Returns
+    ///
+    /// * `Region` - The region passed to `draw` as its area.
+    fn region(&self) -> Region {
+        Region::Main
+    }
+    /// Whether the component can take keyboard focus. Key events only reach the focused one of
+    /// the focusable components; components that aren't focusable always receive them.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether `Action::FocusNext` can move focus to this component.
+    fn is_focusable(&self) -> bool {
+        false
+    }
+    /// Tell the component whether it has keyboard focus, e.g. to highlight its border.
+    ///
+    /// # Arguments
+    ///
+    /// * `focused` - Whether the component is now focused.
+    #[allow(unused_variables)]
+    fn set_focus(&mut self, focused: bool) {}
+    /// Whether the component takes all key events for itself, e.g. while text is typed into it.
+    /// Keybindings are not looked up and no other component receives key events meanwhile.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the component currently captures the keyboard.
+    fn captures_keys(&self) -> bool {
+        false
+    }
+    /// The modes the component is drawn in: by default the mode of its `view_mode`, or none for
+    /// components drawn in every mode, like the status line and popups.
+    ///
+    /// # Returns
+    ///
+    /// * `&[Mode]` - The modes the app draws the component in, or none for all.
+    fn modes(&self) -> &[Mode] {
+        self.view_mode().map_or(&[], Mode::only)
+    }
+    /// Called when the app switches to one of the component's `modes`, before the component
+    /// receives the `Action::SwitchMode`, e.g. to fetch fresh data or scroll back to the top.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<Action>>` - An action to be processed or none.
+    fn on_enter(&mut self) -> Result<Option<Action>> {
+        Ok(None)
+    }
+    /// Called when the app switches away from the component's `modes`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - An Ok result or an error.
+    fn on_exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+    /// The mode whose view this component is, if it is one. A view can be split into two panes
+    /// with `Action::ToggleSplit`, the second of which is another instance of the component.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Mode>` - The mode the component shows the main content of.
+    fn view_mode(&self) -> Option<Mode> {
+        None
+    }
+    /// Whether the component changed since it was last drawn in a way the app can't tell from the
+    /// actions and events it handles, e.g. an animation advancing on `Action::Tick`. The app
+    /// redraws after any action other than `Tick` and `Render` anyway.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the next `Action::Render` has to redraw the screen.
+    fn is_dirty(&self) -> bool {
+        false
+    }
     /// Initialize the component with a specified area if necessary.
     ///
     /// # Arguments
@@ -55,7 +185,8 @@ pub trait Component {
     fn init(&mut self, area: Rect) -> Result<()> {
         Ok(())
     }
-    /// Handle incoming events and produce actions if necessary.
+    /// Handle incoming events and produce actions if necessary. Key events that the keybindings
+    /// used, for an action or a chord in progress, are not passed on.
     ///
     /// # Arguments
     ///
@@ -68,6 +199,7 @@ pub trait Component {
         let r = match event {
             Some(Event::Key(key_event)) => self.handle_key_events(key_event)?,
             Some(Event::Mouse(mouse_event)) => self.handle_mouse_events(mouse_event)?,
+            Some(Event::Paste(text)) => self.handle_paste_events(text)?,
             _ => None,
         };
         Ok(r)
@@ -98,6 +230,20 @@ pub trait Component {
     fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
         Ok(None)
     }
+    /// Handle text pasted into the terminal and produce actions if necessary. Components that
+    /// take typed input insert it in one go. Pasted text goes where key events go.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The pasted text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<Action>>` - An action to be processed or none.
+    #[allow(unused_variables)]
+    fn handle_paste_events(&mut self, text: String) -> Result<Option<Action>> {
+        Ok(None)
+    }
     /// Update the state of the component based on a received action. (REQUIRED)
     ///
     /// # Arguments
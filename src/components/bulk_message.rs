@@ -0,0 +1,261 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{centered_rect, Component, Frame};
+use crate::{
+    action::{Action, SendOutcome},
+    templates,
+};
+
+enum State {
+    Closed,
+    /// The message is being typed.
+    Compose {
+        thread_ids: Vec<String>,
+        text: String,
+    },
+    Confirm {
+        thread_ids: Vec<String>,
+        text: String,
+    },
+    Sending {
+        total: usize,
+        results: Vec<(String, SendOutcome)>,
+    },
+}
+
+/// Input, confirmation prompt and per-thread results for sending one message to many buyers.
+/// Sending goes on in the background when the results are closed before it is done.
+pub struct BulkMessageComponent {
+    state: State,
+}
+
+impl Default for BulkMessageComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BulkMessageComponent {
+    pub fn new() -> Self {
+        Self {
+            state: State::Closed,
+        }
+    }
+
+    fn compose_text(thread_ids: &[String], text: &str) -> Text<'static> {
+        Text::from(vec![
+            Line::from(format!("> {text}")),
+            Line::from(""),
+            Line::from("{buyer_name} is replaced with the name of each buyer.").dim(),
+            Line::from(format!("To: {}", thread_ids.join(", "))).dim(),
+        ])
+    }
+
+    fn confirm_text(thread_ids: &[String], text: &str) -> Text<'static> {
+        let mut lines = vec![
+            Line::from(match thread_ids.len() {
+                1 => "Send this message to 1 buyer?".to_string(),
+                n => format!("Send this message to {n} buyers?"),
+            })
+            .style(Style::new().bold()),
+            Line::from(""),
+        ];
+        lines.extend(text.lines().map(|line| Line::from(line.to_string())));
+        lines.push(Line::from(""));
+        if let Some(first) = thread_ids.first() {
+            // Show what the variables turn into, e.g. `{buyer_name}`.
+            match templates::render(text, &templates::conversation_variables(first)) {
+                Ok(preview) if preview != text => {
+                    lines.push(Line::from(format!("Preview for {first}:")).dim());
+                    lines.extend(preview.lines().map(|line| Line::from(line.to_string())));
+                    lines.push(Line::from(""));
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    lines.push(Line::from(format!("{e}")).red());
+                    lines.push(Line::from(""));
+                }
+            }
+        }
+        lines.push(Line::from(format!("To: {}", thread_ids.join(", "))).dim());
+        Text::from(lines)
+    }
+
+    fn results_text(total: usize, results: &[(String, SendOutcome)]) -> Text<'static> {
+        let mut lines: Vec<Line> = results
+            .iter()
+            .map(|(thread_id, outcome)| match outcome {
+                SendOutcome::Sent => Line::from(format!("✓ {thread_id}")).green(),
+                SendOutcome::Queued => {
+                    Line::from(format!("… {thread_id}: queued until the active hours")).yellow()
+                }
+                SendOutcome::Failed(e) => Line::from(format!("✗ {thread_id}: {e}")).red(),
+            })
+            .collect();
+        if results.len() < total {
+            lines.push(Line::from(format!("Sending {}/{total}...", results.len() + 1)).dim());
+        }
+        Text::from(lines)
+    }
+}
+
+impl Component for BulkMessageComponent {
+    fn captures_keys(&self) -> bool {
+        !matches!(self.state, State::Closed)
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        let action = match &mut self.state {
+            State::Closed => None,
+            State::Compose { thread_ids, text } => {
+                match key.code {
+                    KeyCode::Esc => self.state = State::Closed,
+                    KeyCode::Enter if !text.trim().is_empty() => {
+                        self.state = State::Confirm {
+                            thread_ids: std::mem::take(thread_ids),
+                            text: std::mem::take(text),
+                        };
+                    }
+                    KeyCode::Backspace => {
+                        text.pop();
+                    }
+                    KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        text.push(c)
+                    }
+                    _ => {}
+                }
+                None
+            }
+            State::Confirm { thread_ids, text } => match key.code {
+                KeyCode::Char('y') => {
+                    let action = Action::SendBulkMessage {
+                        thread_ids: thread_ids.clone(),
+                        text: text.clone(),
+                    };
+                    self.state = State::Sending {
+                        total: thread_ids.len(),
+                        results: Vec::new(),
+                    };
+                    Some(action)
+                }
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.state = State::Closed;
+                    None
+                }
+                _ => None,
+            },
+            State::Sending { .. } => {
+                if key.code == KeyCode::Esc {
+                    self.state = State::Closed;
+                }
+                None
+            }
+        };
+        Ok(action)
+    }
+
+    fn handle_paste_events(&mut self, pasted: String) -> Result<Option<Action>> {
+        if let State::Compose { text, .. } = &mut self.state {
+            text.extend(pasted.chars().filter(|c| *c == '\n' || !c.is_control()));
+        }
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::ComposeBulkMessage(thread_ids) => {
+                self.state = State::Compose {
+                    thread_ids,
+                    text: String::new(),
+                };
+            }
+            Action::BulkMessage { thread_ids, text } => {
+                self.state = State::Confirm { thread_ids, text };
+            }
+            Action::BulkMessageSent { thread_id, outcome } => {
+                if let State::Sending { results, .. } = &mut self.state {
+                    results.push((thread_id, outcome));
+                }
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let (title, hint, text) = match &self.state {
+            State::Closed => return Ok(()),
+            State::Compose { thread_ids, text } => (
+                "Bulk message",
+                "<enter> review  <esc> cancel",
+                Self::compose_text(thread_ids, text),
+            ),
+            State::Confirm { thread_ids, text } => (
+                "Bulk message",
+                "<y> send  <n> cancel",
+                Self::confirm_text(thread_ids, text),
+            ),
+            State::Sending { total, results } => (
+                "Bulk message results",
+                if results.len() == *total {
+                    "<esc> close"
+                } else {
+                    "<esc> close, sending goes on"
+                },
+                Self::results_text(*total, results),
+            ),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .title_bottom(Line::from(hint).right_aligned());
+        let area = centered_rect(60, 60, area);
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(text).block(block).wrap(Wrap { trim: false }),
+            area,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_compose_and_send() {
+        let mut bulk = BulkMessageComponent::new();
+        let threads = vec!["alice".to_string(), "bob".to_string()];
+        bulk.update(Action::ComposeBulkMessage(threads.clone()))
+            .unwrap();
+        assert!(bulk.captures_keys());
+        let mut key = |code| bulk.handle_key_events(KeyEvent::from(code)).unwrap();
+        assert_eq!(key(KeyCode::Enter), None);
+        for c in "Hi!".chars() {
+            key(KeyCode::Char(c));
+        }
+        key(KeyCode::Enter);
+        assert_eq!(
+            key(KeyCode::Char('y')),
+            Some(Action::SendBulkMessage {
+                thread_ids: threads,
+                text: "Hi!".to_string(),
+            })
+        );
+
+        bulk.update(Action::BulkMessageSent {
+            thread_id: "alice".to_string(),
+            outcome: SendOutcome::Sent,
+        })
+        .unwrap();
+        // Closing before every thread reported leaves the rest to send in the background.
+        bulk.handle_key_events(KeyEvent::from(KeyCode::Esc))
+            .unwrap();
+        assert!(!bulk.captures_keys());
+    }
+}
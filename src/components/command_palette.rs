@@ -0,0 +1,240 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{centered_rect, Component, Frame};
+use crate::{
+    action::Action,
+    config::{key_sequence_to_string, Config},
+};
+
+/// An entry of the palette: an action, what it does and the keys bound to it.
+#[derive(Debug, Clone)]
+struct Command {
+    action: Action,
+    description: &'static str,
+    keys: String,
+}
+
+impl Command {
+    /// What the query is matched against.
+    fn haystack(&self) -> String {
+        format!("{} {}", self.action, self.description)
+    }
+}
+
+/// Popup opened by `Action::OpenCommandPalette` that lists every command with its keybindings.
+/// Typing narrows the list down, Enter runs the selected command.
+#[derive(Default)]
+pub struct CommandPalette {
+    open: bool,
+    query: String,
+    commands: Vec<Command>,
+    /// Indices into `commands` that match `query`, best match first.
+    matches: Vec<usize>,
+    state: ListState,
+}
+
+impl CommandPalette {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+        self.query.clear();
+    }
+
+    fn filter(&mut self) {
+        let mut matches: Vec<(usize, usize)> = self
+            .commands
+            .iter()
+            .enumerate()
+            .filter_map(|(i, command)| Some((fuzzy_score(&self.query, &command.haystack())?, i)))
+            .collect();
+        matches.sort();
+        self.matches = matches.into_iter().map(|(_, i)| i).collect();
+        self.state.select((!self.matches.is_empty()).then_some(0));
+    }
+
+    fn selected(&self) -> Option<&Command> {
+        let i = self.matches.get(self.state.selected()?)?;
+        self.commands.get(*i)
+    }
+
+    fn move_selection(&mut self, down: bool) {
+        let Some(selected) = self.state.selected() else {
+            return;
+        };
+        let selected = if down {
+            (selected + 1).min(self.matches.len() - 1)
+        } else {
+            selected.saturating_sub(1)
+        };
+        self.state.select(Some(selected));
+    }
+}
+
+/// How well `query` matches `text`, lower is better, or `None` if it doesn't. Every character of
+/// the query has to appear in `text` in order, ignoring case; matches with the characters closer
+/// together and nearer the start score better.
+fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let text = text.to_lowercase();
+    let mut chars = text.char_indices();
+    let mut score = 0;
+    let mut last = None;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let (i, _) = chars.find(|(_, c)| *c == q)?;
+        score += match last {
+            Some(last) => i - last - 1,
+            None => i,
+        };
+        last = Some(i);
+    }
+    Some(score)
+}
+
+impl Component for CommandPalette {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.commands = Action::commands()
+            .into_iter()
+            .map(|(action, description)| {
+                let mut keys: Vec<String> = config
+                    .keybindings
+                    .all()
+                    .flat_map(|keymap| keymap.iter())
+                    .filter(|(_, bound)| **bound == action)
+                    .map(|(keys, _)| key_sequence_to_string(keys))
+                    .collect();
+                keys.sort();
+                keys.dedup();
+                Command {
+                    action,
+                    description,
+                    keys: keys.join(" "),
+                }
+            })
+            .collect();
+        self.filter();
+        Ok(())
+    }
+
+    fn captures_keys(&self) -> bool {
+        self.open
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.open {
+            return Ok(None);
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => {
+                let action = self.selected().map(|command| command.action.clone());
+                self.close();
+                return Ok(action);
+            }
+            KeyCode::Down => self.move_selection(true),
+            KeyCode::Up => self.move_selection(false),
+            KeyCode::Char('n') if ctrl => self.move_selection(true),
+            KeyCode::Char('p') if ctrl => self.move_selection(false),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.filter();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.filter();
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_paste_events(&mut self, text: String) -> Result<Option<Action>> {
+        if self.open {
+            self.query.extend(text.chars().filter(|c| !c.is_control()));
+            self.filter();
+        }
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if action == Action::OpenCommandPalette {
+            self.open = true;
+            self.query.clear();
+            self.filter();
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.open {
+            return Ok(());
+        }
+        let area = centered_rect(60, 50, area);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Commands")
+            .title_bottom(Line::from("<enter> run  <esc> close").right_aligned());
+        let inner = block.inner(area);
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+
+        let [input_area, list_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+        f.render_widget(Paragraph::new(format!("> {}", self.query)), input_area);
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(|i| {
+                let command = &self.commands[*i];
+                ListItem::new(Line::from(vec![
+                    Span::raw(command.description),
+                    Span::raw("  "),
+                    Span::styled(command.keys.clone(), Style::default().dark_gray()),
+                ]))
+            })
+            .collect();
+        let list = List::new(items).highlight_style(Style::default().reversed());
+        f.render_stateful_widget(list, list_area, &mut self.state);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "Quit"), Some(0));
+        assert_eq!(fuzzy_score("quit", "Quit the application"), Some(0));
+        assert_eq!(fuzzy_score("qa", "Quit the application"), Some(8));
+        assert_eq!(fuzzy_score("tq", "Quit"), None);
+    }
+
+    #[test]
+    fn test_filter_orders_best_match_first() {
+        let mut palette = CommandPalette::new();
+        palette.register_config_handler(Config::default()).unwrap();
+        palette.update(Action::OpenCommandPalette).unwrap();
+        for c in "edit".chars() {
+            palette
+                .handle_key_events(KeyEvent::from(KeyCode::Char(c)))
+                .unwrap();
+        }
+        assert_eq!(
+            palette.selected().map(|command| &command.action),
+            Some(&Action::EditConfig)
+        );
+        let action = palette
+            .handle_key_events(KeyEvent::from(KeyCode::Enter))
+            .unwrap();
+        assert_eq!(action, Some(Action::EditConfig));
+        assert!(!palette.captures_keys());
+    }
+}
@@ -0,0 +1,137 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+use serde::{Deserialize, Serialize};
+
+use super::{centered_rect, Component, Frame};
+use crate::action::Action;
+
+/// A question shown in a popup until the user picks one of its choices or presses Esc.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Prompt {
+    pub title: String,
+    pub text: String,
+    pub choices: Vec<Choice>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Choice {
+    pub key: char,
+    pub label: String,
+    /// Sent when the choice is picked. `None` just closes the prompt.
+    pub action: Option<Action>,
+}
+
+impl Choice {
+    pub fn new(key: char, label: &str, action: Option<Action>) -> Self {
+        Self {
+            key,
+            label: label.to_string(),
+            action,
+        }
+    }
+}
+
+/// Popup opened by `Action::Confirm` that asks before an action with side effects goes ahead.
+#[derive(Default)]
+pub struct ConfirmComponent {
+    prompt: Option<Prompt>,
+}
+
+impl ConfirmComponent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Component for ConfirmComponent {
+    fn captures_keys(&self) -> bool {
+        self.prompt.is_some()
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        let Some(prompt) = &self.prompt else {
+            return Ok(None);
+        };
+        if key.code == KeyCode::Esc {
+            self.prompt = None;
+            return Ok(None);
+        }
+        let picked = prompt
+            .choices
+            .iter()
+            .find(|choice| key.code == KeyCode::Char(choice.key))
+            .map(|choice| choice.action.clone());
+        match picked {
+            Some(action) => {
+                self.prompt = None;
+                Ok(action)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if let Action::Confirm(prompt) = action {
+            self.prompt = Some(prompt);
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let Some(prompt) = &self.prompt else {
+            return Ok(());
+        };
+        let hint = prompt
+            .choices
+            .iter()
+            .map(|choice| format!("<{}> {}", choice.key, choice.label))
+            .collect::<Vec<_>>()
+            .join("  ");
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(prompt.title.clone())
+            .title_bottom(Line::from(hint).right_aligned());
+        let area = centered_rect(50, 30, area);
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(prompt.text.clone())
+                .block(block)
+                .wrap(Wrap { trim: false }),
+            area,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_captures_keys_while_open() {
+        let mut confirm = ConfirmComponent::new();
+        assert!(!confirm.captures_keys());
+        confirm
+            .update(Action::Confirm(Prompt {
+                title: "Quit".to_string(),
+                text: "Quit stash?".to_string(),
+                choices: vec![
+                    Choice::new('y', "quit", Some(Action::Quit)),
+                    Choice::new('n', "stay", None),
+                ],
+            }))
+            .unwrap();
+        assert!(confirm.captures_keys());
+        let key = |c| KeyEvent::from(KeyCode::Char(c));
+        assert_eq!(confirm.handle_key_events(key('q')).unwrap(), None);
+        assert!(confirm.captures_keys());
+        assert_eq!(
+            confirm.handle_key_events(key('y')).unwrap(),
+            Some(Action::Quit)
+        );
+        assert!(!confirm.captures_keys());
+    }
+}
@@ -71,6 +71,11 @@ impl Component for FpsCounter {
         Ok(None)
     }
 
+    fn is_dirty(&self) -> bool {
+        // The rates are measured across frames, so every frame has to be drawn.
+        true
+    }
+
     fn draw(&mut self, f: &mut Frame<'_>, rect: Rect) -> Result<()> {
         let rects = Layout::default()
             .direction(Direction::Vertical)
@@ -0,0 +1,320 @@
+use std::path::PathBuf;
+
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{
+    item_list::{Density, ItemList},
+    list_view::ListView,
+    Component, Frame,
+};
+use crate::{
+    action::Action,
+    config::{Config, View},
+    fiverr::{Gig, GigStatus},
+    history::{self, GigSnapshot},
+    mode::Mode,
+    state::State,
+};
+
+/// Name of the gigs list in the state file.
+const LIST_NAME: &str = "gigs";
+
+/// Table of the seller's gigs, shown in `Mode::Gigs`. `e` opens the selected gig's edit page in
+/// the browser, `a` its analytics and `t` pauses or activates it. `R` scrapes a gig that failed
+/// again. Below the table, a sparkline shows the selected gig's impressions over past scrapes.
+#[derive(Default)]
+pub struct GigsComponent {
+    view: ListView,
+    gigs: ItemList<Gig>,
+    data_dir: PathBuf,
+    /// Every stored snapshot of the gigs, see [`crate::history`].
+    history: Vec<GigSnapshot>,
+}
+
+impl GigsComponent {
+    pub fn new() -> Self {
+        Self {
+            view: ListView::new(
+                "Gigs",
+                "<e>dit  <a>nalytics  <t>oggle status",
+                "You have no gigs yet. Press <r> to read them again.",
+            ),
+            ..Self::default()
+        }
+    }
+
+    fn toggle_pin(&mut self) -> Result<()> {
+        self.gigs.toggle_pin();
+        let pinned = self.gigs.pinned().clone();
+        State::update(&self.data_dir, |state| {
+            state.pinned.insert(LIST_NAME.to_string(), pinned);
+        })
+    }
+
+    fn cells(gig: &Gig, density: Density) -> Vec<Text<'static>> {
+        let mut title = Text::from(gig.title.clone());
+        if density == Density::Detailed {
+            let details = format!(
+                "{} · {} impressions · {} clicks",
+                gig.id, gig.impressions, gig.clicks
+            );
+            title.lines.push(Line::from(details).dark_gray());
+        }
+        let status = match &gig.status {
+            GigStatus::Active => Text::from("Active").green(),
+            GigStatus::Paused => Text::from("Paused").yellow(),
+            GigStatus::Other(label) => Text::from(label.clone()).dark_gray(),
+        };
+        vec![
+            title,
+            status,
+            Text::from(gig.price.clone()),
+            Text::from(gig.orders_in_queue.to_string()),
+        ]
+    }
+
+    fn draw_history(&self, f: &mut Frame<'_>, area: Rect) {
+        let Some(gig) = self.gigs.selected() else {
+            return;
+        };
+        let impressions = history::impressions(&self.history, &gig.id);
+        let block = Block::default().borders(Borders::ALL).title(format!(
+            "Impressions of {} over {} scrape(s)",
+            gig.title,
+            impressions.len()
+        ));
+        // Show the most recent scrapes that fit.
+        let width = block.inner(area).width as usize;
+        let recent = &impressions[impressions.len().saturating_sub(width)..];
+        let sparkline = Sparkline::default().block(block).data(recent).green();
+        f.render_widget(sparkline, area);
+    }
+}
+
+impl Component for GigsComponent {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.data_dir = config.config._data_dir.clone();
+        let mut state = State::load(&self.data_dir)?;
+        self.gigs
+            .set_pinned(state.pinned.remove(LIST_NAME).unwrap_or_default());
+        let limit = config.config.memory_limits.gig_snapshots;
+        self.history = history::load(&self.data_dir, limit)?;
+        Ok(())
+    }
+
+    fn view_mode(&self) -> Option<Mode> {
+        Some(Mode::Gigs)
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.view.is_active()
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.view.set_focus(focused);
+    }
+
+    fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
+        self.view.scroll(mouse, &mut self.gigs);
+        Ok(None)
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.view.is_active() {
+            return Ok(None);
+        }
+        let action = match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.gigs.select_next();
+                None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.gigs.select_previous();
+                None
+            }
+            KeyCode::Char('e') => {
+                let url = self.gigs.selected().and_then(|gig| gig.edit_url.clone());
+                url.map(Action::OpenPage)
+            }
+            KeyCode::Char('a') => {
+                let url = self
+                    .gigs
+                    .selected()
+                    .and_then(|gig| gig.analytics_url.clone());
+                url.map(Action::OpenPage)
+            }
+            KeyCode::Char('t') => {
+                let id = self.gigs.selected().map(|gig| gig.id.clone());
+                id.map(Action::ToggleGigStatus)
+            }
+            KeyCode::Char('R') => {
+                let failed = self
+                    .gigs
+                    .selected()
+                    .filter(|gig| self.gigs.failure(gig).is_some());
+                failed.map(|gig| Action::RetryGig(gig.id.clone()))
+            }
+            _ => None,
+        };
+        Ok(action)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::SwitchMode(mode) => self.view.set_active(mode == Mode::Gigs),
+            Action::ViewFailed(View::Gigs) => self.view.loaded(),
+            Action::Gigs(gigs) => {
+                self.view.loaded();
+                self.gigs.replace(gigs);
+                if self.gigs.selected().is_none() {
+                    self.gigs.select_next();
+                }
+            }
+            Action::GigLoaded(gig) => {
+                self.gigs.update(gig);
+            }
+            Action::GigFailed { id, error } => self.gigs.set_failed(&id, error),
+            Action::GigHistory(history) => self.history = history,
+            Action::SelectItem(id) => {
+                self.gigs.select_id(&id);
+            }
+            Action::TogglePin if self.view.is_active() => self.toggle_pin()?,
+            Action::ToggleDensity if self.view.is_active() => self.gigs.toggle_density(),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.view.is_active() {
+            return Ok(());
+        }
+        let [table_area, history_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(5)]).areas(area);
+        f.render_widget(Clear, area);
+        self.view.draw(f, table_area, &mut self.gigs, None, |gigs| {
+            let header = Row::new(["  Title", "Status", "Price", "Queue"]).bold();
+            Table::new(
+                gigs.rows(Self::cells),
+                [
+                    Constraint::Min(20),
+                    Constraint::Length(10),
+                    Constraint::Length(10),
+                    Constraint::Length(6),
+                ],
+            )
+            .header(header)
+        });
+        if self.view.empty_message(&self.gigs).is_none() {
+            self.draw_history(f, history_area);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::MouseEventKind;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn gig(id: &str) -> Gig {
+        Gig {
+            id: id.to_string(),
+            title: format!("I will {id}"),
+            status: GigStatus::Active,
+            price: "$25".to_string(),
+            orders_in_queue: 0,
+            impressions: 0,
+            clicks: 0,
+            edit_url: Some(format!("https://www.fiverr.com/{id}/edit")),
+            analytics_url: None,
+        }
+    }
+
+    #[test]
+    fn test_keys_act_on_selected_gig() {
+        let mut gigs = GigsComponent::new();
+        let key =
+            |gigs: &mut GigsComponent, c| gigs.handle_key_events(KeyEvent::from(KeyCode::Char(c)));
+        gigs.update(Action::Gigs(vec![gig("logo"), gig("banner")]))
+            .unwrap();
+        assert_eq!(key(&mut gigs, 'e').unwrap(), None);
+
+        gigs.update(Action::SwitchMode(Mode::Gigs)).unwrap();
+        key(&mut gigs, 'j').unwrap();
+        assert_eq!(
+            key(&mut gigs, 'e').unwrap(),
+            Some(Action::OpenPage(
+                "https://www.fiverr.com/banner/edit".to_string()
+            ))
+        );
+        assert_eq!(key(&mut gigs, 'a').unwrap(), None);
+        assert_eq!(
+            key(&mut gigs, 't').unwrap(),
+            Some(Action::ToggleGigStatus("banner".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_empty_message_once_loaded() {
+        let mut gigs = GigsComponent::new();
+        gigs.update(Action::SwitchMode(Mode::Gigs)).unwrap();
+        assert_eq!(gigs.view.empty_message(&gigs.gigs), None);
+        gigs.update(Action::Gigs(Vec::new())).unwrap();
+        assert!(gigs.view.empty_message(&gigs.gigs).is_some());
+        gigs.update(Action::Gigs(vec![gig("logo")])).unwrap();
+        assert_eq!(gigs.view.empty_message(&gigs.gigs), None);
+    }
+
+    #[test]
+    fn test_mouse_wheel_over_table_scrolls() {
+        let mut gigs = GigsComponent::new();
+        gigs.update(Action::SwitchMode(Mode::Gigs)).unwrap();
+        gigs.update(Action::Gigs(vec![gig("logo"), gig("banner")]))
+            .unwrap();
+        gigs.view.area = Rect::new(0, 0, 40, 10);
+        let mut scroll = |column| {
+            let mouse = MouseEvent {
+                kind: MouseEventKind::ScrollDown,
+                column,
+                row: 2,
+                modifiers: crossterm::event::KeyModifiers::NONE,
+            };
+            gigs.handle_mouse_events(mouse).unwrap();
+        };
+        scroll(50);
+        scroll(5);
+        assert_eq!(gigs.gigs.selected().unwrap().id, "banner");
+    }
+
+    #[test]
+    fn test_retry_failed_gig() {
+        let mut gigs = GigsComponent::new();
+        gigs.update(Action::SwitchMode(Mode::Gigs)).unwrap();
+        gigs.update(Action::Gigs(vec![gig("logo")])).unwrap();
+        let retry = |gigs: &mut GigsComponent| {
+            gigs.handle_key_events(KeyEvent::from(KeyCode::Char('R')))
+                .unwrap()
+        };
+        assert_eq!(retry(&mut gigs), None);
+
+        gigs.update(Action::GigFailed {
+            id: "logo".to_string(),
+            error: "timed out".to_string(),
+        })
+        .unwrap();
+        assert_eq!(retry(&mut gigs), Some(Action::RetryGig("logo".to_string())));
+
+        let paused = Gig {
+            status: GigStatus::Paused,
+            ..gig("logo")
+        };
+        gigs.update(Action::GigLoaded(paused.clone())).unwrap();
+        assert_eq!(gigs.gigs.items(), [paused]);
+        assert_eq!(retry(&mut gigs), None);
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{centered_rect, Component, Frame};
+use crate::{
+    action::Action,
+    config::{key_sequence_to_string, Config, KeyBindings},
+    mode::Mode,
+};
+
+/// Popup toggled by `Action::Help` that lists the keybindings of the current mode, including
+/// the global ones it doesn't override, one line per action with all of its chords. `j`/`k`
+/// scroll, Esc closes.
+#[derive(Default)]
+pub struct HelpComponent {
+    open: bool,
+    mode: Mode,
+    keybindings: KeyBindings,
+    scroll: u16,
+}
+
+impl HelpComponent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The chords of each action that works in the current mode, by description.
+    fn entries(&self) -> BTreeMap<String, Vec<String>> {
+        let mut entries: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (keys, action) in self.keybindings.effective(self.mode) {
+            entries
+                .entry(action.description())
+                .or_default()
+                .push(key_sequence_to_string(keys));
+        }
+        for chords in entries.values_mut() {
+            chords.sort();
+        }
+        entries
+    }
+}
+
+impl Component for HelpComponent {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.keybindings = config.keybindings;
+        Ok(())
+    }
+
+    fn captures_keys(&self) -> bool {
+        self.open
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.open {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q') => self.open = false,
+            KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::Help => {
+                self.open = !self.open;
+                self.scroll = 0;
+            }
+            Action::SwitchMode(mode) => self.mode = mode,
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.open {
+            return Ok(());
+        }
+        let entries = self.entries();
+        let width = entries.values().map(|chords| chords.join(" ").len()).max();
+        let lines: Vec<Line> = entries
+            .iter()
+            .map(|(description, chords)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<width$}  ", chords.join(" "), width = width.unwrap_or(0)),
+                        Style::default().bold(),
+                    ),
+                    Span::raw(description.clone()),
+                ])
+            })
+            .collect();
+        self.scroll = self.scroll.min(lines.len().saturating_sub(1) as u16);
+        let area = centered_rect(70, 70, area);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Keys in {:?}", self.mode))
+            .title_bottom(Line::from("<j>/<k> scroll  <esc> close").right_aligned());
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(lines).block(block).scroll((self.scroll, 0)),
+            area,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_groups_chords_by_action() {
+        let mut help = HelpComponent::new();
+        let config: Config = json5::from_str(
+            r#"{ keybindings: {
+                "Global": { "<Ctrl-c>": "RequestQuit", "<?>": "Help" },
+                "Gigs": { "<q>": "RequestQuit", "<g><h>": { "SwitchMode": "Home" } },
+            } }"#,
+        )
+        .unwrap();
+        help.register_config_handler(config).unwrap();
+        help.update(Action::SwitchMode(Mode::Gigs)).unwrap();
+        let entries = help.entries();
+        assert_eq!(entries["Quit the application"], ["<ctrl-c>", "<q>"]);
+        assert_eq!(entries["Show Home"], ["<g><h>"]);
+
+        help.update(Action::Help).unwrap();
+        assert!(help.captures_keys());
+        help.handle_key_events(KeyEvent::from(KeyCode::Esc))
+            .unwrap();
+        assert!(!help.captures_keys());
+    }
+}
@@ -1,53 +1,161 @@
-use std::{collections::HashMap, time::Duration};
-
-use canvas::Line;
 use color_eyre::eyre::Result;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{prelude::*, widgets::*};
-use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::UnboundedSender;
 
 use super::{Component, Frame};
-use crate::{action::Action, config::Config};
+use crate::{
+    action::{Action, AuthState},
+    config::{key_sequence_to_string, Config},
+    mode::Mode,
+};
+
+/// A home keybinding as listed on the dashboard.
+struct Binding {
+    keys: String,
+    description: String,
+    action: Action,
+}
 
+/// The dashboard of `Mode::Home`, which takes over from the splash screen once logged in. Lists
+/// what the home keybindings do; `j`/`k` and Enter run one from the list.
 #[derive(Default)]
 pub struct Home {
-    progress: f64,
+    command_tx: Option<UnboundedSender<Action>>,
+    auth_state: AuthState,
+    mode: Mode,
+    /// Every home keybinding, sorted by description.
+    bindings: Vec<Binding>,
+    state: TableState,
 }
 
 impl Home {
     pub fn new() -> Self {
-        Self { progress: 0.0 }
+        Self::default()
     }
 
-    fn set_progress(&mut self, progress: f64) {
-        self.progress = progress;
+    fn is_visible(&self) -> bool {
+        self.auth_state == AuthState::LoggedIn && self.mode == Mode::Home
+    }
+
+    fn move_selection(&mut self, down: bool) {
+        let last = self.bindings.len().saturating_sub(1);
+        let selected = match self.state.selected() {
+            Some(i) if down => (i + 1).min(last),
+            Some(i) => i.saturating_sub(1),
+            None => 0,
+        };
+        self.state.select(Some(selected));
+    }
+
+    /// Send the action of the selected binding, as if its keys had been pressed.
+    fn run_selected(&self) -> Result<()> {
+        let selected = self.state.selected().and_then(|i| self.bindings.get(i));
+        if let (Some(binding), Some(tx)) = (selected, &self.command_tx) {
+            tx.send(binding.action.clone())?;
+        }
+        Ok(())
     }
 }
 
 impl Component for Home {
     fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
         Ok(())
     }
 
     fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        let keymap = config.keybindings.modes.get(&Mode::Home);
+        self.bindings = keymap
+            .into_iter()
+            .flatten()
+            .map(|(keys, action)| Binding {
+                keys: key_sequence_to_string(keys),
+                description: action.description(),
+                action: action.clone(),
+            })
+            .collect();
+        self.bindings.sort_by(|a, b| {
+            (a.description.as_str(), a.keys.as_str()).cmp(&(&b.description, &b.keys))
+        });
+        self.state.select((!self.bindings.is_empty()).then_some(0));
         Ok(())
     }
 
+    fn modes(&self) -> &[Mode] {
+        &[Mode::Home]
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.is_visible() {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(true),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(false),
+            KeyCode::Enter => self.run_selected()?,
+            _ => {}
+        }
+        Ok(None)
+    }
+
     fn update(&mut self, action: Action) -> Result<Option<Action>> {
-        if let Action::Message(map) = action {
-            if map.get("startup").is_some() {
-                if self.progress < 1.0 {
-                    self.progress += 0.1;
-                }
-            }
+        match action {
+            Action::Auth(auth_state) => self.auth_state = auth_state,
+            Action::SwitchMode(mode) => self.mode = mode,
+            _ => {}
         }
         Ok(None)
     }
 
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
-        let gauge = LineGauge::default().ratio(self.progress);
-        f.render_widget(gauge, area);
+        if !self.is_visible() {
+            return Ok(());
+        }
+        let rows = self
+            .bindings
+            .iter()
+            .map(|binding| Row::new([binding.keys.clone(), binding.description.clone()]));
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Home")
+            .title_bottom(Line::from("<enter> run").right_aligned());
+        let table = Table::new(rows, [Constraint::Length(14), Constraint::Min(0)])
+            .block(block)
+            .highlight_style(Style::default().reversed());
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(table, area, &mut self.state);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(Action::RequestQuit.description(), "Quit the application");
+        assert_eq!(Action::SwitchMode(Mode::Gigs).description(), "Show Gigs");
+        assert_eq!(Action::Refresh.description(), "Refresh");
+    }
+
+    #[test]
+    fn test_enter_sends_selected_binding() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut home = Home::new();
+        home.register_action_handler(tx).unwrap();
+        home.register_config_handler(Config::new(None).unwrap())
+            .unwrap();
+        let key = |home: &mut Home, code| home.handle_key_events(KeyEvent::from(code));
+        key(&mut home, KeyCode::Enter).unwrap();
+        assert!(rx.try_recv().is_err());
+
+        home.update(Action::Auth(AuthState::LoggedIn)).unwrap();
+        key(&mut home, KeyCode::Char('j')).unwrap();
+        key(&mut home, KeyCode::Enter).unwrap();
+        assert_eq!(rx.try_recv().unwrap(), home.bindings[1].action);
+    }
+}
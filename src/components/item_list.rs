@@ -0,0 +1,364 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
+
+use ratatui::{
+    style::Stylize,
+    text::{Span, Text},
+    widgets::{Row, TableState},
+};
+
+/// Scraped items that can be told apart across scrapes.
+pub trait Identified {
+    fn id(&self) -> &str;
+}
+
+/// How much of every item a table shows, switched with `Action::ToggleDensity`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Density {
+    /// One line per item.
+    #[default]
+    Compact,
+    /// As many lines as the item needs, with the fields that don't fit on one line.
+    Detailed,
+}
+
+impl Density {
+    pub fn toggled(self) -> Self {
+        match self {
+            Density::Compact => Density::Detailed,
+            Density::Detailed => Density::Compact,
+        }
+    }
+}
+
+/// The rows of a list or table view together with its selection and scroll position. Pinned
+/// items are kept above the others.
+#[derive(Debug)]
+pub struct ItemList<T> {
+    items: Vec<T>,
+    state: TableState,
+    /// Ids of the pinned items, including ones that aren't in the list right now.
+    pinned: HashSet<String>,
+    density: Density,
+    /// Why the last scrape of an item failed, by item id.
+    failures: HashMap<String, String>,
+}
+
+impl<T> Default for ItemList<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            state: TableState::default(),
+            pinned: HashSet::new(),
+            density: Density::default(),
+            failures: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Identified> ItemList<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn density(&self) -> Density {
+        self.density
+    }
+
+    pub fn toggle_density(&mut self) {
+        self.density = self.density.toggled();
+    }
+
+    /// Mark the item with the given id as failed to scrape, until it is updated or replaced.
+    pub fn set_failed(&mut self, id: &str, error: String) {
+        self.failures.insert(id.to_string(), error);
+    }
+
+    /// Forget that the item with the given id failed to scrape, e.g. once a retry worked.
+    pub fn clear_failed(&mut self, id: &str) {
+        self.failures.remove(id);
+    }
+
+    pub fn failure(&self, item: &T) -> Option<&str> {
+        self.failures.get(item.id()).map(String::as_str)
+    }
+
+    /// Replace the item with the same id as `item` in place, e.g. after scraping it again.
+    /// Returns whether there was such an item.
+    pub fn update(&mut self, item: T) -> bool {
+        self.failures.remove(item.id());
+        match self.items.iter_mut().find(|old| old.id() == item.id()) {
+            Some(old) => {
+                *old = item;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The table rows for the items, with the pin marker in front of the first cell and a marker
+    /// after it on items that failed to scrape. `cells` gets
+    /// the current density to add extra fields in `Density::Detailed`; in `Density::Compact` only
+    /// the first line of every cell is shown.
+    pub fn rows<'a>(&self, mut cells: impl FnMut(&T, Density) -> Vec<Text<'a>>) -> Vec<Row<'a>> {
+        self.items
+            .iter()
+            .map(|item| {
+                let mut cells = cells(item, self.density);
+                if let Some(line) = cells.first_mut().and_then(|cell| cell.lines.first_mut()) {
+                    line.spans.insert(0, Span::raw(self.pin_marker(item)));
+                    if self.failure(item).is_some() {
+                        line.spans.push(" ✗ failed, press R to retry".red());
+                    }
+                }
+                let (cells, height) = fit(cells, self.density);
+                Row::new(cells).height(height)
+            })
+            .collect()
+    }
+
+    pub fn pinned(&self) -> &HashSet<String> {
+        &self.pinned
+    }
+
+    /// Pin the items with the given ids, e.g. the ones saved in the state file.
+    pub fn set_pinned(&mut self, pinned: HashSet<String>) {
+        self.pinned = pinned;
+        self.sort_by(|_, _| Ordering::Equal);
+    }
+
+    pub fn is_pinned(&self, item: &T) -> bool {
+        self.pinned.contains(item.id())
+    }
+
+    /// The marker to put in front of `item`'s row, so pinned items stand out.
+    pub fn pin_marker(&self, item: &T) -> &'static str {
+        if self.is_pinned(item) {
+            "▲ "
+        } else {
+            "  "
+        }
+    }
+
+    /// Pin the selected item, or unpin it if it is pinned. The selection follows the item.
+    pub fn toggle_pin(&mut self) {
+        let Some(id) = self.selected().map(|item| item.id().to_string()) else {
+            return;
+        };
+        if !self.pinned.remove(&id) {
+            self.pinned.insert(id);
+        }
+        self.sort_by(|_, _| Ordering::Equal);
+    }
+
+    /// Sort the items by `compare`, pinned items first. The selection follows the selected item.
+    pub fn sort_by(&mut self, mut compare: impl FnMut(&T, &T) -> Ordering) {
+        let mut items = std::mem::take(&mut self.items);
+        let selected = self.state.selected().and_then(|i| items.get(i));
+        let selected = selected.map(|item| item.id().to_string());
+        let pinned = &self.pinned;
+        items.sort_by(|a, b| {
+            let a_pinned = pinned.contains(a.id());
+            let b_pinned = pinned.contains(b.id());
+            b_pinned.cmp(&a_pinned).then_with(|| compare(a, b))
+        });
+        let selected = selected.and_then(|id| items.iter().position(|item| item.id() == id));
+        self.items = items;
+        if selected.is_some() {
+            self.state.select(selected);
+        }
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn selected(&self) -> Option<&T> {
+        self.state.selected().and_then(|i| self.items.get(i))
+    }
+
+    pub fn state_mut(&mut self) -> &mut TableState {
+        &mut self.state
+    }
+
+    /// Select the item with the given id. Returns whether there is one.
+    pub fn select_id(&mut self, id: &str) -> bool {
+        let position = self.items.iter().position(|item| item.id() == id);
+        if position.is_some() {
+            self.state.select(position);
+        }
+        position.is_some()
+    }
+
+    pub fn select_next(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let next = match self.state.selected() {
+            Some(i) => (i + 1).min(self.items.len() - 1),
+            None => 0,
+        };
+        self.state.select(Some(next));
+    }
+
+    pub fn select_previous(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let previous = self.state.selected().map_or(0, |i| i.saturating_sub(1));
+        self.state.select(Some(previous));
+    }
+
+    /// Replace the items with a fresh scrape, keeping pinned items on top. If the selected item is still there, it stays
+    /// selected and on the same screen row; otherwise the selection is clamped to the new length.
+    pub fn replace(&mut self, mut items: Vec<T>) {
+        self.failures.clear();
+        items.sort_by_key(|item| !self.pinned.contains(item.id()));
+        let selected = self.state.selected();
+        let moved_to = self
+            .selected()
+            .and_then(|item| items.iter().position(|new| new.id() == item.id()));
+        let offset = self.state.offset();
+
+        let (selected, offset) = match (selected, moved_to) {
+            (Some(old), Some(new)) => (Some(new), (offset + new).saturating_sub(old)),
+            _ if items.is_empty() => (None, 0),
+            (Some(old), None) => (Some(old.min(items.len() - 1)), offset),
+            (None, _) => (None, offset),
+        };
+        self.items = items;
+        *self.state.offset_mut() = offset.min(self.items.len().saturating_sub(1));
+        self.state.select(selected);
+    }
+}
+
+/// Cut `cells` down to what `density` shows and return them with the height of their row.
+fn fit(mut cells: Vec<Text<'_>>, density: Density) -> (Vec<Text<'_>>, u16) {
+    if density == Density::Compact {
+        for cell in &mut cells {
+            cell.lines.truncate(1);
+        }
+    }
+    let height = cells.iter().map(|cell| cell.height()).max().unwrap_or(0);
+    (cells, height.max(1) as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Item(&'static str);
+
+    impl Identified for Item {
+        fn id(&self) -> &str {
+            self.0
+        }
+    }
+
+    fn list(ids: &[&'static str], selected: usize, offset: usize) -> ItemList<Item> {
+        let mut list = ItemList::new();
+        list.replace(ids.iter().map(|id| Item(id)).collect());
+        list.state_mut().select(Some(selected));
+        *list.state_mut().offset_mut() = offset;
+        list
+    }
+
+    #[test]
+    fn test_replace_keeps_selected_item_when_list_grows() {
+        let mut list = list(&["a", "b", "c"], 1, 1);
+        list.replace(vec![
+            Item("new"),
+            Item("a"),
+            Item("b"),
+            Item("c"),
+            Item("d"),
+        ]);
+        assert_eq!(list.selected(), Some(&Item("b")));
+        assert_eq!(list.state_mut().offset(), 2);
+    }
+
+    #[test]
+    fn test_replace_follows_selected_item_when_earlier_item_removed() {
+        let mut list = list(&["a", "b", "c"], 2, 1);
+        list.replace(vec![Item("b"), Item("c")]);
+        assert_eq!(list.selected(), Some(&Item("c")));
+        assert_eq!(list.state_mut().offset(), 0);
+    }
+
+    #[test]
+    fn test_replace_clamps_when_selected_item_removed() {
+        let mut list = list(&["a", "b", "c"], 2, 0);
+        list.replace(vec![Item("a"), Item("b")]);
+        assert_eq!(list.selected(), Some(&Item("b")));
+
+        list.replace(vec![]);
+        assert_eq!(list.selected(), None);
+        assert_eq!(list.state_mut().offset(), 0);
+    }
+
+    #[test]
+    fn test_pinned_items_stay_on_top() {
+        let mut list = list(&["a", "b", "c"], 2, 0);
+        list.toggle_pin();
+        assert_eq!(list.items(), [Item("c"), Item("a"), Item("b")]);
+        assert_eq!(list.selected(), Some(&Item("c")));
+
+        list.replace(vec![Item("a"), Item("b"), Item("c"), Item("d")]);
+        assert_eq!(list.items()[0], Item("c"));
+
+        list.sort_by(|a, b| b.0.cmp(a.0));
+        assert_eq!(list.items(), [Item("c"), Item("d"), Item("b"), Item("a")]);
+
+        list.toggle_pin();
+        list.sort_by(|a, b| a.0.cmp(b.0));
+        assert_eq!(list.items()[0], Item("a"));
+    }
+
+    #[test]
+    fn test_select_id() {
+        let mut list = list(&["a", "b", "c"], 0, 0);
+        assert!(list.select_id("c"));
+        assert_eq!(list.selected(), Some(&Item("c")));
+        assert!(!list.select_id("d"));
+        assert_eq!(list.selected(), Some(&Item("c")));
+
+        list.replace(vec![]);
+        assert!(!list.select_id("a"));
+        assert_eq!(list.selected(), None);
+    }
+
+    #[test]
+    fn test_fit_to_density() {
+        let cells = || {
+            vec![
+                Text::from("a\nfrom buyer"),
+                Text::from("b\nc\nd"),
+                Text::default(),
+            ]
+        };
+        let (compact, height) = fit(cells(), Density::Compact);
+        assert_eq!(height, 1);
+        assert!(compact.iter().all(|cell| cell.height() <= 1));
+
+        let (detailed, height) = fit(cells(), Density::Detailed);
+        assert_eq!(height, 3);
+        assert_eq!(detailed, cells());
+    }
+
+    #[test]
+    fn test_update_clears_failure() {
+        let mut list = list(&["a", "b"], 0, 0);
+        list.set_failed("b", "timed out".to_string());
+        assert_eq!(list.failure(&Item("b")), Some("timed out"));
+
+        assert!(list.update(Item("b")));
+        assert_eq!(list.failure(&Item("b")), None);
+        assert!(!list.update(Item("c")));
+        assert_eq!(list.items(), [Item("a"), Item("b")]);
+    }
+}
@@ -0,0 +1,116 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{centered_rect, Component, Frame};
+use crate::action::Action;
+
+/// Popup opened by `Action::OpenJump` that asks for an order number, buyer or gig id and sends
+/// `Action::JumpTo` with it on Enter.
+#[derive(Default)]
+pub struct JumpPrompt {
+    open: bool,
+    input: String,
+}
+
+impl JumpPrompt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+        self.input.clear();
+    }
+}
+
+impl Component for JumpPrompt {
+    fn captures_keys(&self) -> bool {
+        self.open
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.open {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => {
+                let id = self.input.trim().to_string();
+                self.close();
+                return Ok((!id.is_empty()).then_some(Action::JumpTo(id)));
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.input.push(c);
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_paste_events(&mut self, text: String) -> Result<Option<Action>> {
+        if self.open {
+            self.input.extend(text.chars().filter(|c| !c.is_control()));
+        }
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if action == Action::OpenJump {
+            self.open = true;
+            self.input.clear();
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.open {
+            return Ok(());
+        }
+        let area = centered_rect(50, 20, area);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Jump to order, buyer or gig")
+            .title_bottom(Line::from("<enter> go  <esc> close").right_aligned());
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(format!("> {}", self.input)).block(block),
+            area,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_enter_jumps_to_trimmed_input() {
+        let mut jump = JumpPrompt::new();
+        let mut key = |code| jump.handle_key_events(KeyEvent::from(code)).unwrap();
+        assert_eq!(key(KeyCode::Char('x')), None);
+
+        jump.update(Action::OpenJump).unwrap();
+        assert!(jump.captures_keys());
+        let mut key = |code| jump.handle_key_events(KeyEvent::from(code)).unwrap();
+        for c in " FO12x".chars() {
+            key(KeyCode::Char(c));
+        }
+        key(KeyCode::Backspace);
+        assert_eq!(
+            key(KeyCode::Enter),
+            Some(Action::JumpTo("FO12".to_string()))
+        );
+        assert!(!jump.captures_keys());
+
+        jump.update(Action::OpenJump).unwrap();
+        let action = jump.handle_key_events(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(action.unwrap(), None);
+    }
+}
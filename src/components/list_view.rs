@@ -0,0 +1,115 @@
+use crossterm::event::{MouseEvent, MouseEventKind};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{
+    item_list::{Identified, ItemList},
+    Frame,
+};
+
+/// What the scraped list views (gigs, orders and messages) have in common: whether the view is
+/// shown and waiting for a scrape, whether it has the keys, and where its table was drawn.
+#[derive(Debug, Default)]
+pub struct ListView {
+    /// The title of the view, e.g. `Gigs`.
+    name: &'static str,
+    /// The keys the view takes, for the bottom of its border.
+    hint: &'static str,
+    /// What to show instead of the table once a scrape found no items.
+    empty: &'static str,
+    active: bool,
+    loading: bool,
+    focused: bool,
+    /// Where the table was last drawn, to tell which pane the mouse is over.
+    pub(super) area: Rect,
+}
+
+impl ListView {
+    pub fn new(name: &'static str, hint: &'static str, empty: &'static str) -> Self {
+        Self {
+            name,
+            hint,
+            empty,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Show or hide the view. A shown view is loading until [`ListView::loaded`].
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+        self.loading = active;
+    }
+
+    /// Stop showing the view as loading, when its scrape arrived or failed.
+    pub fn loaded(&mut self) {
+        self.loading = false;
+    }
+
+    pub fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    /// Move the selection of `items` with the mouse wheel, if the mouse is over the table.
+    pub fn scroll<T: Identified>(&self, mouse: MouseEvent, items: &mut ItemList<T>) {
+        let over_table = self
+            .area
+            .intersects(Rect::new(mouse.column, mouse.row, 1, 1));
+        if !self.active || !over_table {
+            return;
+        }
+        match mouse.kind {
+            MouseEventKind::ScrollDown => items.select_next(),
+            MouseEventKind::ScrollUp => items.select_previous(),
+            _ => {}
+        }
+    }
+
+    /// What to show instead of the table, once a scrape found no items.
+    pub fn empty_message<T: Identified>(&self, items: &ItemList<T>) -> Option<&'static str> {
+        (!self.loading && items.items().is_empty()).then_some(self.empty)
+    }
+
+    /// Draw the table `table` builds into `area`, in a border titled with the view's name and
+    /// `detail` (unless the view is loading), or the empty message once a scrape found no items.
+    pub fn draw<'a, T: Identified>(
+        &mut self,
+        f: &mut Frame<'_>,
+        area: Rect,
+        items: &mut ItemList<T>,
+        detail: Option<String>,
+        table: impl FnOnce(&ItemList<T>) -> Table<'a>,
+    ) {
+        let name = self.name;
+        let title = match detail {
+            _ if self.loading => format!("{name} (loading...)"),
+            Some(detail) => format!("{name} ({detail})"),
+            None => name.to_string(),
+        };
+        let border_style = if self.focused {
+            Style::default().cyan()
+        } else {
+            Style::default()
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .title(title)
+            .title_bottom(Line::from(self.hint).right_aligned());
+        f.render_widget(Clear, area);
+        self.area = area;
+        if let Some(message) = self.empty_message(items) {
+            let paragraph = Paragraph::new(message.dark_gray())
+                .alignment(Alignment::Center)
+                .block(block);
+            f.render_widget(paragraph, area);
+            return;
+        }
+        let table = table(items)
+            .block(block)
+            .highlight_style(Style::default().reversed());
+        f.render_stateful_widget(table, area, items.state_mut());
+    }
+}
@@ -0,0 +1,250 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::PathBuf,
+};
+
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use log::Level;
+use ratatui::{prelude::*, widgets::*};
+
+use super::{Component, Frame};
+use crate::{
+    action::Action,
+    config::{Config, MemoryLimits},
+    utils,
+};
+
+/// Pane in the lower part of the screen that follows this run's log file. `Action::ToggleLogView`
+/// shows and hides it; while shown it can take the focus (`Action::FocusNext`), and then
+/// `e`/`w`/`i`/`d` pick the least severe level to show. Until then those keys go to the view.
+pub struct LogView {
+    visible: bool,
+    path: Option<PathBuf>,
+    /// How far into the log file has been read.
+    offset: u64,
+    /// The end of a line that was still being written on the last read.
+    partial: String,
+    lines: VecDeque<(Level, String)>,
+    /// How many lines are kept in memory, see `MemoryLimits::log_lines`.
+    max_lines: usize,
+    min_level: Level,
+    /// Whether lines were read since the last draw.
+    dirty: bool,
+    focused: bool,
+}
+
+impl Default for LogView {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            path: None,
+            offset: 0,
+            partial: String::new(),
+            lines: VecDeque::new(),
+            max_lines: MemoryLimits::default().log_lines,
+            min_level: Level::Warn,
+            dirty: false,
+            focused: false,
+        }
+    }
+}
+
+impl LogView {
+    pub fn new() -> Self {
+        Self {
+            path: utils::log_path().map(PathBuf::from),
+            ..Self::default()
+        }
+    }
+
+    /// Read what was appended to the log file since the last call.
+    fn read_new_lines(&mut self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut file = File::open(path)?;
+        if file.metadata()?.len() < self.offset {
+            // Truncated, e.g. by another run starting with the same data directory.
+            self.offset = 0;
+            self.partial.clear();
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut text = String::new();
+        self.offset += file.read_to_string(&mut text)? as u64;
+        text.insert_str(0, &std::mem::take(&mut self.partial));
+        let complete = match text.rfind('\n') {
+            Some(end) => {
+                self.partial = text.split_off(end + 1);
+                text
+            }
+            None => {
+                self.partial = text;
+                return Ok(());
+            }
+        };
+        for line in complete.lines() {
+            self.push_line(line);
+        }
+        Ok(())
+    }
+
+    fn push_line(&mut self, line: &str) {
+        // Lines without a level continue the message above them.
+        let level = parse_level(line)
+            .or_else(|| self.lines.back().map(|(level, _)| *level))
+            .unwrap_or(Level::Info);
+        utils::push_bounded(&mut self.lines, (level, line.to_string()), self.max_lines);
+        self.dirty = true;
+    }
+
+    fn shown_lines(&self) -> impl Iterator<Item = &(Level, String)> {
+        self.lines
+            .iter()
+            .filter(|(level, _)| *level <= self.min_level)
+    }
+}
+
+/// The level of a log line as written by `utils::initialize_logging`: a timestamp, then the level.
+fn parse_level(line: &str) -> Option<Level> {
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+fn level_style(level: Level) -> Style {
+    match level {
+        Level::Error => Style::default().red(),
+        Level::Warn => Style::default().yellow(),
+        Level::Info => Style::default(),
+        Level::Debug | Level::Trace => Style::default().dark_gray(),
+    }
+}
+
+impl Component for LogView {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.max_lines = config.config.memory_limits.log_lines;
+        let excess = self.lines.len().saturating_sub(self.max_lines);
+        self.lines.drain(..excess);
+        Ok(())
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.visible
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.visible {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Char('e') => self.min_level = Level::Error,
+            KeyCode::Char('w') => self.min_level = Level::Warn,
+            KeyCode::Char('i') => self.min_level = Level::Info,
+            KeyCode::Char('d') => self.min_level = Level::Debug,
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::ToggleLogView => self.visible = !self.visible,
+            Action::Tick if self.visible => {
+                if let Err(e) = self.read_new_lines() {
+                    // Not logged: the error would end up in the very file that can't be read.
+                    self.path = None;
+                    return Ok(Some(Action::Error(format!("Failed to read the log: {e}"))));
+                }
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.visible && self.dirty
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        self.dirty = false;
+        if !self.visible {
+            return Ok(());
+        }
+        let [_, area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Percentage(40)]).areas(area);
+        let title = format!("Log ({} and above)", self.min_level);
+        let border_style = if self.focused {
+            Style::default().cyan()
+        } else {
+            Style::default()
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .title(title)
+            .title_bottom(Line::from("<e>rror <w>arn <i>nfo <d>ebug").right_aligned());
+        let height = block.inner(area).height as usize;
+        let shown: Vec<&(Level, String)> = self.shown_lines().collect();
+        let lines: Vec<Line> = shown[shown.len().saturating_sub(height)..]
+            .iter()
+            .map(|(level, line)| Line::styled(line.as_str(), level_style(*level)))
+            .collect();
+        f.render_widget(Clear, area);
+        f.render_widget(Paragraph::new(lines).block(block), area);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_filters_by_level() {
+        let mut log_view = LogView::default();
+        for line in [
+            "2026-10-15T10:00:00.000000Z ERROR src/app.rs:1: Failed to connect",
+            "caused by: connection refused",
+            "2026-10-15T10:00:01.000000Z  WARN src/app.rs:2: Stale element, retrying",
+            "2026-10-15T10:00:02.000000Z  INFO src/app.rs:3: Navigated to /orders",
+            "2026-10-15T10:00:03.000000Z DEBUG src/app.rs:4: Dismissed cookie banner",
+        ] {
+            log_view.push_line(line);
+        }
+        let levels = |log_view: &LogView| -> Vec<Level> {
+            log_view.shown_lines().map(|(level, _)| *level).collect()
+        };
+        assert_eq!(levels(&log_view), [Level::Error, Level::Error, Level::Warn]);
+
+        log_view.visible = true;
+        log_view
+            .handle_key_events(KeyEvent::from(KeyCode::Char('e')))
+            .unwrap();
+        assert_eq!(levels(&log_view), [Level::Error, Level::Error]);
+
+        log_view
+            .handle_key_events(KeyEvent::from(KeyCode::Char('d')))
+            .unwrap();
+        assert_eq!(log_view.shown_lines().count(), 5);
+    }
+
+    #[test]
+    fn test_lines_capped_under_flood() {
+        let mut log_view = LogView {
+            max_lines: 50,
+            ..LogView::default()
+        };
+        for i in 0..10_000 {
+            log_view.push_line(&format!("2026-10-15T10:00:00.000000Z ERROR line {i}"));
+        }
+        assert_eq!(log_view.lines.len(), 50);
+        assert!(log_view.lines.back().unwrap().1.ends_with("line 9999"));
+    }
+}
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
 use color_eyre::eyre::Result;
 use crossterm::event::{KeyCode, KeyEvent};
@@ -8,7 +11,7 @@ use tokio::sync::mpsc::UnboundedSender;
 
 use super::{Component, Frame};
 use crate::{
-    action::Action,
+    action::{Action, AuthState, Phase},
     config::{Config, KeyBindings},
 };
 
@@ -23,8 +26,19 @@ pub struct LoginComponent {
     loading_messages: Vec<String>,
 
     // Gauge related fields
+    /// Where the gauge is heading.
     progress: f64,
+    /// What the gauge currently shows while it animates towards `progress`.
+    displayed_progress: f64,
+    animation_from: f64,
+    animation_tick: u32,
     total_loading_messages: usize,
+    /// Whether a tick changed the logo or the gauge since the last draw.
+    dirty: bool,
+    /// Set once logged in, when the splash screen gives way to the home dashboard.
+    collapsed: bool,
+    /// The page of a CAPTCHA that stops the login until it is solved in the browser window.
+    captcha_url: Option<String>,
 }
 
 impl LoginComponent {
@@ -91,17 +105,51 @@ impl LoginComponent {
         }
     }
 
+    /// The latest startup message, which says what is happening right now.
+    fn loading_message(&self) -> &str {
+        self.loading_messages
+            .last()
+            .map_or("Loading...", String::as_str)
+    }
+
     fn set_progress(&mut self, progress: f64) {
+        if progress == self.progress {
+            return;
+        }
         self.progress = progress;
+        self.animation_from = self.displayed_progress;
+        self.animation_tick = 0;
+        if self.config.config.gauge_animation_ticks == 0 {
+            self.displayed_progress = progress;
+        }
     }
 
+    /// Progress counts the distinct messages, so a phase repeated by a retry doesn't move the
+    /// gauge again.
     fn update_progress(&mut self) {
-        let message_count = self.loading_messages.len();
+        let message_count = self.loading_messages.iter().collect::<HashSet<_>>().len();
         if message_count >= self.total_loading_messages {
-            self.progress = 1.0;
+            self.set_progress(1.0);
         } else {
-            self.progress = message_count as f64 / self.total_loading_messages as f64
+            self.set_progress(message_count as f64 / self.total_loading_messages as f64);
+        }
+    }
+
+    /// Move the displayed gauge one tick further towards `progress`.
+    fn animate_gauge(&mut self) {
+        let ticks = self.config.config.gauge_animation_ticks;
+        if self.animation_tick >= ticks {
+            self.displayed_progress = self.progress;
+            return;
         }
+        self.animation_tick += 1;
+        let eased = self
+            .config
+            .config
+            .gauge_easing
+            .apply(self.animation_tick as f64 / ticks as f64);
+        self.displayed_progress =
+            self.animation_from + (self.progress - self.animation_from) * eased;
     }
 }
 
@@ -119,6 +167,7 @@ impl Component for LoginComponent {
     fn update(&mut self, action: Action) -> Result<Option<Action>> {
         match action {
             Action::Tick => {
+                let before = (self.counter, self.displayed_progress);
                 if self.is_animated {
                     self.counter += 1;
                     if self.counter >= self.logo_frames.len() {
@@ -126,19 +175,49 @@ impl Component for LoginComponent {
                         self.is_animated = false;
                     }
                 }
+                self.animate_gauge();
+                self.dirty |= before != (self.counter, self.displayed_progress);
+            }
+            Action::Status {
+                phase: Phase::Startup,
+                text,
+            } if self.loading_messages.last() != Some(&text) => {
+                self.loading_messages.push(text);
+                self.update_progress();
             }
-            Action::Message(map) => {
-                if let Some(startup_message) = map.get("startup") {
-                    self.loading_messages.push(startup_message.clone());
-                    self.update_progress();
+            Action::DemoProgress { percent, text } if self.config.config.demo => {
+                if let Some(text) = text {
+                    self.loading_messages = vec![text];
                 }
+                self.set_progress(f64::from(percent.min(100)) / 100.0);
+            }
+            Action::CaptchaDetected(url) => self.captcha_url = Some(url),
+            Action::OtpRequired => self.captcha_url = None,
+            Action::Auth(AuthState::LoggedIn) => {
+                self.collapsed = true;
+                self.captcha_url = None;
+            }
+            Action::Auth(AuthState::LoggedOut) => {
+                self.collapsed = false;
+                self.captcha_url = None;
+                // Back to the start of the login screen.
+                self.loading_messages = vec!["Logged out".to_string()];
+                self.set_progress(0.0);
             }
             _ => {}
         }
         Ok(None)
     }
 
+    fn is_dirty(&self) -> bool {
+        self.dirty && !self.collapsed
+    }
+
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        self.dirty = false;
+        if self.collapsed {
+            return Ok(());
+        }
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([Constraint::Percentage(99), Constraint::Percentage(1)].as_ref())
@@ -147,7 +226,8 @@ impl Component for LoginComponent {
         // Draw the splash screen in the upper part
         let frame = &self.logo_frames[self.counter];
         let frame_lines: Vec<&str> = frame.lines().collect();
-        let total_lines = frame_lines.len() + 1;
+        let message_lines = if self.captcha_url.is_some() { 2 } else { 1 };
+        let total_lines = frame_lines.len() + message_lines;
         let lines_above = (chunks[0].height as usize - total_lines) / 2;
         let lines_below = chunks[0].height as usize - lines_above - total_lines;
 
@@ -155,19 +235,29 @@ impl Component for LoginComponent {
         for _ in 0..lines_above {
             text.lines.push(Line::from(""));
         }
+        let theme = self.config.config.theme;
         for line in frame_lines {
-            text.lines.push(Line::from(line));
+            text.lines
+                .push(Line::styled(line, Style::default().fg(theme.primary)));
         }
 
         // Add a blank line between the logo and loading message
         text.lines.push(Line::from(""));
 
-        let loading_message = if !self.loading_messages.is_empty() {
-            &self.loading_messages[self.counter % self.loading_messages.len()]
+        if let Some(url) = &self.captcha_url {
+            let warning = Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD);
+            text.lines.push(Line::styled(
+                format!("Fiverr wants a CAPTCHA solved at {url}"),
+                warning,
+            ));
+            text.lines.push(Line::from(
+                "Solve it in the browser window, logging in continues once it is done",
+            ));
         } else {
-            "Loading..."
-        };
-        text.lines.push(Line::from(loading_message));
+            text.lines.push(Line::from(self.loading_message()));
+        }
 
         for _ in 0..lines_below {
             text.lines.push(Line::from(""));
@@ -177,9 +267,131 @@ impl Component for LoginComponent {
         f.render_widget(p, chunks[0]);
 
         // Draw the progress gauge in the bottom part
-        let gauge = LineGauge::default().ratio(self.progress);
+        let gauge = LineGauge::default()
+            .ratio(self.displayed_progress)
+            .gauge_style(Style::default().fg(theme.accent));
         f.render_widget(gauge, chunks[1]);
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn startup(login: &mut LoginComponent, text: &str) {
+        login.update(Action::startup(text)).unwrap();
+    }
+
+    #[test]
+    fn test_gauge_animates_towards_progress() {
+        let mut login = LoginComponent::new();
+        login.config.config.gauge_animation_ticks = 4;
+        startup(&mut login, "Starting Geckodriver...");
+        let target = 1.0 / 3.0;
+        assert_eq!(login.displayed_progress, 0.0);
+
+        login.update(Action::Tick).unwrap();
+        assert!(login.displayed_progress > 0.0 && login.displayed_progress < target);
+
+        for _ in 0..3 {
+            login.update(Action::Tick).unwrap();
+        }
+        assert_eq!(login.displayed_progress, target);
+    }
+
+    #[test]
+    fn test_gauge_jumps_without_animation() {
+        let mut login = LoginComponent::new();
+        login.config.config.gauge_animation_ticks = 0;
+        startup(&mut login, "Starting Geckodriver...");
+        assert_eq!(login.displayed_progress, 1.0 / 3.0);
+    }
+
+    #[test]
+    fn test_repeated_messages_count_once() {
+        let mut login = LoginComponent::new();
+        login.config.config.gauge_animation_ticks = 0;
+        startup(&mut login, "Starting Geckodriver...");
+        startup(&mut login, "Starting Geckodriver...");
+        assert_eq!(login.loading_messages, ["Starting Geckodriver..."]);
+        startup(&mut login, "Connecting...");
+        startup(&mut login, "Starting Geckodriver...");
+        assert_eq!(login.loading_messages.len(), 3);
+        assert_eq!(login.displayed_progress, 2.0 / 3.0);
+    }
+
+    #[test]
+    fn test_shows_latest_message() {
+        let mut login = LoginComponent::new();
+        assert_eq!(login.loading_message(), "Loading...");
+        startup(&mut login, "Starting Geckodriver...");
+        startup(&mut login, "Connecting...");
+        for _ in 0..3 {
+            login.update(Action::Tick).unwrap();
+        }
+        assert_eq!(login.loading_message(), "Connecting...");
+    }
+
+    #[test]
+    fn test_demo_progress() {
+        let mut login = LoginComponent::new();
+        let demo = |percent| Action::DemoProgress {
+            percent,
+            text: Some("Recording".to_string()),
+        };
+        login.update(demo(50)).unwrap();
+        assert_eq!(login.progress, 0.0);
+
+        login.config.config.demo = true;
+        login.config.config.gauge_animation_ticks = 0;
+        login.update(demo(50)).unwrap();
+        assert_eq!(login.displayed_progress, 0.5);
+        assert_eq!(login.loading_messages, ["Recording"]);
+        login.update(demo(250)).unwrap();
+        assert_eq!(login.displayed_progress, 1.0);
+    }
+
+    #[test]
+    fn test_captcha_message() {
+        let mut login = LoginComponent::new();
+        let url = "https://www.fiverr.com/login".to_string();
+        login.update(Action::CaptchaDetected(url.clone())).unwrap();
+        assert_eq!(login.captcha_url, Some(url));
+        login.update(Action::Auth(AuthState::LoggedIn)).unwrap();
+        assert_eq!(login.captcha_url, None);
+    }
+
+    #[test]
+    fn test_dirty_only_while_animating() {
+        let mut login = LoginComponent::new();
+        login.config.config.gauge_animation_ticks = 4;
+        login.update(Action::Tick).unwrap();
+        assert!(login.is_dirty());
+
+        for _ in 0..login.logo_frames.len() {
+            login.update(Action::Tick).unwrap();
+        }
+        login.dirty = false;
+        login.update(Action::Tick).unwrap();
+        assert!(!login.is_dirty());
+
+        startup(&mut login, "Starting Geckodriver...");
+        login.update(Action::Tick).unwrap();
+        assert!(login.is_dirty());
+    }
+
+    #[test]
+    fn test_collapses_once_logged_in() {
+        let mut login = LoginComponent::new();
+        login.update(Action::Auth(AuthState::LoggedIn)).unwrap();
+        login.update(Action::Tick).unwrap();
+        assert!(login.collapsed);
+        assert!(!login.is_dirty());
+
+        login.update(Action::Auth(AuthState::LoggedOut)).unwrap();
+        assert!(!login.collapsed);
+        assert_eq!(login.loading_messages, ["Logged out"]);
+    }
+}
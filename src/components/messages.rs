@@ -0,0 +1,268 @@
+use std::collections::HashSet;
+
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{
+    item_list::{Density, ItemList},
+    list_view::ListView,
+    Component, Frame,
+};
+use crate::{action::Action, config::View, fiverr::Conversation, mode::Mode};
+
+/// The inbox, shown in `Mode::Messages`: one row per conversation, unread ones marked with a dot
+/// and in bold. Enter opens the selected conversation. Space marks conversations and `b` writes
+/// one message to all the marked ones, or to the selected one if none is marked.
+#[derive(Default)]
+pub struct MessagesComponent {
+    view: ListView,
+    conversations: ItemList<Conversation>,
+    /// Usernames of the conversations marked for a bulk message.
+    marked: HashSet<String>,
+}
+
+impl MessagesComponent {
+    pub fn new() -> Self {
+        Self {
+            view: ListView::new(
+                "Messages",
+                "<enter> open  <space> mark  <b>ulk message",
+                "No conversations yet. Press <r> to read the inbox again.",
+            ),
+            ..Self::default()
+        }
+    }
+
+    fn cells(conversation: &Conversation, _density: Density, marked: bool) -> Vec<Text<'static>> {
+        let check = if marked { "✓" } else { " " };
+        let marker = if conversation.unread { "●" } else { " " };
+        let mut username = Text::from(format!("{check}{marker} {}", conversation.username));
+        let mut last_message = Text::from(conversation.last_message.clone());
+        if conversation.unread {
+            username = username.bold();
+            last_message = last_message.bold();
+        }
+        vec![username, last_message]
+    }
+
+    fn toggle_mark(&mut self) {
+        let Some(username) = self
+            .conversations
+            .selected()
+            .map(|conversation| conversation.username.clone())
+        else {
+            return;
+        };
+        if !self.marked.remove(&username) {
+            self.marked.insert(username);
+        }
+        self.conversations.select_next();
+    }
+
+    /// The marked conversations in the order of the list, or else the selected one.
+    fn bulk_recipients(&self) -> Vec<String> {
+        let marked: Vec<String> = self
+            .conversations
+            .items()
+            .iter()
+            .filter(|conversation| self.marked.contains(&conversation.username))
+            .map(|conversation| conversation.username.clone())
+            .collect();
+        if !marked.is_empty() {
+            return marked;
+        }
+        self.conversations
+            .selected()
+            .map(|conversation| conversation.username.clone())
+            .into_iter()
+            .collect()
+    }
+}
+
+impl Component for MessagesComponent {
+    fn view_mode(&self) -> Option<Mode> {
+        Some(Mode::Messages)
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.view.is_active()
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.view.set_focus(focused);
+    }
+
+    fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
+        self.view.scroll(mouse, &mut self.conversations);
+        Ok(None)
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.view.is_active() {
+            return Ok(None);
+        }
+        let action = match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.conversations.select_next();
+                None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.conversations.select_previous();
+                None
+            }
+            KeyCode::Enter => {
+                let username = self
+                    .conversations
+                    .selected()
+                    .map(|conversation| conversation.username.clone());
+                username.map(Action::OpenConversation)
+            }
+            KeyCode::Char(' ') => {
+                self.toggle_mark();
+                None
+            }
+            KeyCode::Char('b') => {
+                let recipients = self.bulk_recipients();
+                (!recipients.is_empty()).then_some(Action::ComposeBulkMessage(recipients))
+            }
+            _ => None,
+        };
+        Ok(action)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::SwitchMode(mode) => self.view.set_active(mode == Mode::Messages),
+            Action::ViewFailed(View::Messages) => self.view.loaded(),
+            Action::Conversations(conversations) => {
+                self.view.loaded();
+                self.conversations.replace(conversations);
+                if self.conversations.selected().is_none() {
+                    self.conversations.select_next();
+                }
+            }
+            Action::SelectItem(id) => {
+                self.conversations.select_id(&id);
+            }
+            Action::SendBulkMessage { .. } => self.marked.clear(),
+            Action::ToggleDensity if self.view.is_active() => self.conversations.toggle_density(),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.view.is_active() {
+            return Ok(());
+        }
+        let unread = self
+            .conversations
+            .items()
+            .iter()
+            .filter(|conversation| conversation.unread)
+            .count();
+        let detail = (unread > 0).then(|| format!("{unread} unread"));
+        let marked = &self.marked;
+        self.view
+            .draw(f, area, &mut self.conversations, detail, |conversations| {
+                let header = Row::new(["   From", "Last message"]).bold();
+                let rows = conversations.rows(|conversation, density| {
+                    Self::cells(
+                        conversation,
+                        density,
+                        marked.contains(&conversation.username),
+                    )
+                });
+                Table::new(rows, [Constraint::Length(24), Constraint::Min(20)]).header(header)
+            });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn conversation(username: &str, unread: bool) -> Conversation {
+        Conversation {
+            username: username.to_string(),
+            last_message: "Hi, is the logo ready?".to_string(),
+            unread,
+        }
+    }
+
+    #[test]
+    fn test_enter_opens_selected_conversation() {
+        let mut messages = MessagesComponent::new();
+        messages.update(Action::SwitchMode(Mode::Messages)).unwrap();
+        assert_eq!(messages.view.empty_message(&messages.conversations), None);
+        messages
+            .update(Action::Conversations(vec![
+                conversation("alice", true),
+                conversation("bob", false),
+            ]))
+            .unwrap();
+        messages
+            .handle_key_events(KeyEvent::from(KeyCode::Char('j')))
+            .unwrap();
+        assert_eq!(
+            messages
+                .handle_key_events(KeyEvent::from(KeyCode::Enter))
+                .unwrap(),
+            Some(Action::OpenConversation("bob".to_string()))
+        );
+
+        messages.update(Action::Conversations(Vec::new())).unwrap();
+        assert!(messages
+            .view
+            .empty_message(&messages.conversations)
+            .is_some());
+    }
+
+    #[test]
+    fn test_failed_scrape_ends_loading() {
+        let mut messages = MessagesComponent::new();
+        messages.update(Action::SwitchMode(Mode::Messages)).unwrap();
+        assert_eq!(messages.view.empty_message(&messages.conversations), None);
+        messages.update(Action::ViewFailed(View::Messages)).unwrap();
+        assert!(messages
+            .view
+            .empty_message(&messages.conversations)
+            .is_some());
+    }
+
+    #[test]
+    fn test_bulk_message_to_marked_conversations() {
+        let mut messages = MessagesComponent::new();
+        messages.update(Action::SwitchMode(Mode::Messages)).unwrap();
+        messages
+            .update(Action::Conversations(vec![
+                conversation("alice", true),
+                conversation("bob", false),
+                conversation("carol", false),
+            ]))
+            .unwrap();
+        let mut key = |c| {
+            messages
+                .handle_key_events(KeyEvent::from(KeyCode::Char(c)))
+                .unwrap()
+        };
+        assert_eq!(
+            key('b'),
+            Some(Action::ComposeBulkMessage(vec!["alice".to_string()]))
+        );
+        key(' ');
+        key('j');
+        key(' ');
+        assert_eq!(
+            key('b'),
+            Some(Action::ComposeBulkMessage(vec![
+                "alice".to_string(),
+                "carol".to_string()
+            ]))
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use std::time::{Duration, Instant};
+
+use color_eyre::eyre::Result;
+use ratatui::{prelude::*, widgets::*};
+
+use super::{Component, Frame};
+use crate::{
+    action::{Action, Phase},
+    config::{Config, MemoryLimits, Theme},
+};
+
+/// How many notifications are shown at once; older ones wait until newer ones are dismissed.
+const MAX_VISIBLE: usize = 3;
+const WIDTH: u16 = 48;
+
+struct Notification {
+    /// An error, rather than a status message.
+    error: bool,
+    text: String,
+    /// How long the notification has been on screen, not counting the time it waited.
+    shown_for: Duration,
+}
+
+/// Errors and status messages shown as toasts in the top right corner, newest first, each
+/// dismissed once it has been shown for `notification_secs`.
+#[derive(Default)]
+pub struct Notifications {
+    notifications: Vec<Notification>,
+    /// How many notifications are kept, see `MemoryLimits::notifications`.
+    max_notifications: usize,
+    duration: Duration,
+    theme: Theme,
+    /// When the shown notifications were last aged.
+    last_tick: Option<Instant>,
+    /// Whether a notification arrived or was dismissed since the last draw.
+    dirty: bool,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self {
+            max_notifications: MemoryLimits::default().notifications,
+            ..Self::default()
+        }
+    }
+
+    fn push(&mut self, error: bool, text: String) {
+        self.notifications.push(Notification {
+            error,
+            text,
+            shown_for: Duration::ZERO,
+        });
+        let excess = self
+            .notifications
+            .len()
+            .saturating_sub(self.max_notifications);
+        self.notifications.drain(..excess);
+        self.dirty = true;
+    }
+}
+
+impl Component for Notifications {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.duration = Duration::from_secs(config.config.notification_secs);
+        self.max_notifications = config.config.memory_limits.notifications;
+        self.theme = config.config.theme;
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::Error(text) => self.push(true, text),
+            Action::Status {
+                phase: Phase::Running,
+                text,
+            } => self.push(false, text),
+            Action::Tick => {
+                let now = Instant::now();
+                let elapsed = self.last_tick.map_or(Duration::ZERO, |last| now - last);
+                self.last_tick = Some(now);
+                for notification in self.notifications.iter_mut().rev().take(MAX_VISIBLE) {
+                    notification.shown_for += elapsed;
+                }
+                let count = self.notifications.len();
+                let duration = self.duration;
+                self.notifications
+                    .retain(|notification| notification.shown_for < duration);
+                self.dirty |= self.notifications.len() != count;
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        self.dirty = false;
+        let width = WIDTH.min(area.width);
+        let mut y = area.y;
+        for notification in self.notifications.iter().rev().take(MAX_VISIBLE) {
+            let (color, title) = if notification.error {
+                (self.theme.error, "Error")
+            } else {
+                (self.theme.accent, "Status")
+            };
+            let paragraph = Paragraph::new(notification.text.as_str())
+                .wrap(Wrap { trim: true })
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(color))
+                        .title(title),
+                );
+            let height = (wrapped_height(&notification.text, width.saturating_sub(2)) + 2)
+                .min(area.bottom().saturating_sub(y));
+            if height < 3 {
+                break;
+            }
+            let toast = Rect::new(area.right() - width, y, width, height);
+            f.render_widget(Clear, toast);
+            f.render_widget(paragraph, toast);
+            y += height;
+        }
+        Ok(())
+    }
+}
+
+/// About how many lines `text` takes when wrapped to `width`; word wrapping may need a few more.
+fn wrapped_height(text: &str, width: u16) -> u16 {
+    let width = usize::from(width.max(1));
+    text.lines()
+        .map(|line| line.chars().count().div_ceil(width).max(1) as u16)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dismissed_after_duration() {
+        let mut notifications = Notifications::new();
+        notifications.duration = Duration::from_secs(5);
+        notifications
+            .update(Action::Error("Lost the connection".to_string()))
+            .unwrap();
+        assert!(notifications.is_dirty());
+        notifications.dirty = false;
+        notifications.update(Action::Tick).unwrap();
+        assert_eq!(notifications.notifications.len(), 1);
+        assert!(!notifications.is_dirty());
+
+        notifications.notifications[0].shown_for = Duration::from_secs(5);
+        notifications.update(Action::Tick).unwrap();
+        assert!(notifications.notifications.is_empty());
+        assert!(notifications.is_dirty());
+    }
+
+    #[test]
+    fn test_hidden_notifications_dont_age() {
+        let mut notifications = Notifications::new();
+        notifications.duration = Duration::from_secs(5);
+        for i in 0..=MAX_VISIBLE {
+            notifications
+                .update(Action::Error(format!("Error {i}")))
+                .unwrap();
+        }
+        notifications.update(Action::Tick).unwrap();
+        notifications.last_tick = notifications
+            .last_tick
+            .map(|last| last - Duration::from_secs(5));
+        notifications.update(Action::Tick).unwrap();
+
+        // The oldest was waiting behind the others, and is shown in full now.
+        let texts: Vec<_> = notifications
+            .notifications
+            .iter()
+            .map(|n| n.text.as_str())
+            .collect();
+        assert_eq!(texts, ["Error 0"]);
+        assert!(notifications.notifications[0].shown_for.is_zero());
+    }
+
+    #[test]
+    fn test_oldest_dropped_beyond_limit() {
+        let mut notifications = Notifications::new();
+        notifications.max_notifications = 2;
+        notifications
+            .update(Action::Error("Lost the connection".to_string()))
+            .unwrap();
+        notifications
+            .update(Action::status("Connected to geckodriver"))
+            .unwrap();
+        notifications
+            .update(Action::startup("Starting geckodriver"))
+            .unwrap();
+        notifications.update(Action::status("Logged in")).unwrap();
+
+        let kept: Vec<_> = notifications
+            .notifications
+            .iter()
+            .map(|n| (n.error, n.text.as_str()))
+            .collect();
+        assert_eq!(
+            kept,
+            [(false, "Connected to geckodriver"), (false, "Logged in")]
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use chrono_tz::Tz;
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{
+    item_list::{Density, ItemList},
+    list_view::ListView,
+    Component, Frame,
+};
+use crate::{
+    action::Action,
+    config::{Config, View},
+    fiverr::{Order, OrderStatus},
+    mode::Mode,
+    templates,
+};
+
+/// The order the orders table is sorted in, changed with `S`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+enum OrderSort {
+    /// As listed on Fiverr's manage orders page.
+    #[default]
+    Page,
+    /// Due soonest first, orders without a readable due date last.
+    DueDate,
+    /// The orders that need the seller soonest first, see [`OrderStatus::urgency`].
+    Status,
+}
+
+impl OrderSort {
+    fn next(self) -> Self {
+        match self {
+            OrderSort::Page => OrderSort::DueDate,
+            OrderSort::DueDate => OrderSort::Status,
+            OrderSort::Status => OrderSort::Page,
+        }
+    }
+
+    fn compare(self, a: &Order, b: &Order) -> Ordering {
+        match self {
+            OrderSort::Page => Ordering::Equal,
+            OrderSort::DueDate => match (a.due(), b.due()) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (a, b) => a.is_none().cmp(&b.is_none()),
+            },
+            OrderSort::Status => a.status.urgency().cmp(&b.status.urgency()),
+        }
+    }
+}
+
+/// Table of the seller's orders, shown in `Mode::Orders`. `S` sorts them by due date, by status
+/// or as on the page; Enter shows the selected order's requirements, and `R` loads them again for
+/// an order whose requirements failed to load.
+#[derive(Default)]
+pub struct OrdersComponent {
+    view: ListView,
+    orders: ItemList<Order>,
+    /// The orders in the order of the page, to go back to with `OrderSort::Page`.
+    scraped: Vec<Order>,
+    sort: OrderSort,
+    timezone: Option<Tz>,
+    /// The config's named templates, for `Action::MessageBuyer`.
+    templates: HashMap<String, String>,
+}
+
+impl OrdersComponent {
+    pub fn new() -> Self {
+        Self {
+            view: ListView::new(
+                "Orders",
+                "<enter> requirements  <S>ort",
+                "No orders yet. Press <r> to read them again.",
+            ),
+            ..Self::default()
+        }
+    }
+
+    fn cells(order: &Order, density: Density, tz: Tz) -> Vec<Text<'static>> {
+        let mut buyer = Text::from(order.buyer.clone());
+        if density == Density::Detailed {
+            buyer.lines.push(Line::from(order.id.clone()).dark_gray());
+        }
+        let status = Text::from(order.status.to_string());
+        let status = match order.status {
+            OrderStatus::Late => status.red(),
+            _ if order.status.needs_action() => status.yellow(),
+            OrderStatus::Completed | OrderStatus::Cancelled => status.dark_gray(),
+            _ => status,
+        };
+        vec![
+            buyer,
+            Text::from(order.gig.clone()),
+            Text::from(order.due_date_in(tz)),
+            status,
+            Text::from(order.amount.clone()),
+        ]
+    }
+
+    /// Ask to send the template `name`, filled in for the selected order, to its buyer.
+    fn message_buyer(&self, name: &str) -> Option<Action> {
+        let order = self.orders.selected()?;
+        let Some(template) = self.templates.get(name) else {
+            return Some(Action::Error(format!("There is no template named {name}")));
+        };
+        match templates::render(template, &templates::order_variables(order)) {
+            // Escaped, the message is rendered once more for the buyer when it is sent.
+            Ok(text) => Some(Action::BulkMessage {
+                thread_ids: vec![order.buyer.clone()],
+                text: templates::escape(&text),
+            }),
+            Err(e) => Some(Action::Error(format!("Template {name}: {e}"))),
+        }
+    }
+
+    fn set_sort(&mut self, sort: OrderSort) {
+        self.sort = sort;
+        if sort == OrderSort::Page {
+            // Back to the page's order, keeping the selection.
+            let selected = self.orders.selected().map(|order| order.id.clone());
+            self.orders.replace(self.scraped.clone());
+            if let Some(id) = selected {
+                self.orders.select_id(&id);
+            }
+        } else {
+            self.orders.sort_by(|a, b| sort.compare(a, b));
+        }
+    }
+}
+
+impl Component for OrdersComponent {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.timezone = Some(config.config.display_timezone());
+        self.templates = config.templates;
+        Ok(())
+    }
+
+    fn view_mode(&self) -> Option<Mode> {
+        Some(Mode::Orders)
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.view.is_active()
+    }
+
+    fn set_focus(&mut self, focused: bool) {
+        self.view.set_focus(focused);
+    }
+
+    fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
+        self.view.scroll(mouse, &mut self.orders);
+        Ok(None)
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.view.is_active() {
+            return Ok(None);
+        }
+        let action = match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.orders.select_next();
+                None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.orders.select_previous();
+                None
+            }
+            KeyCode::Char('S') => {
+                self.set_sort(self.sort.next());
+                None
+            }
+            KeyCode::Enter => {
+                let id = self.orders.selected().map(|order| order.id.clone());
+                id.map(Action::ViewRequirements)
+            }
+            KeyCode::Char('R') => {
+                let failed = self
+                    .orders
+                    .selected()
+                    .filter(|order| self.orders.failure(order).is_some());
+                failed.map(|order| Action::ViewRequirements(order.id.clone()))
+            }
+            _ => None,
+        };
+        Ok(action)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::MessageBuyer(name) if self.view.is_active() => {
+                return Ok(self.message_buyer(&name))
+            }
+            Action::SwitchMode(mode) => self.view.set_active(mode == Mode::Orders),
+            Action::ViewFailed(View::Orders) => self.view.loaded(),
+            Action::Orders(orders) => {
+                self.view.loaded();
+                self.scraped = orders.clone();
+                self.orders.replace(orders);
+                self.set_sort(self.sort);
+                if self.orders.selected().is_none() {
+                    self.orders.select_next();
+                }
+            }
+            Action::SelectItem(id) => {
+                self.orders.select_id(&id);
+            }
+            Action::RequirementsFailed { order_id, error } => {
+                self.orders.set_failed(&order_id, error)
+            }
+            Action::RequirementsLoaded(requirements) => {
+                self.orders.clear_failed(&requirements.order_id)
+            }
+            Action::ToggleDensity if self.view.is_active() => self.orders.toggle_density(),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.view.is_active() {
+            return Ok(());
+        }
+        let detail = match self.sort {
+            OrderSort::Page => None,
+            OrderSort::DueDate => Some("by due date".to_string()),
+            OrderSort::Status => Some("by status".to_string()),
+        };
+        let tz = self.timezone.unwrap_or(Tz::UTC);
+        self.view.draw(f, area, &mut self.orders, detail, |orders| {
+            let header = Row::new(["  Buyer", "Gig", "Due", "Status", "Amount"]).bold();
+            Table::new(
+                orders.rows(|order, density| Self::cells(order, density, tz)),
+                [
+                    Constraint::Length(20),
+                    Constraint::Min(20),
+                    Constraint::Length(26),
+                    Constraint::Length(22),
+                    Constraint::Length(10),
+                ],
+            )
+            .header(header)
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    use crate::fiverr::OrderRequirements;
+
+    fn order(id: &str, due_date: &str, status: OrderStatus) -> Order {
+        Order {
+            id: id.to_string(),
+            buyer: "buyer".to_string(),
+            gig: "I will design your logo".to_string(),
+            due_date: due_date.to_string(),
+            status,
+            amount: "$25".to_string(),
+        }
+    }
+
+    fn ids(orders: &OrdersComponent) -> Vec<&str> {
+        orders
+            .orders
+            .items()
+            .iter()
+            .map(|order| order.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_sort_cycles() {
+        let mut orders = OrdersComponent::new();
+        orders.update(Action::SwitchMode(Mode::Orders)).unwrap();
+        orders
+            .update(Action::Orders(vec![
+                order("FO1", "2024-03-02T10:00:00Z", OrderStatus::InProgress),
+                order("FO2", "soon", OrderStatus::Late),
+                order("FO3", "2024-03-01T10:00:00Z", OrderStatus::Delivered),
+            ]))
+            .unwrap();
+        let sort = |orders: &mut OrdersComponent| {
+            orders
+                .handle_key_events(KeyEvent::from(KeyCode::Char('S')))
+                .unwrap()
+        };
+        sort(&mut orders);
+        assert_eq!(ids(&orders), ["FO3", "FO1", "FO2"]);
+        sort(&mut orders);
+        assert_eq!(ids(&orders), ["FO2", "FO1", "FO3"]);
+        sort(&mut orders);
+        assert_eq!(ids(&orders), ["FO1", "FO2", "FO3"]);
+        assert_eq!(
+            orders
+                .handle_key_events(KeyEvent::from(KeyCode::Enter))
+                .unwrap(),
+            Some(Action::ViewRequirements("FO1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_retry_failed_requirements() {
+        let mut orders = OrdersComponent::new();
+        orders.update(Action::SwitchMode(Mode::Orders)).unwrap();
+        orders
+            .update(Action::Orders(vec![order(
+                "FO1",
+                "soon",
+                OrderStatus::InProgress,
+            )]))
+            .unwrap();
+        let retry = |orders: &mut OrdersComponent| {
+            orders
+                .handle_key_events(KeyEvent::from(KeyCode::Char('R')))
+                .unwrap()
+        };
+        assert_eq!(retry(&mut orders), None);
+
+        orders
+            .update(Action::RequirementsFailed {
+                order_id: "FO1".to_string(),
+                error: "timed out".to_string(),
+            })
+            .unwrap();
+        assert_eq!(
+            retry(&mut orders),
+            Some(Action::ViewRequirements("FO1".to_string()))
+        );
+
+        orders
+            .update(Action::RequirementsLoaded(OrderRequirements {
+                order_id: "FO1".to_string(),
+                buyer: "buyer".to_string(),
+                answers: None,
+            }))
+            .unwrap();
+        assert_eq!(retry(&mut orders), None);
+    }
+
+    #[test]
+    fn test_message_buyer_with_template() {
+        let mut orders = OrdersComponent::new();
+        orders.templates = HashMap::from([(
+            "delivered".to_string(),
+            "Hi {buyer_name}, {order_id} is delivered {{:}}".to_string(),
+        )]);
+        orders.update(Action::SwitchMode(Mode::Orders)).unwrap();
+        orders
+            .update(Action::Orders(vec![order(
+                "FO1",
+                "soon",
+                OrderStatus::Delivered,
+            )]))
+            .unwrap();
+        assert_eq!(
+            orders
+                .update(Action::MessageBuyer("delivered".to_string()))
+                .unwrap(),
+            Some(Action::BulkMessage {
+                thread_ids: vec!["buyer".to_string()],
+                text: "Hi buyer, FO1 is delivered {{:}}".to_string(),
+            })
+        );
+        assert!(matches!(
+            orders.update(Action::MessageBuyer("thanks".to_string())),
+            Ok(Some(Action::Error(_)))
+        ));
+    }
+
+    #[test]
+    fn test_failed_scrape_ends_loading() {
+        let mut orders = OrdersComponent::new();
+        orders.update(Action::SwitchMode(Mode::Orders)).unwrap();
+        assert_eq!(orders.view.empty_message(&orders.orders), None);
+        orders.update(Action::ViewFailed(View::Orders)).unwrap();
+        assert!(orders.view.empty_message(&orders.orders).is_some());
+    }
+}
@@ -0,0 +1,121 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{centered_rect, Component, Frame};
+use crate::action::Action;
+
+/// Popup opened by `Action::OtpRequired` that asks for the one-time code Fiverr wants during login
+/// and sends `Action::SubmitOtp` with it on Enter.
+#[derive(Default)]
+pub struct OtpPrompt {
+    open: bool,
+    input: String,
+}
+
+impl OtpPrompt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+        self.input.clear();
+    }
+}
+
+impl Component for OtpPrompt {
+    fn captures_keys(&self) -> bool {
+        self.open
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.open {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => {
+                let code = self.input.trim().to_string();
+                self.close();
+                return Ok((!code.is_empty()).then_some(Action::SubmitOtp(code)));
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.input.push(c);
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_paste_events(&mut self, text: String) -> Result<Option<Action>> {
+        if self.open {
+            self.input.extend(text.chars().filter(|c| !c.is_control()));
+        }
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if action == Action::OtpRequired {
+            self.open = true;
+            self.input.clear();
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.open {
+            return Ok(());
+        }
+        let area = centered_rect(50, 20, area);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("One-time code from Fiverr")
+            .title_bottom(Line::from("<enter> submit  <esc> log in by hand").right_aligned());
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(format!("> {}", self.input)).block(block),
+            area,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_enter_submits_code() {
+        let mut otp = OtpPrompt::new();
+        otp.update(Action::OtpRequired).unwrap();
+        assert!(otp.captures_keys());
+        let mut key = |code| otp.handle_key_events(KeyEvent::from(code)).unwrap();
+        for c in "123456".chars() {
+            key(KeyCode::Char(c));
+        }
+        assert_eq!(
+            key(KeyCode::Enter),
+            Some(Action::SubmitOtp("123456".to_string()))
+        );
+        assert!(!otp.captures_keys());
+    }
+
+    #[test]
+    fn test_paste_inserts_code() {
+        let mut otp = OtpPrompt::new();
+        otp.handle_paste_events("123".to_string()).unwrap();
+        otp.update(Action::OtpRequired).unwrap();
+        otp.handle_paste_events("123 456\n".to_string()).unwrap();
+        assert_eq!(
+            otp.handle_key_events(KeyEvent::from(KeyCode::Enter))
+                .unwrap(),
+            Some(Action::SubmitOtp("123 456".to_string()))
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{centered_rect, Component, Frame};
+use crate::{action::Action, fiverr::OrderRequirements};
+
+/// Popup showing the requirements a buyer submitted for an order.
+#[derive(Default)]
+pub struct RequirementsComponent {
+    requirements: Option<OrderRequirements>,
+    loading: Option<String>,
+    scroll: u16,
+}
+
+impl RequirementsComponent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn is_open(&self) -> bool {
+        self.requirements.is_some() || self.loading.is_some()
+    }
+
+    fn close(&mut self) {
+        self.requirements = None;
+        self.loading = None;
+        self.scroll = 0;
+    }
+
+    fn text(&self) -> Text<'static> {
+        let Some(requirements) = &self.requirements else {
+            return Text::from("Loading requirements...");
+        };
+        let Some(answers) = &requirements.answers else {
+            return Text::from(format!(
+                "{} has not submitted the requirements yet.",
+                requirements.buyer
+            ));
+        };
+        let mut text = Text::default();
+        for requirement in answers {
+            text.lines
+                .push(Line::from(requirement.question.clone()).style(Style::new().bold()));
+            for line in requirement.answer.lines() {
+                text.lines.push(Line::from(line.to_string()));
+            }
+            text.lines.push(Line::from(""));
+        }
+        text
+    }
+}
+
+impl Component for RequirementsComponent {
+    fn captures_keys(&self) -> bool {
+        self.is_open()
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.is_open() {
+            return Ok(None);
+        }
+        let action = match key.code {
+            KeyCode::Esc => {
+                self.close();
+                None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.scroll = self.scroll.saturating_add(1);
+                None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll = self.scroll.saturating_sub(1);
+                None
+            }
+            KeyCode::Char('c') => self
+                .requirements
+                .as_ref()
+                .map(|requirements| Action::OpenConversation(requirements.buyer.clone())),
+            _ => None,
+        };
+        Ok(action)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::ViewRequirements(order_id) => {
+                self.close();
+                self.loading = Some(order_id);
+            }
+            // The conversation opens in its own popup.
+            Action::OpenConversation(_) => self.close(),
+            Action::RequirementsLoaded(requirements)
+                if self.loading.as_ref() == Some(&requirements.order_id) =>
+            {
+                self.loading = None;
+                self.requirements = Some(requirements);
+            }
+            // The error is shown as a notification.
+            Action::RequirementsFailed { order_id, .. }
+                if self.loading.as_ref() == Some(&order_id) =>
+            {
+                self.close()
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.is_open() {
+            return Ok(());
+        }
+        let order_id = self
+            .requirements
+            .as_ref()
+            .map(|requirements| &requirements.order_id)
+            .or(self.loading.as_ref())
+            .cloned()
+            .unwrap_or_default();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Requirements for order {order_id}"))
+            .title_bottom(Line::from("<c> conversation  <esc> close").right_aligned());
+        let area = centered_rect(80, 80, area);
+        let p = Paragraph::new(self.text())
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0));
+        f.render_widget(Clear, area);
+        f.render_widget(p, area);
+        Ok(())
+    }
+}
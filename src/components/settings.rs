@@ -0,0 +1,101 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{Component, Frame};
+use crate::{action::Action, config::Config, mode::Mode};
+
+/// The settings loaded from the config files and the environment, shown in `Mode::Settings`.
+/// Read-only: `Action::EditConfig` opens the config file to change them. `j`/`k` scroll.
+#[derive(Default)]
+pub struct SettingsComponent {
+    active: bool,
+    lines: Vec<String>,
+    scroll: u16,
+}
+
+impl SettingsComponent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Component for SettingsComponent {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        // Secrets like the password are redacted by their `Debug`.
+        self.lines = format!("{:#?}", config.config)
+            .lines()
+            .map(str::to_string)
+            .collect();
+        self.scroll = self.scroll.min(self.lines.len() as u16);
+        Ok(())
+    }
+
+    fn modes(&self) -> &[Mode] {
+        &[Mode::Settings]
+    }
+
+    fn on_enter(&mut self) -> Result<Option<Action>> {
+        self.scroll = 0;
+        Ok(None)
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.active {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.scroll = (self.scroll + 1).min(self.lines.len() as u16)
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if let Action::SwitchMode(mode) = action {
+            self.active = mode == Mode::Settings;
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Settings")
+            .title_bottom(Line::from("<Ctrl-e> edit").right_aligned());
+        let text: Vec<Line> = self
+            .lines
+            .iter()
+            .map(|line| Line::raw(line.as_str()))
+            .collect();
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(text).block(block).scroll((self.scroll, 0)),
+            area,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shows_settings_without_secrets() {
+        let mut settings = SettingsComponent::new();
+        let config: Config =
+            json5::from_str(r#"{ login_timeout_secs: 42, fivver_password: "hunter2" }"#).unwrap();
+        settings.register_config_handler(config).unwrap();
+        let text = settings.lines.join("\n");
+        assert!(text.contains("login_timeout_secs: 42"));
+        assert!(!text.contains("hunter2"));
+
+        settings.scroll = 3;
+        settings.on_enter().unwrap();
+        assert_eq!(settings.scroll, 0);
+    }
+}
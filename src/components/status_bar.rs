@@ -0,0 +1,210 @@
+use std::time::{Duration, Instant};
+
+use color_eyre::eyre::Result;
+use crossterm::event::KeyEvent;
+use ratatui::{prelude::*, widgets::*};
+
+use super::{Component, Frame, Region};
+use crate::{
+    action::{Action, AuthState, Phase},
+    config::{key_sequence_to_string, Config},
+    mode::Mode,
+};
+
+/// How long the progress gauge stays after the last `Action::Progress`.
+const PROGRESS_IDLE: Duration = Duration::from_secs(2);
+
+/// The last `Action::Progress` and when it arrived.
+struct Progress {
+    label: String,
+    done: usize,
+    total: Option<usize>,
+    updated_at: Instant,
+}
+
+/// One-line bar at the bottom of the screen: the current mode, the last status message and
+/// whether WebDriver is connected. Shows a gauge while a background scrape reports progress.
+#[derive(Default)]
+pub struct StatusBar {
+    mode: Mode,
+    /// Whether the WebDriver session works, once connected.
+    webdriver_connected: Option<bool>,
+    pending_keys: Vec<KeyEvent>,
+    /// The last `Phase::Running` status message.
+    status: String,
+    /// Whether `active_hours` are configured, so the automation state is worth showing.
+    has_active_hours: bool,
+    automation_active: bool,
+    auto_accept_armed: bool,
+    /// Whether the seller shows as online, once known.
+    online: Option<bool>,
+    progress: Option<Progress>,
+    /// Whether the gauge was hidden since the last draw.
+    dirty: bool,
+}
+
+impl StatusBar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The keys of a chord typed so far.
+    fn pending_keys_text(&self) -> String {
+        key_sequence_to_string(&self.pending_keys)
+    }
+
+    fn draw_progress(&self, f: &mut Frame<'_>, area: Rect) {
+        let Some(progress) = &self.progress else {
+            return;
+        };
+        match progress.total {
+            Some(total) => {
+                let ratio = progress.done.min(total) as f64 / total.max(1) as f64;
+                let label = format!("{} {}/{total}", progress.label, progress.done);
+                f.render_widget(LineGauge::default().ratio(ratio).label(label), area);
+            }
+            None => {
+                let text = format!("{}: {}", progress.label, progress.done);
+                f.render_widget(Paragraph::new(text), area);
+            }
+        }
+    }
+
+    /// The WebDriver connection, the seller's online status once known, the automation state if
+    /// `active_hours` are configured, and a warning while auto-accept is armed.
+    fn automation_line(&self) -> Line<'static> {
+        let mut spans = Vec::new();
+        match self.webdriver_connected {
+            Some(true) => spans.push(" webdriver ".green()),
+            Some(false) => spans.push(" webdriver lost ".white().on_red()),
+            None => spans.push(" connecting ".dark_gray()),
+        }
+        match self.online {
+            Some(true) => spans.push(" online ".green()),
+            Some(false) => spans.push(" away ".dark_gray()),
+            None => {}
+        }
+        if self.auto_accept_armed {
+            spans.push(" AUTO-ACCEPT ARMED ".white().on_red().bold());
+        }
+        if self.has_active_hours {
+            spans.push(if self.automation_active {
+                " automation on ".green()
+            } else {
+                " automation paused ".yellow()
+            });
+        }
+        Line::from(spans)
+    }
+}
+
+impl Component for StatusBar {
+    fn region(&self) -> Region {
+        Region::StatusLine
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.has_active_hours = config.config.active_hours.is_some();
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::SwitchMode(mode) => self.mode = mode,
+            Action::WebDriverStatus(connected) => self.webdriver_connected = Some(connected),
+            Action::PendingKeys(keys) => self.pending_keys = keys,
+            Action::Status {
+                phase: Phase::Running,
+                text,
+            } => self.status = text,
+            Action::AutomationActive(active) => self.automation_active = active,
+            Action::AutoAcceptArmed(armed) => self.auto_accept_armed = armed,
+            Action::Online(online) => self.online = Some(online),
+            Action::Auth(AuthState::LoggedOut) => self.online = None,
+            Action::Progress { label, done, total } => {
+                let updated_at = Instant::now();
+                self.progress = Some(Progress {
+                    label,
+                    done,
+                    total,
+                    updated_at,
+                });
+            }
+            Action::Tick => {
+                let idle = self
+                    .progress
+                    .as_ref()
+                    .is_some_and(|progress| progress.updated_at.elapsed() >= PROGRESS_IDLE);
+                if idle {
+                    self.progress = None;
+                    self.dirty = true;
+                }
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        self.dirty = false;
+        let pending = self.pending_keys_text();
+        let progress_width = if self.progress.is_some() { 32 } else { 0 };
+        let automation = self.automation_line();
+        let automation_width = automation.width() as u16;
+        let mode = format!(" {:?} ", self.mode);
+        let [mode_area, status_area, progress_area, pending_area, automation_area] =
+            Layout::horizontal([
+                Constraint::Length(mode.len() as u16 + 1),
+                Constraint::Min(0),
+                Constraint::Length(progress_width),
+                Constraint::Length(pending.len() as u16),
+                Constraint::Length(automation_width),
+            ])
+            .areas(area);
+        f.render_widget(Paragraph::new(mode.reversed()), mode_area);
+        f.render_widget(Paragraph::new(self.status.as_str()), status_area);
+        self.draw_progress(f, progress_area);
+        f.render_widget(Paragraph::new(pending), pending_area);
+        f.render_widget(Paragraph::new(automation), automation_area);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracks_mode_and_webdriver() {
+        let mut status_bar = StatusBar::new();
+        status_bar.update(Action::SwitchMode(Mode::Gigs)).unwrap();
+        assert_eq!(status_bar.mode, Mode::Gigs);
+        assert_eq!(status_bar.webdriver_connected, None);
+        status_bar.update(Action::WebDriverStatus(false)).unwrap();
+        assert_eq!(status_bar.webdriver_connected, Some(false));
+    }
+
+    #[test]
+    fn test_progress_hides_when_idle() {
+        let mut status_bar = StatusBar::new();
+        status_bar
+            .update(Action::Progress {
+                label: "Reading gigs".to_string(),
+                done: 3,
+                total: Some(10),
+            })
+            .unwrap();
+        status_bar.update(Action::Tick).unwrap();
+        assert!(status_bar.progress.is_some());
+        assert!(!status_bar.is_dirty());
+
+        status_bar.progress.as_mut().unwrap().updated_at -= PROGRESS_IDLE;
+        status_bar.update(Action::Tick).unwrap();
+        assert!(status_bar.progress.is_none());
+        assert!(status_bar.is_dirty());
+    }
+}
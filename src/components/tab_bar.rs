@@ -0,0 +1,123 @@
+use color_eyre::eyre::Result;
+use ratatui::{prelude::*, widgets::*};
+
+use super::{Component, Frame, Region};
+use crate::{
+    action::Action,
+    fiverr::{Conversation, Order},
+    mode::Mode,
+};
+
+/// One-line bar at the top of the screen listing the screens, with a badge counting what needs
+/// attention on each, and the current one highlighted.
+#[derive(Default)]
+pub struct TabBar {
+    mode: Mode,
+    /// Orders that need the seller to act, see [`crate::fiverr::OrderStatus::needs_action`].
+    orders_needing_action: usize,
+    unread_conversations: usize,
+}
+
+impl TabBar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn count_orders(orders: &[Order]) -> usize {
+        orders
+            .iter()
+            .filter(|order| order.status.needs_action())
+            .count()
+    }
+
+    fn count_unread(conversations: &[Conversation]) -> usize {
+        conversations
+            .iter()
+            .filter(|conversation| conversation.unread)
+            .count()
+    }
+
+    fn title(label: &str, badge: usize) -> Line<'static> {
+        if badge == 0 {
+            return Line::from(label.to_string());
+        }
+        Line::from(vec![
+            Span::raw(format!("{label} ")),
+            Span::styled(format!("({badge})"), Style::default().yellow().bold()),
+        ])
+    }
+}
+
+impl Component for TabBar {
+    fn region(&self) -> Region {
+        Region::TabLine
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::SwitchMode(mode) if Mode::SCREENS.contains(&mode) => self.mode = mode,
+            Action::Orders(orders) => self.orders_needing_action = Self::count_orders(&orders),
+            Action::Conversations(conversations) => {
+                self.unread_conversations = Self::count_unread(&conversations)
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let titles = Mode::SCREENS.map(|mode| {
+            let badge = match mode {
+                Mode::Orders => self.orders_needing_action,
+                Mode::Messages => self.unread_conversations,
+                _ => 0,
+            };
+            Self::title(&format!("{mode:?}"), badge)
+        });
+        let selected = Mode::SCREENS.iter().position(|mode| *mode == self.mode);
+        f.render_widget(
+            Tabs::new(titles)
+                .select(selected.unwrap_or_default())
+                .highlight_style(Style::default().reversed()),
+            area,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::fiverr::OrderStatus;
+
+    fn order(status: &str) -> Order {
+        Order {
+            id: status.to_string(),
+            buyer: "buyer".to_string(),
+            gig: "gig".to_string(),
+            due_date: String::new(),
+            status: OrderStatus::from_label(status),
+            amount: "$5".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_counts_orders_needing_action() {
+        let mut tab_bar = TabBar::new();
+        let orders = [
+            "New",
+            "In Progress",
+            "Late",
+            "Incomplete",
+            "Completed",
+            "On hold",
+        ]
+        .into_iter()
+        .map(order)
+        .collect();
+        tab_bar.update(Action::Orders(orders)).unwrap();
+        assert_eq!(tab_bar.orders_needing_action, 3);
+    }
+}
@@ -0,0 +1,189 @@
+use chrono_tz::Tz;
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{centered_rect, Component, Frame};
+use crate::{
+    action::Action,
+    config::Config,
+    fiverr::{format_date, Thread, ThreadMessage},
+};
+
+/// Popup showing the messages of a conversation, the seller's on the right and the buyer's on
+/// the left.
+#[derive(Default)]
+pub struct ThreadComponent {
+    thread: Option<Thread>,
+    /// The user whose conversation is being loaded.
+    loading: Option<String>,
+    scroll: u16,
+    timezone: Option<Tz>,
+}
+
+impl ThreadComponent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn is_open(&self) -> bool {
+        self.thread.is_some() || self.loading.is_some()
+    }
+
+    fn close(&mut self) {
+        self.thread = None;
+        self.loading = None;
+        self.scroll = 0;
+    }
+
+    fn text(&self) -> Text<'static> {
+        let Some(thread) = &self.thread else {
+            return Text::from("Loading conversation...");
+        };
+        if thread.messages.is_empty() {
+            return Text::from(format!("No messages with {} yet.", thread.username));
+        }
+        let tz = self.timezone.unwrap_or(Tz::UTC);
+        let mut text = Text::default();
+        for message in &thread.messages {
+            text.lines.extend(message_lines(message, tz));
+            text.lines.push(Line::from(""));
+        }
+        text
+    }
+}
+
+/// A header with the sender and time, the body, then a placeholder per attachment.
+fn message_lines(message: &ThreadMessage, tz: Tz) -> Vec<Line<'static>> {
+    let alignment = if message.from_me {
+        Alignment::Right
+    } else {
+        Alignment::Left
+    };
+    let mut header = vec![Span::styled(message.sender.clone(), Style::new().bold())];
+    if !message.time.is_empty() {
+        header.push(Span::styled(
+            format!(" · {}", format_date(&message.time, tz)),
+            Style::new().dark_gray(),
+        ));
+    }
+    let mut lines = vec![Line::from(header)];
+    for line in message.body.lines() {
+        lines.push(Line::from(line.to_string()));
+    }
+    for name in &message.attachments {
+        lines.push(Line::styled(
+            format!("[attachment: {name}]"),
+            Style::new().italic(),
+        ));
+    }
+    lines
+        .into_iter()
+        .map(|line| line.alignment(alignment))
+        .collect()
+}
+
+impl Component for ThreadComponent {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.timezone = Some(config.config.display_timezone());
+        Ok(())
+    }
+
+    fn captures_keys(&self) -> bool {
+        self.is_open()
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.is_open() {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Esc => self.close(),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::OpenConversation(username) => {
+                self.close();
+                self.loading = Some(username);
+            }
+            Action::ThreadLoaded(thread) if self.loading.as_ref() == Some(&thread.username) => {
+                self.loading = None;
+                self.thread = Some(thread);
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.is_open() {
+            return Ok(());
+        }
+        let username = self
+            .thread
+            .as_ref()
+            .map(|thread| &thread.username)
+            .or(self.loading.as_ref())
+            .cloned()
+            .unwrap_or_default();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Conversation with {username}"))
+            .title_bottom(Line::from("<esc> close").right_aligned());
+        let area = centered_rect(80, 80, area);
+        let p = Paragraph::new(self.text())
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0));
+        f.render_widget(Clear, area);
+        f.render_widget(p, area);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_message_lines() {
+        let message = ThreadMessage {
+            sender: "me".to_string(),
+            time: "2026-10-15T10:00:00Z".to_string(),
+            body: "Here you go.\nLet me know!".to_string(),
+            attachments: vec!["logo.png".to_string()],
+            from_me: true,
+        };
+        let lines = message_lines(&message, Tz::Europe__Berlin);
+        let text: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        assert_eq!(
+            text,
+            [
+                "me · Oct 15, 2026 12:00 CEST",
+                "Here you go.",
+                "Let me know!",
+                "[attachment: logo.png]",
+            ]
+        );
+        assert!(lines
+            .iter()
+            .all(|line| line.alignment == Some(Alignment::Right)));
+
+        let buyer = ThreadMessage {
+            from_me: false,
+            ..message
+        };
+        assert_eq!(
+            message_lines(&buyer, Tz::UTC)[0].alignment,
+            Some(Alignment::Left)
+        );
+    }
+}
@@ -1,7 +1,14 @@
-use std::{collections::HashMap, fmt, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
 use color_eyre::eyre::Result;
-use config::Value;
+use config::{Value, ValueKind};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use derive_deref::{Deref, DerefMut};
 use ratatui::style::{Color, Modifier, Style};
@@ -10,17 +17,589 @@ use serde::{
     Deserialize, Serialize,
 };
 use serde_json::Value as JsonValue;
+use strum::Display;
 
-use crate::{action::Action, mode::Mode};
+use crate::{
+    action::Action,
+    fiverr::{Order, OrderStatus},
+    mode::Mode,
+};
 
 const CONFIG: &str = include_str!("../.config/config.json5");
 
-#[derive(Clone, Debug, Deserialize, Default)]
+/// Directory, relative to the working directory, of the project-local config layer.
+pub const PROJECT_CONFIG_DIR: &str = ".stash";
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
     #[serde(default)]
     pub _data_dir: PathBuf,
     #[serde(default)]
     pub _config_dir: PathBuf,
+    /// How many WebDriver operations may drive the browser at the same time.
+    /// See [`crate::browser::Browser`].
+    pub max_concurrent_webdriver_ops: usize,
+    /// How long to wait for the next key of a multi-key binding before giving up on it.
+    pub chord_timeout_ms: u64,
+    /// How often to re-run a scrape step whose element was replaced while it was being read.
+    pub stale_element_retries: usize,
+    /// Minimum delay between two messages sent in a row, to stay clear of Fiverr's spam detection.
+    pub message_interval_ms: u64,
+    /// What to do with the cookie consent banner Fiverr shows on the first visit.
+    pub cookie_consent: CookieConsent,
+    /// Log every page visited and selector looked up while scraping, to find broken selectors.
+    pub navigation_log: NavigationLog,
+    /// How many ticks the splash screen's gauge takes to catch up with new progress. `0` makes it
+    /// jump straight to the new value.
+    pub gauge_animation_ticks: u32,
+    /// How the gauge's animation speeds up and slows down.
+    pub gauge_easing: Easing,
+    /// Time zone to show Fiverr's dates in, e.g. `"Europe/Berlin"`. Defaults to the system's.
+    pub timezone: Option<Tz>,
+    /// Fixed WebDriver ports by profile name. The default profile uses the backend's default port
+    /// unless listed, other profiles a port between 30000 and 39999 derived from their name.
+    pub profile_ports: HashMap<String, u16>,
+    /// The WebDriver servers to try, in order, until one connects. `STASH_WEBDRIVER`, e.g.
+    /// `chrome` or `chrome,gecko`, overrides it.
+    pub webdriver_backends: Vec<WebDriverKind>,
+    /// A WebDriver server to connect to instead of the local `webdriver_backends`, e.g. a remote
+    /// Selenium grid. stash never starts or stops it.
+    pub webdriver_url: Option<String>,
+    /// A directory for the browser to keep its profile in, so a login survives restarts. Each
+    /// account profile gets a subdirectory. A fresh, temporary profile is used when unset. Not
+    /// used with `webdriver_url`, whose browser runs elsewhere.
+    pub browser_profile_dir: Option<PathBuf>,
+    /// Run the browser without a window, see [`AppConfig::headless_enabled`]. A CAPTCHA during
+    /// login can't be solved then.
+    pub headless: bool,
+    /// Whether stash starts the WebDriver server when none is running and stops it on quit. Turn
+    /// off when the server is managed elsewhere (e.g. systemd or docker); profiles other than the
+    /// default then need a port in `profile_ports`.
+    pub manage_webdriver: bool,
+    /// What to do when Fiverr no longer accepts the stored session.
+    pub expired_session: ExpiredSession,
+    /// Waiting for lazily loaded content before scraping.
+    pub lazy_load: LazyLoad,
+    /// Retrying the connection to a WebDriver server that is still starting up.
+    pub connect_retry: ConnectRetry,
+    /// Backing off when Fiverr answers with a rate limit page.
+    pub rate_limit: RateLimit,
+    /// Dealing with tabs and popups the pages open.
+    pub windows: Windows,
+    /// How long logging in with credentials waits for the login form, and then for Fiverr to
+    /// show the logged in page.
+    pub login_timeout_secs: u64,
+    /// How long to wait for a CAPTCHA shown during login to be solved in the browser window.
+    pub captcha_timeout_secs: u64,
+    /// How often to check that the WebDriver session is still alive, which also keeps it from
+    /// idling out. Off when unset.
+    pub heartbeat_secs: Option<u64>,
+    /// How long a scraped view is shown from memory when switching back to it before it is
+    /// scraped again. `Action::Refresh` always scrapes.
+    pub view_cache_ttl_secs: u64,
+    /// How long an error or status message stays in the corner of the screen.
+    pub notification_secs: u64,
+    /// How to signal events, e.g. `{ error: ["flash", "bell"] }`.
+    pub feedback: HashMap<FeedbackEvent, Vec<Feedback>>,
+    /// How much stash keeps in memory, so a long running session doesn't keep growing.
+    pub memory_limits: MemoryLimits,
+    /// The colors of the UI, see [`Theme`].
+    pub theme: Theme,
+    /// Ask before `Action::RequestQuit` quits, since quitting closes the browser session.
+    pub confirm_quit: bool,
+    /// Where the log is written and how much of it is kept.
+    pub log_files: LogFiles,
+    /// The least severe messages that are logged, e.g. `"debug"`. `RUST_LOG` and
+    /// `STASH_LOGLEVEL` override it.
+    pub log_level: String,
+    /// When stash may send messages. Always when unset.
+    pub active_hours: Option<ActiveHours>,
+    /// The mode to switch to after the first login, e.g. `"Gigs"`.
+    pub startup_mode: Mode,
+    /// Accepting new orders without asking. Off unless enabled here and armed in the app.
+    pub auto_accept: AutoAccept,
+    /// Ticks per second, overriding `--tick-rate`. Ticks drive the splash animation and timers.
+    pub tick_rate: Option<f64>,
+    /// Frames per second, overriding `--frame-rate`.
+    pub frame_rate: Option<f64>,
+    /// Capture the mouse, e.g. to scroll lists with the wheel. Turn off to select text with the
+    /// mouse for copying.
+    pub mouse: bool,
+    /// Draw without colors, see [`AppConfig::color_enabled`].
+    pub no_color: bool,
+    /// Let `Action::DemoProgress` drive the splash screen, for recording demos.
+    pub demo: bool,
+    /// Open the page of every exported order or conversation and save a screenshot of it, whose
+    /// path goes in a `screenshot` column of the export. Slow, as each page is loaded in turn.
+    pub export_screenshots: bool,
+    /// The Fiverr login, used when `FIVVER_USERNAME` isn't set.
+    pub fivver_username: Option<Secret>,
+    /// Used when `FIVVER_PASSWORD` isn't set.
+    pub fivver_password: Option<Secret>,
+}
+
+/// A value that is left out of `Debug` output, so printing the config doesn't leak it.
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(..)")
+    }
+}
+
+/// The `KEY=value` lines of a `.env` file. Blank lines and `#` comments are skipped, and values
+/// may be quoted. A missing file has no variables.
+pub fn read_env_file(path: &Path) -> Result<HashMap<String, String>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (key, value) = line
+                .strip_prefix("export ")
+                .unwrap_or(line)
+                .split_once('=')?;
+            let value = value.trim();
+            let value = ["\"", "'"]
+                .iter()
+                .find_map(|quote| value.strip_prefix(quote)?.strip_suffix(quote))
+                .unwrap_or(value);
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect())
+}
+
+/// Accepting new orders on their own. Even when `enabled`, it only runs once armed with
+/// `Action::ToggleAutoAccept` and confirmed, only for orders of the listed gigs, and only within
+/// the `active_hours`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct AutoAccept {
+    pub enabled: bool,
+    /// Titles of the gigs whose orders may be accepted, as shown on the order. Must not be empty
+    /// when `enabled`.
+    pub gigs: Vec<String>,
+}
+
+impl AutoAccept {
+    /// Whether `order` is a new order, waiting to be accepted, of one of the allowed gigs.
+    pub fn allows(&self, order: &Order) -> bool {
+        self.enabled
+            && order.status == OrderStatus::PendingAcceptance
+            && self.gigs.iter().any(|gig| gig.trim() == order.gig.trim())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct MemoryLimits {
+    /// Lines of the log view. Older ones are still in the log file.
+    pub log_lines: usize,
+    /// Gig snapshots loaded for the history charts. The history file keeps up to twice as many
+    /// and then drops all but these.
+    pub gig_snapshots: usize,
+    /// Messages held back outside of the active hours. The oldest are dropped beyond this.
+    pub queued_messages: usize,
+    /// Notifications, shown or waiting to be. The oldest are dropped beyond this.
+    pub notifications: usize,
+    /// Warnings and errors printed after quitting, the latest of the run. Takes effect on restart.
+    pub session_log_lines: usize,
+}
+
+impl Default for MemoryLimits {
+    fn default() -> Self {
+        Self {
+            log_lines: 1000,
+            gig_snapshots: 5000,
+            queued_messages: 100,
+            notifications: 50,
+            session_log_lines: 100,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LogFiles {
+    /// The directory of the log files, the data directory if unset.
+    pub dir: Option<PathBuf>,
+    /// The size at which the log file is renamed to `stash.log.1` and a new one started.
+    pub max_size_kb: u64,
+    /// How many renamed log files are kept, including the ones of earlier runs.
+    pub keep: usize,
+}
+
+impl Default for LogFiles {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            max_size_kb: 5 * 1024,
+            keep: 3,
+        }
+    }
+}
+
+/// The hours of the day in which stash sends messages and auto-accepts orders on its own, e.g.
+/// `{ start: "09:00", end: "18:00", timezone: "Europe/Berlin" }`. Scraping goes on regardless.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct ActiveHours {
+    #[serde(deserialize_with = "deserialize_time")]
+    pub start: NaiveTime,
+    /// May be before `start` for hours that span midnight.
+    #[serde(deserialize_with = "deserialize_time")]
+    pub end: NaiveTime,
+    /// Defaults to [`AppConfig::display_timezone`].
+    #[serde(default)]
+    pub timezone: Option<Tz>,
+    #[serde(default)]
+    pub outside: OutsideHours,
+}
+
+impl ActiveHours {
+    /// Whether `now` falls within the hours, in `timezone` or else `default_timezone`.
+    pub fn contains(&self, now: DateTime<Utc>, default_timezone: Tz) -> bool {
+        let time = now
+            .with_timezone(&self.timezone.unwrap_or(default_timezone))
+            .time();
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// What happens to messages sent outside of [`ActiveHours`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutsideHours {
+    /// Hold them back and send them once the hours start.
+    #[default]
+    Queue,
+    /// Drop them.
+    Skip,
+}
+
+fn deserialize_time<'de, D>(deserializer: D) -> Result<NaiveTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let time = String::deserialize(deserializer)?;
+    NaiveTime::parse_from_str(&time, "%H:%M")
+        .map_err(|e| de::Error::custom(format!("Invalid time {time:?}, expected HH:MM: {e}")))
+}
+
+/// Events that can be signalled with [`Feedback`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackEvent {
+    Error,
+    /// An order showed up that wasn't in the previous scrape.
+    NewOrder,
+    MessageSent,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Feedback {
+    /// Invert the screen's colors for one frame.
+    Flash,
+    /// Ring the terminal bell.
+    Bell,
+}
+
+/// The pages stash scrapes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Display, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum View {
+    Orders,
+    Messages,
+    Requirements,
+    Thread,
+    Gigs,
+}
+
+/// Fiverr loads long lists while scrolling, so a scrape right after navigating can miss rows. For
+/// the listed views, the page is scrolled to the bottom until the number of items stops changing.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LazyLoad {
+    /// The views to wait for. Each adds at least two polls of latency.
+    pub views: Vec<View>,
+    /// How long to wait after scrolling before counting the items again.
+    pub poll_ms: u64,
+    /// When to give up and scrape what has loaded.
+    pub timeout_ms: u64,
+}
+
+impl Default for LazyLoad {
+    fn default() -> Self {
+        Self {
+            views: vec![View::Orders, View::Messages],
+            poll_ms: 500,
+            timeout_ms: 10_000,
+        }
+    }
+}
+
+/// A freshly started WebDriver server takes a moment before it accepts sessions, longer on slow
+/// machines. Connecting is tried `attempts` times, waiting `base_delay_ms` after the first failure
+/// and twice as long after each further one.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ConnectRetry {
+    pub attempts: u32,
+    pub base_delay_ms: u64,
+}
+
+impl Default for ConnectRetry {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            base_delay_ms: 200,
+        }
+    }
+}
+
+impl ConnectRetry {
+    /// How long to wait after the `attempt`th failed attempt, counting from 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        Duration::from_millis(self.base_delay_ms.saturating_mul(factor))
+    }
+}
+
+/// When a page Fiverr serves looks like a rate limit, stash stops navigating for `cooldown_secs`
+/// and waits longer before every navigation for the rest of the session.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RateLimit {
+    /// Texts that mark a rate limit page when its title contains one, ignoring case.
+    pub title_markers: Vec<String>,
+    pub cooldown_secs: u64,
+    /// How much the delay before every navigation grows with each rate limit page.
+    pub delay_step_ms: u64,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            title_markers: vec!["too many requests".to_string(), "error 429".to_string()],
+            cooldown_secs: 300,
+            delay_step_ms: 1000,
+        }
+    }
+}
+
+/// stash drives one main browser window. See [`crate::browser::Session::with_new_window`] for
+/// actions that open another one on purpose.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Windows {
+    /// How long to wait for an expected window to open.
+    pub new_window_timeout_ms: u64,
+    /// What to do with other windows found before a navigation, e.g. popups a click opened.
+    pub stray: StrayWindows,
+}
+
+impl Default for Windows {
+    fn default() -> Self {
+        Self {
+            new_window_timeout_ms: 5000,
+            stray: StrayWindows::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StrayWindows {
+    #[default]
+    Close,
+    /// Leave them open and only switch back to the main window, e.g. to look at them.
+    Keep,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebDriverKind {
+    /// geckodriver, for Firefox.
+    Gecko,
+    /// chromedriver, for Chrome.
+    Chrome,
+}
+
+impl WebDriverKind {
+    pub fn binary(self) -> &'static str {
+        match self {
+            WebDriverKind::Gecko => "geckodriver",
+            WebDriverKind::Chrome => "chromedriver",
+        }
+    }
+
+    pub fn default_port(self) -> u16 {
+        match self {
+            WebDriverKind::Gecko => 4444,
+            WebDriverKind::Chrome => 9515,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExpiredSession {
+    /// Log in with the credentials right away.
+    #[default]
+    Login,
+    /// Ask before logging in.
+    Confirm,
+}
+
+impl AppConfig {
+    /// Whether the browser runs without a window: when `headless` is set, or `STASH_HEADLESS` is
+    /// set to anything but an empty string.
+    pub fn headless_enabled(&self) -> bool {
+        self.headless
+            || std::env::var_os(crate::utils::HEADLESS_ENV.as_str())
+                .is_some_and(|value| !value.is_empty())
+    }
+
+    /// `log_level` as a filter, or an error naming the levels if it isn't one of them.
+    pub fn log_level_filter(&self) -> Result<log::LevelFilter, config::ConfigError> {
+        self.log_level.parse().map_err(|_| {
+            config::ConfigError::Message(format!(
+                "log_level must be one of off, error, warn, info, debug or trace, not {:?}",
+                self.log_level
+            ))
+        })
+    }
+
+    /// Whether the UI may use colors: not when `no_color` is set, or when the `NO_COLOR`
+    /// environment variable is (see <https://no-color.org>).
+    pub fn color_enabled(&self) -> bool {
+        !self.no_color && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+    }
+
+    /// The configured time zone, else the system's, else UTC.
+    pub fn display_timezone(&self) -> Tz {
+        self.timezone
+            .or_else(|| iana_time_zone::get_timezone().ok()?.parse().ok())
+            .unwrap_or(Tz::UTC)
+    }
+
+    /// Whether stash may send messages right now, see [`ActiveHours`].
+    pub fn automation_active(&self) -> bool {
+        self.active_hours
+            .as_ref()
+            .is_none_or(|hours| hours.contains(Utc::now(), self.display_timezone()))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Easing {
+    Linear,
+    /// Start fast and slow down towards the end.
+    #[default]
+    Out,
+}
+
+impl Easing {
+    /// Map the fraction `t` (0 to 1) of the animation's duration to the fraction of the distance
+    /// covered.
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::Out => 1.0 - (1.0 - t).powi(3),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NavigationLog {
+    #[default]
+    Off,
+    /// Log URLs and whether each selector matched.
+    Selectors,
+    /// Also log how many elements each selector matched. Costs an extra lookup per find.
+    Counts,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CookieConsent {
+    Accept,
+    #[default]
+    Reject,
+    /// Leave the banner alone, e.g. when the browser profile already answered it.
+    Ignore,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            _data_dir: PathBuf::default(),
+            _config_dir: PathBuf::default(),
+            max_concurrent_webdriver_ops: 1,
+            chord_timeout_ms: 1000,
+            stale_element_retries: 3,
+            message_interval_ms: 5000,
+            cookie_consent: CookieConsent::default(),
+            navigation_log: NavigationLog::default(),
+            gauge_animation_ticks: 8,
+            gauge_easing: Easing::default(),
+            timezone: None,
+            profile_ports: HashMap::new(),
+            expired_session: ExpiredSession::default(),
+            webdriver_backends: vec![WebDriverKind::Gecko],
+            webdriver_url: None,
+            browser_profile_dir: None,
+            headless: false,
+            fivver_username: None,
+            fivver_password: None,
+            manage_webdriver: true,
+            lazy_load: LazyLoad::default(),
+            connect_retry: ConnectRetry::default(),
+            rate_limit: RateLimit::default(),
+            windows: Windows::default(),
+            login_timeout_secs: 15,
+            captcha_timeout_secs: 300,
+            heartbeat_secs: None,
+            view_cache_ttl_secs: 300,
+            notification_secs: 8,
+            feedback: HashMap::from([(FeedbackEvent::Error, vec![Feedback::Flash])]),
+            memory_limits: MemoryLimits::default(),
+            theme: Theme::default(),
+            confirm_quit: true,
+            log_files: LogFiles::default(),
+            log_level: "info".to_string(),
+            active_hours: None,
+            startup_mode: Mode::default(),
+            auto_accept: AutoAccept::default(),
+            tick_rate: None,
+            frame_rate: None,
+            mouse: true,
+            no_color: false,
+            demo: false,
+            export_screenshots: false,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -31,43 +610,285 @@ pub struct Config {
     pub keybindings: KeyBindings,
     #[serde(default)]
     pub styles: Styles,
+    #[serde(default)]
+    pub selectors: Selectors,
+    /// Canned messages by name, see [`crate::templates::render`] for the variables.
+    #[serde(default)]
+    pub templates: HashMap<String, String>,
+    /// What loading the config had to say. The config is loaded before logging is set up, since
+    /// it says where to log, so these wait for [`Config::log_load_messages`].
+    #[serde(skip)]
+    pub load_messages: Vec<(log::Level, String)>,
+}
+
+/// CSS selectors for the elements stash reads from or clicks on Fiverr's pages. When Fiverr
+/// changes its markup, these can be fixed in the config file without a new release.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Selectors {
+    pub cookie_accept: Selector,
+    pub cookie_reject: Selector,
+    pub requirements_buyer: Selector,
+    pub requirements_item: Selector,
+    pub requirements_question: Selector,
+    pub requirements_answer: Selector,
+    pub message_input: Selector,
+    pub message_send: Selector,
+    /// Only present on pages of a logged in user.
+    pub logged_in: Selector,
+    pub thread_message: Selector,
+    /// Matches the messages in a thread that the seller sent.
+    pub thread_own_message: Selector,
+    pub thread_sender: Selector,
+    pub thread_time: Selector,
+    pub thread_body: Selector,
+    pub thread_attachment: Selector,
+    /// An order on the manage orders page.
+    pub order_row: Selector,
+    /// The link to the order's page, whose last path segment is taken as the order's number.
+    pub order_link: Selector,
+    pub order_buyer: Selector,
+    pub order_gig: Selector,
+    pub order_due_date: Selector,
+    pub order_status: Selector,
+    pub order_amount: Selector,
+    /// A conversation in the inbox.
+    pub inbox_row: Selector,
+    pub inbox_username: Selector,
+    pub inbox_last_message: Selector,
+    /// Only present in conversations with unread messages.
+    pub inbox_unread: Selector,
+    pub gig_row: Selector,
+    /// The link to the gig's page, whose last path segment is taken as the gig's id.
+    pub gig_title: Selector,
+    pub gig_status: Selector,
+    pub gig_price: Selector,
+    pub gig_orders_in_queue: Selector,
+    pub gig_impressions: Selector,
+    pub gig_clicks: Selector,
+    pub gig_edit_link: Selector,
+    pub gig_analytics_link: Selector,
+    /// The switch that pauses or activates a gig.
+    pub gig_status_toggle: Selector,
+    /// The button on an order's page that accepts it.
+    pub order_accept: Selector,
+    /// The online/away switch, checked while the seller is online.
+    pub availability_toggle: Selector,
+    /// The fields and button of the login form.
+    pub login_email: Selector,
+    pub login_password: Selector,
+    pub login_submit: Selector,
+    /// The one-time code field Fiverr shows after the credentials when it wants a second factor.
+    pub login_otp: Selector,
+    /// The frame of a CAPTCHA shown during login.
+    pub login_captcha: Selector,
+}
+
+impl Default for Selectors {
+    fn default() -> Self {
+        Self {
+            cookie_accept: "#onetrust-accept-btn-handler".into(),
+            cookie_reject: "#onetrust-reject-all-handler".into(),
+            requirements_buyer: ".order-requirements .buyer-username".into(),
+            requirements_item: ".order-requirements .requirement-item".into(),
+            requirements_question: ".requirement-question".into(),
+            requirements_answer: ".requirement-answer".into(),
+            message_input: ".message-box textarea".into(),
+            message_send: ".message-box button[type=submit]".into(),
+            logged_in: "[data-testid='user-avatar']".into(),
+            thread_message: ".message-flow .message-item".into(),
+            thread_own_message: ".message-flow .message-item.is-outgoing".into(),
+            thread_sender: ".sender-name".into(),
+            thread_time: "time".into(),
+            thread_body: ".message-body".into(),
+            thread_attachment: ".attachment .file-name".into(),
+            order_row: ".manage-orders-table tbody tr".into(),
+            order_link: "a.order-id".into(),
+            order_buyer: ".buyer-username".into(),
+            order_gig: ".gig-title".into(),
+            order_due_date: ".order-due-date time".into(),
+            order_status: ".order-status".into(),
+            order_amount: ".order-total".into(),
+            inbox_row: ".inbox-contacts .contact".into(),
+            inbox_username: ".contact-username".into(),
+            inbox_last_message: ".contact-excerpt".into(),
+            inbox_unread: ".unread-indicator, .unread-count".into(),
+            gig_row: ".manage-gigs-table tbody tr".into(),
+            gig_title: ".gig-title a".into(),
+            gig_status: ".gig-status".into(),
+            gig_price: ".gig-price".into(),
+            gig_orders_in_queue: ".gig-queue".into(),
+            gig_impressions: ".gig-impressions".into(),
+            gig_clicks: ".gig-clicks".into(),
+            gig_edit_link: "a.edit-gig".into(),
+            gig_analytics_link: "a.gig-analytics".into(),
+            gig_status_toggle: ".gig-status-toggle input".into(),
+            order_accept: ".order-actions button.accept-order".into(),
+            availability_toggle: ".availability-toggle input[type=checkbox]".into(),
+            login_email: "form input[name='login']".into(),
+            login_password: "form input[name='password']".into(),
+            login_submit: "form button[type=submit]".into(),
+            login_otp: "form input[autocomplete='one-time-code']".into(),
+            login_captcha: Selector(vec![
+                "iframe[src*='hcaptcha.com']".to_string(),
+                "iframe[src*='recaptcha']".to_string(),
+                "iframe[title*='captcha' i]".to_string(),
+            ]),
+        }
+    }
+}
+
+/// A CSS selector with fallbacks, written as `"a.edit"` or `["a.edit", ".edit-link a"]`. The
+/// candidates are tried in order and the first that matches anything is used, so markup that
+/// differs between accounts (e.g. while Fiverr runs an A/B test) can be covered by one selector.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Selector(Vec<String>);
+
+impl Selector {
+    /// The candidates in the order they are tried. Never empty.
+    pub fn candidates(&self) -> &[String] {
+        &self.0
+    }
+}
+
+impl From<&str> for Selector {
+    fn from(css: &str) -> Self {
+        Self(vec![css.to_string()])
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.join(" | "))
+    }
+}
+
+impl<'de> Deserialize<'de> for Selector {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Candidates {
+            One(String),
+            Many(Vec<String>),
+        }
+        let candidates = match Candidates::deserialize(deserializer)? {
+            Candidates::One(css) => vec![css],
+            Candidates::Many(candidates) => candidates,
+        };
+        if candidates.is_empty() {
+            return Err(de::Error::custom("a selector needs at least one candidate"));
+        }
+        Ok(Self(candidates))
+    }
 }
 
 impl Config {
-    pub fn new() -> Result<Self, config::ConfigError> {
+    /// Load the config in layers, each overriding the one before it key by key: the built-in
+    /// defaults, then `config_path` if given or else the `config.*` files in the config
+    /// directory, then the project-local `config.*` files in `.stash/` under the current
+    /// directory. A layer that sets one keybinding of a mode keeps the other bindings of that
+    /// mode from the layers below.
+    pub fn new(config_path: Option<PathBuf>) -> Result<Self, config::ConfigError> {
+        let config_dir = crate::utils::get_config_dir();
+        let mut cfg = Self::layered(config_path, &config_dir, Path::new(PROJECT_CONFIG_DIR))?;
+        if crate::utils::CONFIG_FOLDER.is_none() && !crate::utils::has_project_directory() {
+            let message = format!(
+                "Could not determine the config directory (is HOME set?), looking for config \
+                 files in {} instead",
+                config_dir.display()
+            );
+            cfg.load_messages.insert(0, (log::Level::Warn, message));
+        }
+        Ok(cfg)
+    }
+
+    /// Log the [`Config::load_messages`], once.
+    pub fn log_load_messages(&mut self) {
+        for (level, message) in std::mem::take(&mut self.load_messages) {
+            log::log!(level, "{message}");
+        }
+    }
+
+    pub(crate) fn layered(
+        config_path: Option<PathBuf>,
+        config_dir: &Path,
+        project_dir: &Path,
+    ) -> Result<Self, config::ConfigError> {
         let default_config: Config = json5::from_str(CONFIG).unwrap();
         let data_dir = crate::utils::get_data_dir();
-        let config_dir = crate::utils::get_config_dir();
+        let mut load_messages = Vec::new();
         let mut builder = config::Config::builder()
-            .set_default("_data_dir", data_dir.to_str().unwrap())?
-            .set_default("_config_dir", config_dir.to_str().unwrap())?;
-
-        let config_files = [
-            ("config.json5", config::FileFormat::Json5),
-            ("config.json", config::FileFormat::Json),
-            ("config.yaml", config::FileFormat::Yaml),
-            ("config.toml", config::FileFormat::Toml),
-            ("config.ini", config::FileFormat::Ini),
-        ];
-        let mut found_config = false;
-        for (file, format) in &config_files {
-            builder = builder.add_source(
-                config::File::from(config_dir.join(file))
-                    .format(*format)
-                    .required(false),
-            );
-            if config_dir.join(file).exists() {
-                found_config = true
+            .set_default("_data_dir", data_dir.to_string_lossy().as_ref())?
+            .set_default("_config_dir", config_dir.to_string_lossy().as_ref())?;
+
+        if let Some(config_path) = config_path {
+            if !config_path.is_file() {
+                return Err(config::ConfigError::Message(format!(
+                    "Config file {} does not exist",
+                    config_path.display()
+                )));
             }
+            builder = builder.add_source(config::File::from(config_path).required(true));
+        } else if has_config_files(config_dir) {
+            builder = add_config_files(builder, config_dir);
+        } else if !has_config_files(project_dir) {
+            let message = "No configuration file found. Application may not behave as expected";
+            load_messages.push((log::Level::Error, message.to_string()));
         }
-        if !found_config {
-            log::error!("No configuration file found. Application may not behave as expected");
+        if has_config_files(project_dir) {
+            let message = format!("Using the project config in {}", project_dir.display());
+            load_messages.push((log::Level::Info, message));
+            builder = add_config_files(builder, project_dir);
         }
 
-        let mut cfg: Self = builder.build()?.try_deserialize()?;
+        let mut settings = builder.build()?;
+        interpolate_env(&mut settings.cache)?;
+        let mut cfg: Self = settings.try_deserialize()?;
+        cfg.load_messages = load_messages;
+
+        if let Ok(backends) = std::env::var(crate::utils::WEBDRIVER_ENV.as_str()) {
+            cfg.config.webdriver_backends = parse_webdriver_backends(&backends)?;
+        }
+        if let Some(url) = &cfg.config.webdriver_url {
+            validate_webdriver_url(url)?;
+        }
+        validate_rate("tick_rate", cfg.config.tick_rate)?;
+        validate_rate("frame_rate", cfg.config.frame_rate)?;
+        if cfg.config.auto_accept.enabled && cfg.config.auto_accept.gigs.is_empty() {
+            return Err(config::ConfigError::Message(
+                "auto_accept.gigs must list the gigs whose orders may be accepted".to_string(),
+            ));
+        }
+        cfg.config.log_level_filter()?;
+        if cfg.config.log_files.max_size_kb == 0 {
+            return Err(config::ConfigError::Message(
+                "log_files.max_size_kb must be at least 1".to_string(),
+            ));
+        }
+        if cfg.config.connect_retry.attempts == 0 {
+            return Err(config::ConfigError::Message(
+                "connect_retry.attempts must be at least 1".to_string(),
+            ));
+        }
+        if cfg.config.max_concurrent_webdriver_ops == 0 {
+            return Err(config::ConfigError::Message(
+                "max_concurrent_webdriver_ops must be at least 1".to_string(),
+            ));
+        }
 
-        for (mode, default_bindings) in default_config.keybindings.iter() {
-            let user_bindings = cfg.keybindings.entry(*mode).or_default();
+        let default_keymaps = default_config.keybindings.modes.iter();
+        let default_global = (None, &default_config.keybindings.global);
+        for (mode, default_bindings) in default_keymaps
+            .map(|(mode, keymap)| (Some(*mode), keymap))
+            .chain([default_global])
+        {
+            let user_bindings = match mode {
+                Some(mode) => cfg.keybindings.modes.entry(mode).or_default(),
+                None => &mut cfg.keybindings.global,
+            };
             for (key, cmd) in default_bindings.iter() {
                 user_bindings
                     .entry(key.clone())
@@ -79,7 +900,7 @@ impl Config {
             for (style_key, style) in default_styles.iter() {
                 user_styles
                     .entry(style_key.clone())
-                    .or_insert_with(|| style.clone());
+                    .or_insert_with(|| *style);
             }
         }
 
@@ -87,28 +908,207 @@ impl Config {
     }
 }
 
-#[derive(Clone, Debug, Default, Deref, DerefMut)]
-pub struct KeyBindings(pub HashMap<Mode, HashMap<Vec<KeyEvent>, Action>>);
+/// Check that a configured rate can be turned into an interval.
+fn validate_rate(name: &str, rate: Option<f64>) -> Result<(), config::ConfigError> {
+    match rate {
+        Some(rate) if !rate.is_finite() || rate <= 0.0 => Err(config::ConfigError::Message(
+            format!("{name} must be a positive number, not {rate}"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Check that `url` is an http(s) URL; `localhost:4444` would otherwise parse with `localhost` as
+/// its scheme.
+fn validate_webdriver_url(url: &str) -> Result<(), config::ConfigError> {
+    match url::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(()),
+        Ok(parsed) => Err(config::ConfigError::Message(format!(
+            "webdriver_url {url:?} must be an http or https URL, not {}",
+            parsed.scheme()
+        ))),
+        Err(e) => Err(config::ConfigError::Message(format!(
+            "webdriver_url {url:?} is not a valid URL: {e}"
+        ))),
+    }
+}
+
+/// The backends in a comma separated list like `chrome,gecko`.
+fn parse_webdriver_backends(list: &str) -> Result<Vec<WebDriverKind>, config::ConfigError> {
+    let env = crate::utils::WEBDRIVER_ENV.as_str();
+    let backends = list
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            WebDriverKind::deserialize(de::value::StrDeserializer::<de::value::Error>::new(name))
+                .map_err(|_| {
+                    config::ConfigError::Message(format!(
+                        "{env}: unknown WebDriver {name:?}, expected gecko or chrome"
+                    ))
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if backends.is_empty() {
+        return Err(config::ConfigError::Message(format!(
+            "{env} must name at least one WebDriver"
+        )));
+    }
+    Ok(backends)
+}
+
+/// The `config.*` files looked for in a config directory, in the order they are merged.
+fn config_files(dir: &Path) -> impl Iterator<Item = (PathBuf, config::FileFormat)> + '_ {
+    [
+        ("config.json5", config::FileFormat::Json5),
+        ("config.json", config::FileFormat::Json),
+        ("config.yaml", config::FileFormat::Yaml),
+        ("config.toml", config::FileFormat::Toml),
+        ("config.ini", config::FileFormat::Ini),
+    ]
+    .into_iter()
+    .map(move |(file, format)| (dir.join(file), format))
+}
+
+fn has_config_files(dir: &Path) -> bool {
+    config_files(dir).any(|(path, _)| path.exists())
+}
+
+fn add_config_files(
+    mut builder: config::ConfigBuilder<config::builder::DefaultState>,
+    dir: &Path,
+) -> config::ConfigBuilder<config::builder::DefaultState> {
+    for (path, format) in config_files(dir) {
+        builder = builder.add_source(config::File::from(path).format(format).required(false));
+    }
+    builder
+}
+
+/// Replace `${VAR}` in every string value with the contents of the environment variable `VAR`,
+/// so secrets can stay out of the config file.
+fn interpolate_env(value: &mut Value) -> Result<(), config::ConfigError> {
+    match &mut value.kind {
+        ValueKind::String(s) => *s = interpolate_env_str(s)?,
+        ValueKind::Table(table) => {
+            for value in table.values_mut() {
+                interpolate_env(value)?;
+            }
+        }
+        ValueKind::Array(array) => {
+            for value in array.iter_mut() {
+                interpolate_env(value)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn interpolate_env_str(raw: &str) -> Result<String, config::ConfigError> {
+    let mut interpolated = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find("${") {
+        interpolated.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find('}').ok_or_else(|| {
+            config::ConfigError::Message("Unterminated `${` in config value".to_string())
+        })?;
+        let name = &after[..end];
+        let value = std::env::var(name).map_err(|_| {
+            config::ConfigError::Message(format!(
+                "Config references environment variable `{name}`, which is not set"
+            ))
+        })?;
+        interpolated.push_str(&value);
+        rest = &after[end + 1..];
+    }
+    interpolated.push_str(rest);
+    Ok(interpolated)
+}
+
+/// The actions bound to key chords.
+pub type Keymap = HashMap<Vec<KeyEvent>, Action>;
+
+#[derive(Clone, Debug, Default)]
+pub struct KeyBindings {
+    /// The keymap of each mode.
+    pub modes: HashMap<Mode, Keymap>,
+    /// `"Global"` in the config: applies in every mode, for the keys that mode doesn't bind
+    /// itself, so a mode without a keymap can still be quit or left.
+    pub global: Keymap,
+}
 
 impl<'de> Deserialize<'de> for KeyBindings {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let parsed_map = HashMap::<Mode, HashMap<String, Action>>::deserialize(deserializer)?;
+        fn convert(keymap: HashMap<String, Action>) -> Keymap {
+            keymap
+                .into_iter()
+                .map(|(key_str, cmd)| (parse_key_sequence(&key_str).unwrap(), cmd))
+                .collect()
+        }
 
-        let keybindings = parsed_map
-            .into_iter()
-            .map(|(mode, inner_map)| {
-                let converted_inner_map = inner_map
-                    .into_iter()
-                    .map(|(key_str, cmd)| (parse_key_sequence(&key_str).unwrap(), cmd))
-                    .collect();
-                (mode, converted_inner_map)
-            })
-            .collect();
+        // The config crate lowercases the keys of some formats, so the names are matched
+        // ignoring case, as the config crate does for a `Mode` key.
+        let parsed_map = HashMap::<String, HashMap<String, Action>>::deserialize(deserializer)?;
+        let mut keybindings = KeyBindings::default();
+        for (name, keymap) in parsed_map {
+            if name.eq_ignore_ascii_case("Global") {
+                keybindings.global = convert(keymap);
+                continue;
+            }
+            let mode = Mode::SCREENS
+                .into_iter()
+                .find(|mode| format!("{mode:?}").eq_ignore_ascii_case(&name))
+                .ok_or_else(|| {
+                    de::Error::unknown_variant(
+                        &name,
+                        &["Global", "Home", "Orders", "Messages", "Gigs", "Settings"],
+                    )
+                })?;
+            keybindings.modes.insert(mode, convert(keymap));
+        }
+        Ok(keybindings)
+    }
+}
 
-        Ok(KeyBindings(keybindings))
+impl KeyBindings {
+    /// The action bound to `keys` in `mode`, or else in the global keymap.
+    pub fn action(&self, mode: Mode, keys: &[KeyEvent]) -> Option<&Action> {
+        self.keymaps(mode).find_map(|keymap| keymap.get(keys))
+    }
+
+    /// Every chord that works in `mode` with its action: the mode's own, and those of the global
+    /// keymap it doesn't bind itself.
+    pub fn effective(&self, mode: Mode) -> Vec<(&[KeyEvent], &Action)> {
+        let own = self.modes.get(&mode);
+        own.into_iter()
+            .flatten()
+            .chain(
+                self.global
+                    .iter()
+                    .filter(|(keys, _)| own.is_none_or(|own| !own.contains_key(*keys))),
+            )
+            .map(|(keys, action)| (keys.as_slice(), action))
+            .collect()
+    }
+
+    /// Whether `keys` start a chord bound in `mode` or in the global keymap.
+    pub fn is_prefix(&self, mode: Mode, keys: &[KeyEvent]) -> bool {
+        self.keymaps(mode)
+            .any(|keymap| keymap.keys().any(|chord| chord.starts_with(keys)))
+    }
+
+    /// `mode`'s keymap, if it has one, then the global keymap.
+    fn keymaps(&self, mode: Mode) -> impl Iterator<Item = &Keymap> {
+        self.modes.get(&mode).into_iter().chain([&self.global])
+    }
+
+    /// The keymaps of all modes and the global one.
+    pub fn all(&self) -> impl Iterator<Item = &Keymap> {
+        self.modes.values().chain([&self.global])
     }
 }
 
@@ -193,6 +1193,13 @@ fn parse_key_code_with_modifiers(
     Ok(KeyEvent::new(c, modifiers))
 }
 
+/// `keys` in the `<a><b>` notation of the keybindings config.
+pub fn key_sequence_to_string(keys: &[KeyEvent]) -> String {
+    keys.iter()
+        .map(|key| format!("<{}>", key_event_to_string(key)))
+        .collect()
+}
+
 pub fn key_event_to_string(key_event: &KeyEvent) -> String {
     let char;
     let key_code = match key_event.code {
@@ -214,7 +1221,7 @@ pub fn key_event_to_string(key_event: &KeyEvent) -> String {
             char = format!("f({c})");
             &char
         }
-        KeyCode::Char(c) if c == ' ' => "space",
+        KeyCode::Char(' ') => "space",
         KeyCode::Char(c) => {
             char = c.to_string();
             &char
@@ -283,6 +1290,119 @@ pub fn parse_key_sequence(raw: &str) -> Result<Vec<KeyEvent>, String> {
     sequences.into_iter().map(parse_key_event).collect()
 }
 
+/// The colors the UI is drawn with: a preset, `"dark"` or `"light"`, or a preset with some of its
+/// colors replaced, e.g. `{ preset: "light", accent: "#d75f00" }`. Colors are names like
+/// `"lightblue"`, `"#rrggbb"` or indexes into the terminal's palette; `"reset"` leaves the
+/// terminal's own color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    /// Titles and the logo.
+    pub primary: Color,
+    /// Progress gauges.
+    pub accent: Color,
+    /// Behind everything that doesn't set its own background.
+    pub background: Color,
+    /// Text that doesn't set its own color.
+    pub text: Color,
+    /// Errors.
+    pub error: Color,
+}
+
+impl Theme {
+    /// The terminal's own foreground and background, with colored highlights.
+    pub fn dark() -> Self {
+        Self {
+            primary: Color::Cyan,
+            accent: Color::Green,
+            background: Color::Reset,
+            text: Color::Reset,
+            error: Color::Red,
+        }
+    }
+
+    /// Dark text on a white background, for terminals with a light color scheme.
+    pub fn light() -> Self {
+        Self {
+            primary: Color::Blue,
+            accent: Color::Magenta,
+            background: Color::White,
+            text: Color::Black,
+            error: Color::Red,
+        }
+    }
+
+    pub fn preset(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl<'de> Deserialize<'de> for Theme {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Colors {
+            preset: Option<String>,
+            primary: Option<String>,
+            accent: Option<String>,
+            background: Option<String>,
+            text: Option<String>,
+            error: Option<String>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Preset(String),
+            Colors(Colors),
+        }
+
+        let preset = |name: &str| {
+            Theme::preset(name).ok_or_else(|| {
+                de::Error::custom(format!(
+                    "unknown theme {name:?}, the presets are \"dark\" and \"light\""
+                ))
+            })
+        };
+        let colors = match Raw::deserialize(deserializer)? {
+            Raw::Preset(name) => return preset(&name),
+            Raw::Colors(colors) => colors,
+        };
+        let mut theme = match &colors.preset {
+            Some(name) => preset(name)?,
+            None => Theme::default(),
+        };
+        for (color, raw) in [
+            (&mut theme.primary, colors.primary),
+            (&mut theme.accent, colors.accent),
+            (&mut theme.background, colors.background),
+            (&mut theme.text, colors.text),
+            (&mut theme.error, colors.error),
+        ] {
+            if let Some(raw) = raw {
+                *color = raw.parse().map_err(|_| {
+                    de::Error::custom(format!(
+                        "{raw:?} is not a color, use a name like \"lightblue\" or \"#rrggbb\""
+                    ))
+                })?;
+            }
+        }
+        Ok(theme)
+    }
+}
+
 #[derive(Clone, Debug, Default, Deref, DerefMut)]
 pub struct Styles(pub HashMap<Mode, HashMap<String, Style>>);
 
@@ -455,7 +1575,7 @@ mod tests {
     #[test]
     fn test_parse_color_rgb() {
         let color = parse_color("rgb123");
-        let expected = 16 + 1 * 36 + 2 * 6 + 3;
+        let expected = 16 + 36 + 2 * 6 + 3;
         assert_eq!(color, Some(Color::Indexed(expected)));
     }
 
@@ -467,18 +1587,295 @@ mod tests {
 
     #[test]
     fn test_config() -> Result<()> {
-        let c = Config::new()?;
+        let c = Config::new(None)?;
         assert_eq!(
             c.keybindings
+                .modes
                 .get(&Mode::Home)
                 .unwrap()
                 .get(&parse_key_sequence("<q>").unwrap_or_default())
                 .unwrap(),
-            &Action::Quit
+            &Action::RequestQuit
+        );
+        assert_eq!(
+            c.keybindings
+                .action(Mode::Gigs, &parse_key_sequence("<Alt-l>").unwrap()),
+            Some(&Action::Logout)
+        );
+        assert_eq!(
+            c.keybindings
+                .action(Mode::Orders, &parse_key_sequence("<m>").unwrap()),
+            Some(&Action::SwitchMode(Mode::Messages))
+        );
+        assert_eq!(
+            c.keybindings
+                .action(Mode::Home, &parse_key_sequence("<f12>").unwrap()),
+            Some(&Action::Screenshot(PathBuf::from("screenshot.png")))
         );
         Ok(())
     }
 
+    #[test]
+    fn test_config_layers() {
+        let dir = std::env::temp_dir().join(format!("stash-config-test-{}", std::process::id()));
+        let (user_dir, project_dir) = (dir.join("user"), dir.join("project"));
+        std::fs::create_dir_all(&user_dir).unwrap();
+        std::fs::create_dir_all(&project_dir).unwrap();
+        std::fs::write(
+            user_dir.join("config.json5"),
+            r#"{ heartbeat_secs: 30, max_concurrent_webdriver_ops: 2,
+                 keybindings: { Home: { "<x>": "Quit", "<y>": "Refresh" } } }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            project_dir.join("config.toml"),
+            "heartbeat_secs = 10\n[keybindings.Home]\n\"<y>\" = \"Help\"\n",
+        )
+        .unwrap();
+
+        let c = Config::layered(None, &user_dir, &project_dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let project_config = format!("Using the project config in {}", project_dir.display());
+        assert_eq!(c.load_messages, [(log::Level::Info, project_config)]);
+        assert_eq!(c.config.heartbeat_secs, Some(10));
+        assert_eq!(c.config.max_concurrent_webdriver_ops, 2);
+        let home = c.keybindings.modes.get(&Mode::Home).unwrap();
+        let binding = |keys| home.get(&parse_key_sequence(keys).unwrap()).unwrap();
+        assert_eq!(binding("<x>"), &Action::Quit);
+        assert_eq!(binding("<y>"), &Action::Help);
+        assert_eq!(binding("<q>"), &Action::RequestQuit);
+    }
+
+    #[test]
+    fn test_auto_accept_cant_be_bound() {
+        for action in [r#""ArmAutoAccept""#, r#"{ "AutoAcceptArmed": true }"#] {
+            let json = format!(r#"{{ "Home": {{ "<a>": {action} }} }}"#);
+            assert!(serde_json::from_str::<KeyBindings>(&json).is_err());
+        }
+    }
+
+    #[test]
+    fn test_confirmed_actions_cant_be_bound() {
+        // Bound to a key, these would skip the confirmation that sends them.
+        let actions = [
+            r#"{ "SendBulkMessage": { "thread_ids": ["buyer"], "text": "Hi" } }"#,
+            r#"{ "WriteSessionSnapshot": { "redact": false } }"#,
+        ];
+        for action in actions {
+            let json = format!(r#"{{ "Messages": {{ "<a>": {action} }} }}"#);
+            assert!(serde_json::from_str::<KeyBindings>(&json).is_err());
+        }
+    }
+
+    #[test]
+    fn test_log_level_keys_are_free() {
+        // Bound keys never reach the log, even while it has the focus.
+        let config: Config = json5::from_str(CONFIG).unwrap();
+        for mode in Mode::SCREENS {
+            for key in ["<e>", "<w>", "<i>", "<d>"] {
+                let keys = parse_key_sequence(key).unwrap();
+                assert_eq!(
+                    config.keybindings.action(mode, &keys),
+                    None,
+                    "{key} is bound in {mode:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_auto_accept_allows() {
+        let auto_accept = AutoAccept {
+            enabled: true,
+            gigs: vec!["I will design your logo".to_string()],
+        };
+        let mut order = Order {
+            id: "FO1".to_string(),
+            buyer: "ann".to_string(),
+            gig: " I will design your logo".to_string(),
+            due_date: String::new(),
+            status: OrderStatus::PendingAcceptance,
+            amount: "$25".to_string(),
+        };
+        assert!(auto_accept.allows(&order));
+        assert!(!AutoAccept {
+            enabled: false,
+            ..auto_accept.clone()
+        }
+        .allows(&order));
+        order.status = OrderStatus::InProgress;
+        assert!(!auto_accept.allows(&order));
+        order.status = OrderStatus::PendingAcceptance;
+        order.gig = "I will write your copy".to_string();
+        assert!(!auto_accept.allows(&order));
+    }
+
+    #[test]
+    fn test_global_keybindings() {
+        let config: Config = json5::from_str(
+            r#"{ keybindings: {
+                "Global": { "<Ctrl-c>": "Quit", "<g><h>": { "SwitchMode": "Home" } },
+                "Gigs": { "<Ctrl-c>": "Refresh", "<g><g>": "Help" },
+            } }"#,
+        )
+        .unwrap();
+        let keys = |raw| parse_key_sequence(raw).unwrap();
+        let bindings = &config.keybindings;
+        assert_eq!(
+            bindings.action(Mode::Gigs, &keys("<Ctrl-c>")),
+            Some(&Action::Refresh)
+        );
+        assert_eq!(
+            bindings.action(Mode::Home, &keys("<Ctrl-c>")),
+            Some(&Action::Quit)
+        );
+        assert_eq!(
+            bindings.action(Mode::Gigs, &keys("<g><h>")),
+            Some(&Action::SwitchMode(Mode::Home))
+        );
+        let effective = bindings.effective(Mode::Gigs);
+        assert_eq!(effective.len(), 3);
+        assert!(effective.contains(&(keys("<Ctrl-c>").as_slice(), &Action::Refresh)));
+        assert!(bindings.is_prefix(Mode::Home, &keys("<g>")));
+        assert!(!bindings.is_prefix(Mode::Home, &keys("<x>")));
+    }
+
+    #[test]
+    fn test_selector_fallbacks() {
+        let selectors: Selectors =
+            json5::from_str(r#"{ gig_row: [".gigs tr", ".gig-card"], gig_title: ".title a" }"#)
+                .unwrap();
+        assert_eq!(selectors.gig_row.candidates(), [".gigs tr", ".gig-card"]);
+        assert_eq!(selectors.gig_title, Selector::from(".title a"));
+        assert_eq!(selectors.gig_price, Selectors::default().gig_price);
+        assert!(json5::from_str::<Selectors>(r#"{ gig_row: [] }"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_webdriver_backends() {
+        assert_eq!(
+            parse_webdriver_backends(" chrome, gecko").unwrap(),
+            [WebDriverKind::Chrome, WebDriverKind::Gecko]
+        );
+        assert!(parse_webdriver_backends("firefox").is_err());
+        assert!(parse_webdriver_backends(",").is_err());
+    }
+
+    #[test]
+    fn test_validate_webdriver_url() {
+        assert!(validate_webdriver_url("http://grid.local:4444/wd/hub").is_ok());
+        assert!(validate_webdriver_url("localhost:4444").is_err());
+        assert!(validate_webdriver_url("not a url").is_err());
+    }
+
+    #[test]
+    fn test_theme() {
+        let theme = |json| json5::from_str::<Config>(json).map(|config| config.config.theme);
+        assert_eq!(theme("{}").unwrap(), Theme::dark());
+        assert_eq!(theme(r#"{ theme: "light" }"#).unwrap(), Theme::light());
+        let custom = theme(r##"{ theme: { preset: "light", accent: "#d75f00" } }"##).unwrap();
+        assert_eq!(custom.accent, Color::Rgb(0xd7, 0x5f, 0x00));
+        assert_eq!(custom.background, Color::White);
+        assert!(theme(r#"{ theme: "solarized" }"#).is_err());
+        assert!(theme(r#"{ theme: { error: "crimson-ish" } }"#).is_err());
+    }
+
+    #[test]
+    fn test_log_level_filter() {
+        let mut config = AppConfig::default();
+        assert_eq!(config.log_level_filter().unwrap(), log::LevelFilter::Info);
+        config.log_level = "DEBUG".to_string();
+        assert_eq!(config.log_level_filter().unwrap(), log::LevelFilter::Debug);
+        config.log_level = "verbose".to_string();
+        assert!(config.log_level_filter().is_err());
+    }
+
+    #[test]
+    fn test_validate_rate() {
+        assert!(validate_rate("tick_rate", None).is_ok());
+        assert!(validate_rate("tick_rate", Some(0.5)).is_ok());
+        assert!(validate_rate("tick_rate", Some(0.0)).is_err());
+        assert!(validate_rate("frame_rate", Some(-60.0)).is_err());
+        assert!(validate_rate("frame_rate", Some(f64::INFINITY)).is_err());
+    }
+
+    #[test]
+    fn test_connect_retry_delay() {
+        let retry = ConnectRetry::default();
+        assert_eq!(retry.delay(1), Duration::from_millis(200));
+        assert_eq!(retry.delay(3), Duration::from_millis(800));
+    }
+
+    #[test]
+    fn test_read_env_file() {
+        let dir = std::env::temp_dir().join(format!("stash-env-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".env");
+        std::fs::write(
+            &path,
+            "# login\nFIVVER_USERNAME=seller\nexport FIVVER_PASSWORD = \"p=ss word\"\n",
+        )
+        .unwrap();
+        let vars = read_env_file(&path).unwrap();
+        assert_eq!(vars["FIVVER_USERNAME"], "seller");
+        assert_eq!(vars["FIVVER_PASSWORD"], "p=ss word");
+        assert!(read_env_file(&dir.join("missing")).unwrap().is_empty());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_secret_is_redacted() {
+        let config: Config = json5::from_str(r#"{ fivver_password: "hunter2" }"#).unwrap();
+        assert_eq!(
+            config.config.fivver_password.as_ref().map(Secret::expose),
+            Some("hunter2")
+        );
+        assert!(!format!("{config:?}").contains("hunter2"));
+    }
+
+    #[test]
+    fn test_config_missing_path() {
+        let err = Config::new(Some(PathBuf::from("does/not/exist.json5"))).unwrap_err();
+        assert!(err.to_string().contains("does/not/exist.json5"));
+    }
+
+    #[test]
+    fn test_lazy_load_partial() {
+        let config: Config = json5::from_str(r#"{ lazy_load: { views: ["thread"] } }"#).unwrap();
+        assert_eq!(config.config.lazy_load.views, [View::Thread]);
+        assert_eq!(config.config.lazy_load.poll_ms, LazyLoad::default().poll_ms);
+    }
+
+    #[test]
+    fn test_feedback() {
+        let config: Config =
+            json5::from_str(r#"{ feedback: { new_order: ["bell", "flash"] } }"#).unwrap();
+        assert_eq!(
+            config.config.feedback,
+            HashMap::from([(
+                FeedbackEvent::NewOrder,
+                vec![Feedback::Bell, Feedback::Flash]
+            )])
+        );
+    }
+
+    #[test]
+    fn test_interpolate_env() {
+        std::env::set_var("STASH_TEST_INTERPOLATE", "hunter2");
+        assert_eq!(
+            interpolate_env_str("pass: ${STASH_TEST_INTERPOLATE}!").unwrap(),
+            "pass: hunter2!"
+        );
+        assert_eq!(interpolate_env_str("plain").unwrap(), "plain");
+    }
+
+    #[test]
+    fn test_interpolate_env_unset() {
+        let err = interpolate_env_str("${STASH_TEST_UNSET_VARIABLE}").unwrap_err();
+        assert!(err.to_string().contains("STASH_TEST_UNSET_VARIABLE"));
+        assert!(interpolate_env_str("${STASH_TEST_UNSET_VARIABLE").is_err());
+    }
+
     #[test]
     fn test_simple_keys() {
         assert_eq!(
@@ -560,4 +1957,24 @@ mod tests {
             KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT)
         );
     }
+
+    #[test]
+    fn test_active_hours() {
+        let hours: ActiveHours = serde_json::from_value(serde_json::json!({
+            "start": "22:00",
+            "end": "06:30",
+            "timezone": "Europe/Berlin",
+        }))
+        .unwrap();
+        assert_eq!(hours.outside, OutsideHours::Queue);
+        let at = |time: &str| time.parse::<DateTime<Utc>>().unwrap();
+        // Berlin is UTC+2 in summer.
+        assert!(hours.contains(at("2024-07-01T21:00:00Z"), Tz::UTC));
+        assert!(hours.contains(at("2024-07-01T04:00:00Z"), Tz::UTC));
+        assert!(!hours.contains(at("2024-07-01T04:30:00Z"), Tz::UTC));
+        assert!(!hours.contains(at("2024-07-01T12:00:00Z"), Tz::UTC));
+
+        let invalid = serde_json::json!({ "start": "9am", "end": "17:00" });
+        assert!(serde_json::from_value::<ActiveHours>(invalid).is_err());
+    }
 }
@@ -0,0 +1,380 @@
+use std::{
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{Arc, Mutex},
+};
+
+use color_eyre::eyre::{bail, Result, WrapErr};
+use fantoccini::{error::NewSessionError, wd::Capabilities, Client, ClientBuilder};
+use serde_json::json;
+use tokio::{sync::mpsc::UnboundedSender, time::sleep};
+
+use crate::{
+    action::Action,
+    browser::Browser,
+    config::{ConnectRetry, WebDriverKind},
+};
+
+/// A WebDriver server stash is connected to.
+pub struct DriverProcess {
+    pub kind: WebDriverKind,
+    pub port: u16,
+    /// The server process if we started it, `None` if it was already running.
+    pub child: Option<Child>,
+    /// The id of a server that was already running because an earlier run of stash started it
+    /// and detached, see [`DriverProcess::detach`]. Servers started by anything else have none.
+    pub detached_pid: Option<u32>,
+    /// Whether stash may start and stop the server, see `AppConfig::manage_webdriver`.
+    pub managed: bool,
+}
+
+/// The WebDriver server and browser session of one account profile.
+pub struct Driver {
+    pub browser: Browser,
+    /// Filled in by the connecting task once one of the backends has connected.
+    pub process: Arc<Mutex<Option<DriverProcess>>>,
+}
+
+pub async fn is_running(port: u16) -> bool {
+    tokio::net::TcpStream::connect(("127.0.0.1", port))
+        .await
+        .is_ok()
+}
+
+pub fn start(kind: WebDriverKind, port: u16) -> Result<Child> {
+    Command::new(kind.binary())
+        .arg(format!("--port={port}"))
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .wrap_err_with(|| format!("Failed to start {}", kind.binary()))
+}
+
+/// How the browser of a local WebDriver server is launched.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BrowserOptions {
+    /// The profile directory, see `AppConfig::browser_profile_dir`. The cookies and logins kept
+    /// there outlive the WebDriver session.
+    pub profile_dir: Option<PathBuf>,
+    /// Run without a window.
+    pub headless: bool,
+}
+
+impl BrowserOptions {
+    /// The capabilities that launch `kind`'s browser this way.
+    pub fn capabilities(&self, kind: WebDriverKind) -> Capabilities {
+        let mut args = Vec::new();
+        if self.headless {
+            args.push(match kind {
+                WebDriverKind::Gecko => "-headless".to_string(),
+                WebDriverKind::Chrome => "--headless=new".to_string(),
+            });
+        }
+        if let Some(dir) = &self.profile_dir {
+            match kind {
+                WebDriverKind::Gecko => {
+                    args.extend(["-profile".to_string(), dir.display().to_string()])
+                }
+                WebDriverKind::Chrome => args.push(format!("--user-data-dir={}", dir.display())),
+            }
+        }
+        if args.is_empty() {
+            return Capabilities::new();
+        }
+        let key = match kind {
+            WebDriverKind::Gecko => "moz:firefoxOptions",
+            WebDriverKind::Chrome => "goog:chromeOptions",
+        };
+        Capabilities::from_iter([(key.to_string(), json!({ "args": args }))])
+    }
+}
+
+/// Open a session on the server at `url`, retrying with backoff while it doesn't answer yet, and
+/// reporting each retry on `progress`. A server that refuses the session isn't retried.
+async fn open_session(
+    url: &str,
+    capabilities: Capabilities,
+    retry: &ConnectRetry,
+    progress: &UnboundedSender<Action>,
+) -> Result<Client, NewSessionError> {
+    let mut attempt = 1;
+    loop {
+        let result = ClientBuilder::native()
+            .capabilities(capabilities.clone())
+            .connect(url)
+            .await;
+        match result {
+            Ok(client) => return Ok(client),
+            Err(e @ (NewSessionError::Failed(_) | NewSessionError::Lost(_)))
+                if attempt < retry.attempts =>
+            {
+                log::debug!("Connecting to {url} failed: {e}");
+                sleep(retry.delay(attempt)).await;
+                attempt += 1;
+                let _ = progress.send(Action::status(format!(
+                    "Connecting to WebDriver (attempt {attempt}/{})...",
+                    retry.attempts
+                )));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Connect to the `kind` server on `port`. If nothing listens there yet, the server is started
+/// first when `managed`, and it is an error otherwise. The browser is launched with `options`.
+///
+/// Firefox and Chrome lock their profile while they run, so a profile that another browser
+/// still has open, e.g. one started by hand or left behind by a crashed run, can't be used
+/// and the session is refused.
+pub async fn connect(
+    kind: WebDriverKind,
+    port: u16,
+    managed: bool,
+    options: &BrowserOptions,
+    retry: &ConnectRetry,
+    progress: &UnboundedSender<Action>,
+) -> Result<(Client, DriverProcess)> {
+    let mut child = None;
+    let mut detached_pid = None;
+    if is_running(port).await {
+        detached_pid = take_detached_pid(&crate::utils::get_data_dir(), kind, port);
+    } else {
+        if !managed {
+            bail!(
+                "No {kind} server is listening on port {port}. Start {} yourself or set \
+                 manage_webdriver to true",
+                kind.binary()
+            );
+        }
+        child = Some(start(kind, port)?);
+    }
+    let mut process = DriverProcess {
+        kind,
+        port,
+        child,
+        detached_pid,
+        managed,
+    };
+    if let Some(dir) = &options.profile_dir {
+        std::fs::create_dir_all(dir)
+            .wrap_err_with(|| format!("Failed to create browser profile {}", dir.display()))?;
+    }
+    let url = format!("http://localhost:{port}");
+    match open_session(&url, options.capabilities(kind), retry, progress).await {
+        Ok(client) => Ok((client, process)),
+        Err(e) => {
+            if process.child.is_some() {
+                // The connect error says more than a failure to stop the server it was for.
+                if let Err(stop_error) = process.stop() {
+                    log::error!("Failed to stop {kind} on port {port}: {stop_error:#}");
+                }
+            }
+            let refused = matches!(e, NewSessionError::SessionNotCreated(_));
+            let result =
+                Err(e).wrap_err_with(|| format!("Failed to connect to {kind} on port {port}"));
+            match &options.profile_dir {
+                Some(dir) if refused => result.wrap_err_with(|| {
+                    format!(
+                        "{kind} could not open the browser profile {}. Another browser is \
+                         probably using it; close that one or set a different browser_profile_dir",
+                        dir.display()
+                    )
+                }),
+                _ => result,
+            }
+        }
+    }
+}
+
+/// Connect to the first of `backends`, given with their ports, that works.
+pub async fn connect_any(
+    backends: &[(WebDriverKind, u16)],
+    managed: bool,
+    options: &BrowserOptions,
+    retry: &ConnectRetry,
+    progress: &UnboundedSender<Action>,
+) -> Result<(Client, DriverProcess)> {
+    let mut failures = Vec::new();
+    for &(kind, port) in backends {
+        match connect(kind, port, managed, options, retry, progress).await {
+            Ok(connected) => {
+                log::info!("Connected to {kind} on port {port}");
+                return Ok(connected);
+            }
+            Err(e) => {
+                log::warn!("{e:#}");
+                failures.push(format!("{e:#}"));
+            }
+        }
+    }
+    bail!(
+        "Failed to connect to WebDriver, tried: {}",
+        failures.join("; ")
+    )
+}
+
+/// Connect to the server at `url`, which stash doesn't manage, see `AppConfig::webdriver_url`.
+pub async fn connect_url(
+    url: &str,
+    retry: &ConnectRetry,
+    progress: &UnboundedSender<Action>,
+) -> Result<Client> {
+    let client = open_session(url, Capabilities::new(), retry, progress)
+        .await
+        .wrap_err_with(|| format!("Failed to connect to WebDriver at {url}"))?;
+    log::info!("Connected to WebDriver at {url}");
+    Ok(client)
+}
+
+/// Where the id of a detached server on `port` is kept until the next launch adopts it.
+fn pid_file(data_dir: &Path, port: u16) -> PathBuf {
+    data_dir.join(format!("webdriver-{port}.pid"))
+}
+
+/// The id of the `kind` server an earlier run detached from on `port`, if any. The file is
+/// removed, a server is only adopted once. An id that now belongs to another process, e.g. after
+/// a reboot, isn't adopted, so that stopping the server can't kill that process.
+fn take_detached_pid(data_dir: &Path, kind: WebDriverKind, port: u16) -> Option<u32> {
+    let path = pid_file(data_dir, port);
+    let pid = std::fs::read_to_string(&path).ok()?.trim().parse().ok();
+    if let Err(e) = std::fs::remove_file(&path) {
+        log::warn!("Failed to remove {}: {e}", path.display());
+    }
+    let pid = pid?;
+    if !is_driver_process(pid, kind, port) {
+        log::warn!("Not adopting process {pid}, it isn't the {kind} server on port {port}");
+        return None;
+    }
+    Some(pid)
+}
+
+/// Whether the command line of process `pid` runs `kind`'s binary with `--port=<port>`. Where
+/// `/proc` can't tell, e.g. because the process is gone, it doesn't.
+fn is_driver_process(pid: u32, kind: WebDriverKind, port: u16) -> bool {
+    let Ok(cmdline) = std::fs::read(format!("/proc/{pid}/cmdline")) else {
+        return false;
+    };
+    let args: Vec<_> = cmdline
+        .split(|&byte| byte == 0)
+        .map(String::from_utf8_lossy)
+        .collect();
+    let port_arg = format!("--port={port}");
+    let is_binary = |arg: &str| {
+        Path::new(arg)
+            .file_name()
+            .is_some_and(|name| name == kind.binary())
+    };
+    args.iter().any(|arg| is_binary(arg)) && args.iter().any(|arg| *arg == port_arg)
+}
+
+impl DriverProcess {
+    /// Stop the server if stash started it, in this run or in an earlier one that detached. A
+    /// server started by something else, or one that isn't `managed`, is left running.
+    pub fn stop(&mut self) -> Result<()> {
+        if !self.managed {
+            return Ok(());
+        }
+        if let Some(child) = &mut self.child {
+            child
+                .kill()
+                .wrap_err_with(|| format!("Failed to stop {}", self.kind.binary()))?;
+            child.wait()?;
+        } else if let Some(pid) = self.detached_pid {
+            let output = Command::new("kill")
+                .arg(pid.to_string())
+                .output()
+                .wrap_err_with(|| format!("Failed to stop {}", self.kind.binary()))?;
+            if !output.status.success() {
+                bail!(
+                    "Failed to stop {} (process {pid}): {}",
+                    self.kind.binary(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Leave the server running for the next launch, remembering its id so that launch may stop
+    /// it. Nothing is remembered for a server stash didn't start.
+    pub fn detach(&self, data_dir: &Path) -> Result<()> {
+        let pid = match &self.child {
+            Some(child) => child.id(),
+            None => match self.detached_pid {
+                Some(pid) => pid,
+                None => return Ok(()),
+            },
+        };
+        std::fs::create_dir_all(data_dir)?;
+        let path = pid_file(data_dir, self.port);
+        std::fs::write(&path, pid.to_string())
+            .wrap_err_with(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_detached_pid_is_adopted_once() {
+        let dir = std::env::temp_dir().join(format!("stash-pid-{}", std::process::id()));
+        // Looks like a geckodriver on port 4444 to `is_driver_process`: sh gets them as $0 and $1.
+        let mut server = Command::new("sh")
+            .args(["-c", "sleep 10; true", "geckodriver", "--port=4444"])
+            .spawn()
+            .unwrap();
+        let mut process = DriverProcess {
+            kind: WebDriverKind::Gecko,
+            port: 4444,
+            child: None,
+            detached_pid: Some(server.id()),
+            managed: true,
+        };
+        // The command line only shows up once the process got going.
+        let cmdline = format!("/proc/{}/cmdline", server.id());
+        while std::fs::read(&cmdline).unwrap().is_empty() {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        process.detach(&dir).unwrap();
+        assert_eq!(take_detached_pid(&dir, WebDriverKind::Gecko, 4445), None);
+        assert_eq!(
+            take_detached_pid(&dir, WebDriverKind::Gecko, 4444),
+            Some(server.id())
+        );
+        assert_eq!(take_detached_pid(&dir, WebDriverKind::Gecko, 4444), None);
+
+        // Not the server on another port, nor with another binary.
+        process.detach(&dir).unwrap();
+        assert_eq!(take_detached_pid(&dir, WebDriverKind::Chrome, 4444), None);
+        process.port = 4445;
+        process.detach(&dir).unwrap();
+        assert_eq!(take_detached_pid(&dir, WebDriverKind::Gecko, 4445), None);
+
+        process.stop().unwrap();
+        server.wait().unwrap();
+        // The process is gone, so stopping it again fails.
+        assert!(process.stop().is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_browser_capabilities() {
+        let mut options = BrowserOptions::default();
+        assert!(options.capabilities(WebDriverKind::Gecko).is_empty());
+
+        options.profile_dir = Some(PathBuf::from("/tmp/stash-profile"));
+        options.headless = true;
+        assert_eq!(
+            options.capabilities(WebDriverKind::Gecko)["moz:firefoxOptions"],
+            json!({ "args": ["-headless", "-profile", "/tmp/stash-profile"] })
+        );
+        assert_eq!(
+            options.capabilities(WebDriverKind::Chrome)["goog:chromeOptions"],
+            json!({ "args": ["--headless=new", "--user-data-dir=/tmp/stash-profile"] })
+        );
+    }
+}
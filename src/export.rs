@@ -0,0 +1,128 @@
+use std::{borrow::Cow, path::PathBuf};
+
+use crate::{
+    components::item_list::Identified,
+    fiverr::{conversation_url, order_url, Conversation, Order},
+};
+
+/// A scraped item that can be a row of an export, see [`to_csv`].
+pub trait Export: Identified {
+    /// What the export is named after, e.g. `orders`.
+    const NAME: &'static str;
+    /// The names of the columns [`Export::fields`] fills in.
+    const COLUMNS: &'static [&'static str];
+
+    fn fields(&self) -> Vec<String>;
+
+    /// The page showing the item, for a screenshot of it.
+    fn page_url(&self) -> String;
+}
+
+impl Export for Order {
+    const NAME: &'static str = "orders";
+    const COLUMNS: &'static [&'static str] =
+        &["id", "buyer", "gig", "due_date", "status", "amount"];
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.id.clone(),
+            self.buyer.clone(),
+            self.gig.clone(),
+            self.due_date.clone(),
+            self.status.to_string(),
+            self.amount.clone(),
+        ]
+    }
+
+    fn page_url(&self) -> String {
+        order_url(&self.id)
+    }
+}
+
+impl Export for Conversation {
+    const NAME: &'static str = "conversations";
+    const COLUMNS: &'static [&'static str] = &["username", "last_message", "unread"];
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.username.clone(),
+            self.last_message.clone(),
+            self.unread.to_string(),
+        ]
+    }
+
+    fn page_url(&self) -> String {
+        conversation_url(&self.username)
+    }
+}
+
+/// `items` as CSV with a header line. With `screenshots`, the path of each item's screenshot
+/// follows in a `screenshot` column.
+pub fn to_csv<T: Export>(items: &[T], screenshots: Option<&[PathBuf]>) -> String {
+    let mut header: Vec<&str> = T::COLUMNS.to_vec();
+    if screenshots.is_some() {
+        header.push("screenshot");
+    }
+    let mut csv = csv_line(header.into_iter().map(Cow::Borrowed));
+    for (i, item) in items.iter().enumerate() {
+        let screenshot = screenshots
+            .and_then(|paths| paths.get(i))
+            .map(|path| path.to_string_lossy().into_owned());
+        let fields = item.fields().into_iter().chain(screenshot);
+        csv.push_str(&csv_line(fields.map(Cow::Owned)));
+    }
+    csv
+}
+
+fn csv_line<'a>(fields: impl Iterator<Item = Cow<'a, str>>) -> String {
+    let fields: Vec<_> = fields.map(|field| csv_field(&field).into_owned()).collect();
+    fields.join(",") + "\n"
+}
+
+/// `value` quoted if it has a comma, quote or line break, with its quotes doubled.
+fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_to_csv() {
+        let conversations = [
+            Conversation {
+                username: "buyer".to_string(),
+                last_message: "Thanks, \"great\" work".to_string(),
+                unread: true,
+            },
+            Conversation {
+                username: "other".to_string(),
+                last_message: "One, two\nthree".to_string(),
+                unread: false,
+            },
+        ];
+        assert_eq!(
+            to_csv(&conversations, None),
+            "username,last_message,unread\n\
+             buyer,\"Thanks, \"\"great\"\" work\",true\n\
+             other,\"One, two\nthree\",false\n"
+        );
+
+        let screenshots = [
+            PathBuf::from("/data/buyer.png"),
+            PathBuf::from("/data/other.png"),
+        ];
+        assert_eq!(
+            to_csv(&conversations[..1], Some(&screenshots[..1])),
+            "username,last_message,unread,screenshot\n\
+             buyer,\"Thanks, \"\"great\"\" work\",true,/data/buyer.png\n"
+        );
+    }
+}
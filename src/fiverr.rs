@@ -0,0 +1,843 @@
+use std::{fmt, path::Path};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use color_eyre::eyre::{bail, eyre, Result};
+use fantoccini::{elements::Element, error::CmdError, key::Key};
+use serde::{Deserialize, Serialize};
+use tokio::time::{sleep, Duration, Instant};
+
+use crate::{
+    browser::{CommandError, Session, SessionSnapshot, REDACTED},
+    components::item_list::Identified,
+    config::{Selector, View},
+};
+
+pub const BASE_URL: &str = "https://www.fiverr.com";
+
+pub fn order_url(order_id: &str) -> String {
+    format!("{BASE_URL}/orders/{order_id}")
+}
+
+/// Whether `id` looks like a Fiverr order number, e.g. `FO1234ABCD56`.
+pub fn is_order_id(id: &str) -> bool {
+    let Some(rest) = id
+        .get(..2)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("FO"))
+    else {
+        return false;
+    };
+    let number = &id[rest.len()..];
+    !number.is_empty() && number.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+pub fn logout_url() -> String {
+    format!("{BASE_URL}/logout")
+}
+
+pub fn login_url() -> String {
+    format!("{BASE_URL}/login")
+}
+
+pub fn inbox_url() -> String {
+    format!("{BASE_URL}/inbox")
+}
+
+pub fn conversation_url(username: &str) -> String {
+    format!("{BASE_URL}/inbox/{username}")
+}
+
+pub fn manage_orders_url(username: &str) -> String {
+    format!("{BASE_URL}/users/{username}/manage_orders")
+}
+
+pub fn seller_dashboard_url() -> String {
+    format!("{BASE_URL}/seller_dashboard")
+}
+
+pub fn manage_gigs_url(username: &str) -> String {
+    format!("{BASE_URL}/users/{username}/manage_gigs")
+}
+
+/// What was found in a stored session file.
+#[derive(Debug, PartialEq, Eq)]
+pub enum StoredSession {
+    Missing,
+    /// The file can't be used, for the given reason.
+    Invalid(String),
+    Found(SessionSnapshot),
+}
+
+/// Read a session saved with [`save_session`], or an unredacted `Action::WriteSessionSnapshot`,
+/// from `path`.
+pub fn load_session(path: &Path) -> StoredSession {
+    let json = match std::fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return StoredSession::Missing,
+        Err(e) => return StoredSession::Invalid(e.to_string()),
+    };
+    match serde_json::from_str::<SessionSnapshot>(&json) {
+        Ok(snapshot) if snapshot.cookies.is_empty() => {
+            StoredSession::Invalid("it has no cookies".to_string())
+        }
+        Ok(snapshot) if snapshot.cookies.iter().any(|c| c.value == REDACTED) => {
+            StoredSession::Invalid("its cookies are redacted".to_string())
+        }
+        Ok(snapshot) => StoredSession::Found(snapshot),
+        Err(e) => StoredSession::Invalid(e.to_string()),
+    }
+}
+
+/// Save the current page and cookies of `session` to `path`, for [`load_session`] to pick up on
+/// the next launch.
+pub async fn save_session(session: &Session, path: &Path) -> Result<()> {
+    let snapshot = session.snapshot(false).await?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&snapshot)?)?;
+    Ok(())
+}
+
+/// Whether the open page is shown to a logged in user.
+pub async fn is_logged_in(session: &Session) -> Result<bool> {
+    match session.selectors().logged_in.find(session).await {
+        Ok(_) => Ok(true),
+        Err(e) if e.is_miss() => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// End the Fiverr session. The cookies are cleared as well, in case the logout page changed.
+pub async fn logout(session: &Session) -> Result<()> {
+    session.goto(&logout_url()).await?;
+    session.delete_all_cookies().await?;
+    Ok(())
+}
+
+/// Where logging in got to once the credentials were accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoginOutcome {
+    LoggedIn,
+    /// Fiverr wants a one-time code, to be entered with [`submit_otp`].
+    OtpRequired,
+    /// Fiverr shows a CAPTCHA on the page with this URL. Once it is solved in the browser window,
+    /// [`wait_for_captcha`] picks up where logging in got to.
+    Captcha(String),
+}
+
+/// Log in with the login form, calling `on_step` as it goes. Fails when the form doesn't show up
+/// within `timeout`, or when Fiverr shows neither a logged in page, a one-time code field nor a
+/// CAPTCHA within `timeout` of submitting.
+pub async fn login(
+    session: &Session,
+    username: &str,
+    password: &str,
+    timeout: Duration,
+    on_step: impl Fn(&str),
+) -> Result<LoginOutcome> {
+    let selectors = session.selectors();
+    on_step("Opening the login page...");
+    session.navigate(&login_url()).await?;
+    let email = match selectors.login_email.wait_for(session, timeout).await {
+        Ok(email) => email,
+        Err(e) if e.is_timeout() => bail!("The login form did not show up: {e}"),
+        Err(e) => return Err(e.into()),
+    };
+    on_step("Entering credentials...");
+    email.send_keys(username).await?;
+    selectors
+        .login_password
+        .find(session)
+        .await?
+        .send_keys(password)
+        .await?;
+    on_step("Submitting...");
+    selectors.login_submit.find(session).await?.click().await?;
+    await_login(session, timeout, true).await
+}
+
+/// Wait up to `timeout` for the CAPTCHA [`login`] stopped at to be solved by hand.
+pub async fn wait_for_captcha(session: &Session, timeout: Duration) -> Result<LoginOutcome> {
+    await_login(session, timeout, false).await
+}
+
+/// Poll for the page Fiverr shows after the credentials were submitted.
+async fn await_login(
+    session: &Session,
+    timeout: Duration,
+    detect_captcha: bool,
+) -> Result<LoginOutcome> {
+    let selectors = session.selectors();
+    let deadline = Instant::now() + timeout;
+    loop {
+        if found(selectors.logged_in.find(session).await)? {
+            return Ok(LoginOutcome::LoggedIn);
+        }
+        if found(selectors.login_otp.find(session).await)? {
+            return Ok(LoginOutcome::OtpRequired);
+        }
+        if detect_captcha && found(selectors.login_captcha.find(session).await)? {
+            let url = session.current_url().await?;
+            return Ok(LoginOutcome::Captcha(url.to_string()));
+        }
+        if Instant::now() >= deadline {
+            bail!(
+                "Fiverr did not log in within {}s, finish logging in in the browser window",
+                timeout.as_secs()
+            );
+        }
+        sleep(Duration::from_millis(250)).await;
+    }
+}
+
+/// Enter the one-time code Fiverr asked for after [`login`] and wait up to `timeout` for the
+/// logged in page.
+pub async fn submit_otp(session: &Session, code: &str, timeout: Duration) -> Result<()> {
+    let selectors = session.selectors();
+    let field = match selectors.login_otp.find(session).await {
+        Ok(field) => field,
+        Err(e) if e.is_miss() => bail!("Fiverr is no longer asking for a one-time code"),
+        Err(e) => return Err(e.into()),
+    };
+    field
+        .send_keys(&format!("{code}{}", char::from(Key::Enter)))
+        .await?;
+    match selectors.logged_in.wait_for(session, timeout).await {
+        Ok(_) => Ok(()),
+        Err(e) if e.is_timeout() => bail!(
+            "Fiverr did not accept the one-time code within {}s",
+            timeout.as_secs()
+        ),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Whether a lookup found its element, with misses being `false` rather than errors.
+fn found(result: Result<Element, CommandError>) -> Result<bool> {
+    match result {
+        Ok(_) => Ok(true),
+        Err(e) if e.is_miss() => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Whether the seller shows as online rather than away, read from the availability switch on
+/// the seller dashboard.
+pub async fn fetch_online(session: &Session) -> Result<bool> {
+    session.navigate(&seller_dashboard_url()).await?;
+    read_online(session).await
+}
+
+/// Flip the seller between online and away, returning whether they are online now.
+pub async fn toggle_online(session: &Session) -> Result<bool> {
+    session.navigate(&seller_dashboard_url()).await?;
+    availability_toggle(session).await?.click().await?;
+    read_online(session).await
+}
+
+async fn read_online(session: &Session) -> Result<bool> {
+    Ok(availability_toggle(session).await?.is_selected().await?)
+}
+
+async fn availability_toggle(session: &Session) -> Result<Element> {
+    match session.selectors().availability_toggle.find(session).await {
+        Ok(toggle) => Ok(toggle),
+        Err(e) if e.is_miss() => bail!("The online status switch is not on the seller dashboard"),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Formats of the dates Fiverr shows without an offset. These are in UTC.
+const DATE_FORMATS: [&str; 3] = [
+    "%b %d, %Y, %I:%M %p",
+    "%b %d, %Y %I:%M %p",
+    "%Y-%m-%d %H:%M:%S",
+];
+
+fn parse_date(raw: &str) -> Option<DateTime<Utc>> {
+    let raw = raw.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(raw) {
+        return Some(date.with_timezone(&Utc));
+    }
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(raw, format).ok())
+        .map(|date| date.and_utc())
+}
+
+/// Show a date scraped from Fiverr in `tz`, followed by the zone's abbreviation. Dates that
+/// can't be parsed are shown as scraped.
+pub fn format_date(raw: &str, tz: Tz) -> String {
+    match parse_date(raw) {
+        Some(date) => date
+            .with_timezone(&tz)
+            .format("%b %-d, %Y %H:%M %Z")
+            .to_string(),
+        None => raw.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    New,
+    /// Waiting for the seller to accept or decline it.
+    PendingAcceptance,
+    /// The buyer hasn't submitted the requirements yet.
+    RequirementsPending,
+    InProgress,
+    Late,
+    Delivered,
+    Completed,
+    Cancelled,
+    /// A label stash doesn't know about, as shown on the page.
+    Other(String),
+}
+
+impl OrderStatus {
+    /// How soon an order with this status needs the seller, lower first.
+    pub fn urgency(&self) -> u8 {
+        match self {
+            OrderStatus::Late => 0,
+            OrderStatus::New | OrderStatus::PendingAcceptance => 1,
+            OrderStatus::InProgress => 2,
+            OrderStatus::RequirementsPending => 3,
+            OrderStatus::Other(_) => 4,
+            OrderStatus::Delivered => 5,
+            OrderStatus::Completed | OrderStatus::Cancelled => 6,
+        }
+    }
+
+    /// Parse the status label shown on Fiverr's manage orders page.
+    pub fn from_label(label: &str) -> Self {
+        match label.trim().to_ascii_lowercase().as_str() {
+            "new" => OrderStatus::New,
+            "pending acceptance" | "awaiting acceptance" => OrderStatus::PendingAcceptance,
+            "incomplete" | "missing details" | "requirements pending" => {
+                OrderStatus::RequirementsPending
+            }
+            "in progress" | "active" => OrderStatus::InProgress,
+            "late" => OrderStatus::Late,
+            "delivered" => OrderStatus::Delivered,
+            "completed" => OrderStatus::Completed,
+            "cancelled" | "canceled" => OrderStatus::Cancelled,
+            _ => OrderStatus::Other(label.trim().to_string()),
+        }
+    }
+
+    /// Whether the seller has to do something about the order.
+    pub fn needs_action(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::New
+                | OrderStatus::PendingAcceptance
+                | OrderStatus::RequirementsPending
+                | OrderStatus::Late
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Order {
+    pub id: String,
+    pub buyer: String,
+    pub gig: String,
+    /// As shown on the page.
+    pub due_date: String,
+    pub status: OrderStatus,
+    pub amount: String,
+}
+
+impl Order {
+    pub fn due_date_in(&self, tz: Tz) -> String {
+        format_date(&self.due_date, tz)
+    }
+
+    /// The due date, if it could be parsed.
+    pub fn due(&self) -> Option<DateTime<Utc>> {
+        parse_date(&self.due_date)
+    }
+}
+
+impl Identified for Order {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// A thread in the inbox.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Conversation {
+    pub username: String,
+    pub last_message: String,
+    pub unread: bool,
+}
+
+impl Identified for Conversation {
+    fn id(&self) -> &str {
+        &self.username
+    }
+}
+
+/// One message of a conversation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadMessage {
+    pub sender: String,
+    /// As shown on the page, see [`format_date`].
+    pub time: String,
+    pub body: String,
+    /// File names of the attached files.
+    pub attachments: Vec<String>,
+    /// Whether the seller sent it, rather than the buyer.
+    pub from_me: bool,
+}
+
+/// The messages of the conversation with `username`, oldest first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Thread {
+    pub username: String,
+    pub messages: Vec<ThreadMessage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GigStatus {
+    Active,
+    Paused,
+    /// E.g. pending approval or denied, as shown on the page.
+    Other(String),
+}
+
+impl GigStatus {
+    /// Parse the status label shown on Fiverr's manage gigs page.
+    pub fn from_label(label: &str) -> Self {
+        match label.trim().to_ascii_lowercase().as_str() {
+            "active" => GigStatus::Active,
+            "paused" => GigStatus::Paused,
+            _ => GigStatus::Other(label.trim().to_string()),
+        }
+    }
+}
+
+impl fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderStatus::New => write!(f, "New"),
+            OrderStatus::PendingAcceptance => write!(f, "Pending acceptance"),
+            OrderStatus::RequirementsPending => write!(f, "Requirements pending"),
+            OrderStatus::InProgress => write!(f, "In progress"),
+            OrderStatus::Late => write!(f, "Late"),
+            OrderStatus::Delivered => write!(f, "Delivered"),
+            OrderStatus::Completed => write!(f, "Completed"),
+            OrderStatus::Cancelled => write!(f, "Cancelled"),
+            OrderStatus::Other(label) => write!(f, "{label}"),
+        }
+    }
+}
+
+impl fmt::Display for GigStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GigStatus::Active => write!(f, "Active"),
+            GigStatus::Paused => write!(f, "Paused"),
+            GigStatus::Other(label) => write!(f, "{label}"),
+        }
+    }
+}
+
+/// A gig of the seller, as listed on the manage gigs page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Gig {
+    /// The gig's slug, e.g. `do-logo-design`.
+    pub id: String,
+    pub title: String,
+    pub status: GigStatus,
+    /// As shown on the page, e.g. `$25`.
+    pub price: String,
+    pub orders_in_queue: u32,
+    /// Over the period the manage gigs page shows, 0 if the page doesn't show it.
+    pub impressions: u32,
+    pub clicks: u32,
+    pub edit_url: Option<String>,
+    pub analytics_url: Option<String>,
+}
+
+impl Identified for Gig {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Requirement {
+    pub question: String,
+    pub answer: String,
+}
+
+/// What the buyer filled in when they started an order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderRequirements {
+    pub order_id: String,
+    pub buyer: String,
+    /// `None` until the buyer has submitted the requirements form.
+    pub answers: Option<Vec<Requirement>>,
+}
+
+pub async fn fetch_requirements(session: &Session, order_id: &str) -> Result<OrderRequirements> {
+    let selectors = session.selectors();
+    session
+        .navigate(&format!("{}/requirements", order_url(order_id)))
+        .await?;
+    session
+        .wait_for_lazy_load(View::Requirements, &selectors.requirements_item)
+        .await?;
+    let buyer = match selectors.requirements_buyer.find(session).await {
+        Ok(buyer) => buyer.text().await?,
+        Err(e) if e.is_miss() => bail!("Order {order_id} not found"),
+        Err(e) => return Err(e.into()),
+    };
+
+    let answers = session
+        .retry_on_stale(|session| async move {
+            let mut answers = Vec::new();
+            for item in selectors.requirements_item.find_all(session).await? {
+                let question = selectors.requirements_question.find(&item).await?;
+                let answer = selectors.requirements_answer.find(&item).await?;
+                answers.push(Requirement {
+                    question: question.text().await?.trim().to_string(),
+                    answer: answer.text().await?.trim().to_string(),
+                });
+            }
+            Ok(answers)
+        })
+        .await?;
+
+    Ok(OrderRequirements {
+        order_id: order_id.to_string(),
+        buyer: buyer.trim().to_string(),
+        answers: (!answers.is_empty()).then_some(answers),
+    })
+}
+
+/// Read the orders of the seller `username` from the manage orders page.
+pub async fn fetch_orders(session: &Session, username: &str) -> Result<Vec<Order>> {
+    let selectors = session.selectors();
+    session.navigate(&manage_orders_url(username)).await?;
+    session
+        .wait_for_lazy_load(View::Orders, &selectors.order_row)
+        .await?;
+    let orders = session
+        .retry_on_stale(|session| async move {
+            let mut orders = Vec::new();
+            for row in selectors.order_row.find_all(session).await? {
+                let href = optional_href(&row, &selectors.order_link).await?;
+                let Some(id) = href.as_deref().and_then(last_segment) else {
+                    continue;
+                };
+                let status = optional_text(&row, &selectors.order_status).await?;
+                orders.push(Order {
+                    id,
+                    buyer: optional_text(&row, &selectors.order_buyer).await?,
+                    gig: optional_text(&row, &selectors.order_gig).await?,
+                    due_date: optional_text(&row, &selectors.order_due_date).await?,
+                    status: OrderStatus::from_label(&status),
+                    amount: optional_text(&row, &selectors.order_amount).await?,
+                });
+            }
+            Ok(orders)
+        })
+        .await?;
+    Ok(orders)
+}
+
+/// Read the conversations from the inbox, most recent first.
+pub async fn fetch_conversations(session: &Session) -> Result<Vec<Conversation>> {
+    let selectors = session.selectors();
+    session.navigate(&inbox_url()).await?;
+    session
+        .wait_for_lazy_load(View::Messages, &selectors.inbox_row)
+        .await?;
+    let conversations = session
+        .retry_on_stale(|session| async move {
+            let mut conversations = Vec::new();
+            for row in selectors.inbox_row.find_all(session).await? {
+                let username = optional_text(&row, &selectors.inbox_username).await?;
+                if username.is_empty() {
+                    continue;
+                }
+                conversations.push(Conversation {
+                    username,
+                    last_message: optional_text(&row, &selectors.inbox_last_message).await?,
+                    unread: found(selectors.inbox_unread.find(&row).await)?,
+                });
+            }
+            Ok(conversations)
+        })
+        .await?;
+    Ok(conversations)
+}
+
+/// Open the conversation with `username` and read its messages.
+pub async fn fetch_thread(session: &Session, username: &str) -> Result<Thread> {
+    let selectors = session.selectors();
+    session.navigate(&conversation_url(username)).await?;
+    session
+        .wait_for_lazy_load(View::Thread, &selectors.thread_message)
+        .await?;
+    let messages = session
+        .retry_on_stale(|session| async move {
+            let mut own = Vec::new();
+            for element in selectors.thread_own_message.find_all(session).await? {
+                own.push(element.element_id());
+            }
+            let mut messages = Vec::new();
+            for item in selectors.thread_message.find_all(session).await? {
+                // Consecutive messages from the same sender leave the name out.
+                let sender = optional_text(&item, &selectors.thread_sender).await?;
+                let time = match selectors.thread_time.find(&item).await {
+                    Ok(time) => match time.attr("datetime").await? {
+                        Some(datetime) => datetime,
+                        None => time.text().await?,
+                    },
+                    Err(e) if e.is_miss() => String::new(),
+                    Err(e) => return Err(e.into()),
+                };
+                let body = optional_text(&item, &selectors.thread_body).await?;
+                let mut attachments = Vec::new();
+                for attachment in selectors.thread_attachment.find_all(&item).await? {
+                    attachments.push(attachment.text().await?.trim().to_string());
+                }
+                messages.push(ThreadMessage {
+                    sender,
+                    time: time.trim().to_string(),
+                    body,
+                    attachments,
+                    from_me: own.contains(&item.element_id()),
+                });
+            }
+            Ok(messages)
+        })
+        .await?;
+    Ok(Thread {
+        username: username.to_string(),
+        messages: fill_in_senders(messages),
+    })
+}
+
+/// Read the gigs of the seller `username` from the manage gigs page.
+pub async fn fetch_gigs(session: &Session, username: &str) -> Result<Vec<Gig>> {
+    let selectors = session.selectors();
+    session.navigate(&manage_gigs_url(username)).await?;
+    session
+        .wait_for_lazy_load(View::Gigs, &selectors.gig_row)
+        .await?;
+    let gigs = session
+        .retry_on_stale(|session| async move {
+            let mut gigs = Vec::new();
+            let rows = selectors.gig_row.find_all(session).await?;
+            let total = rows.len();
+            for (i, row) in rows.into_iter().enumerate() {
+                session.progress("Reading gigs", i, Some(total));
+                let title = selectors.gig_title.find(&row).await?;
+                let Some(id) = title.attr("href").await?.as_deref().and_then(last_segment) else {
+                    continue;
+                };
+                let status = optional_text(&row, &selectors.gig_status).await?;
+                let queue = optional_text(&row, &selectors.gig_orders_in_queue).await?;
+                let impressions = optional_text(&row, &selectors.gig_impressions).await?;
+                let clicks = optional_text(&row, &selectors.gig_clicks).await?;
+                gigs.push(Gig {
+                    id,
+                    title: title.text().await?.trim().to_string(),
+                    status: GigStatus::from_label(&status),
+                    price: optional_text(&row, &selectors.gig_price).await?,
+                    orders_in_queue: leading_number(&queue),
+                    impressions: leading_number(&impressions),
+                    clicks: leading_number(&clicks),
+                    edit_url: optional_href(&row, &selectors.gig_edit_link).await?,
+                    analytics_url: optional_href(&row, &selectors.gig_analytics_link).await?,
+                });
+            }
+            session.progress("Reading gigs", total, Some(total));
+            Ok(gigs)
+        })
+        .await?;
+    Ok(gigs)
+}
+
+/// Read the gig `gig_id` of the seller `username`.
+pub async fn fetch_gig(session: &Session, username: &str, gig_id: &str) -> Result<Gig> {
+    fetch_gigs(session, username)
+        .await?
+        .into_iter()
+        .find(|gig| gig.id == gig_id)
+        .ok_or_else(|| eyre!("Gig {gig_id} is not on the manage gigs page"))
+}
+
+/// Pause or activate the gig `gig_id` of the seller `username`, then read the gigs again.
+pub async fn toggle_gig_status(
+    session: &Session,
+    username: &str,
+    gig_id: &str,
+) -> Result<Vec<Gig>> {
+    let selectors = session.selectors();
+    session.navigate(&manage_gigs_url(username)).await?;
+    let mut toggled = false;
+    for row in selectors.gig_row.find_all(session).await? {
+        let title = selectors.gig_title.find(&row).await?;
+        if title.attr("href").await?.as_deref().and_then(last_segment) == Some(gig_id.to_string()) {
+            selectors
+                .gig_status_toggle
+                .find(&row)
+                .await?
+                .click()
+                .await?;
+            toggled = true;
+            break;
+        }
+    }
+    if !toggled {
+        return Err(eyre!("Gig {gig_id} is not on the manage gigs page"));
+    }
+    fetch_gigs(session, username).await
+}
+
+/// The last segment of a URL's path, e.g. the slug of a gig or the number of an order.
+fn last_segment(href: &str) -> Option<String> {
+    let path = href.split(['?', '#']).next()?;
+    let id = path.trim_end_matches('/').rsplit('/').next()?;
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+/// The number a text like `3 orders` or `1,204 impressions` starts with, or 0.
+fn leading_number(text: &str) -> u32 {
+    let digits: String = text
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == ',')
+        .filter(|c| *c != ',')
+        .collect();
+    digits.parse().unwrap_or(0)
+}
+
+/// The `href` of the element matching `selector` inside `element`, if there is one.
+async fn optional_href(element: &Element, selector: &Selector) -> Result<Option<String>> {
+    match selector.find(element).await {
+        Ok(link) => Ok(link.attr("href").await?),
+        Err(e) if e.is_miss() => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// The trimmed text of the element matching `selector` inside `element`, or an empty string.
+async fn optional_text(element: &Element, selector: &Selector) -> Result<String> {
+    match selector.find(element).await {
+        Ok(found) => Ok(found.text().await?.trim().to_string()),
+        Err(e) if e.is_miss() => Ok(String::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Give messages without a sender the one of the message before.
+fn fill_in_senders(mut messages: Vec<ThreadMessage>) -> Vec<ThreadMessage> {
+    let mut last = String::new();
+    for message in &mut messages {
+        if message.sender.is_empty() {
+            message.sender = last.clone();
+        } else {
+            last = message.sender.clone();
+        }
+    }
+    messages
+}
+
+/// Send `text` to the conversation with `username`.
+pub async fn send_message(session: &Session, username: &str, text: &str) -> Result<()> {
+    let selectors = session.selectors();
+    session.navigate(&conversation_url(username)).await?;
+    selectors
+        .message_input
+        .find(session)
+        .await?
+        .send_keys(text)
+        .await?;
+    selectors.message_send.find(session).await?.click().await?;
+    Ok(())
+}
+
+/// Accept the order `order_id`, which must be waiting for acceptance.
+pub async fn accept_order(session: &Session, order_id: &str) -> Result<()> {
+    session.navigate(&order_url(order_id)).await?;
+    match session.selectors().order_accept.find(session).await {
+        Ok(button) => button.click().await?,
+        Err(e) if e.is_miss() => bail!("Order {order_id} has no accept button"),
+        Err(e) => return Err(e.into()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_format_date() {
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        assert_eq!(
+            format_date("Oct 15, 2026, 3:30 PM", berlin),
+            "Oct 15, 2026 17:30 CEST"
+        );
+        assert_eq!(
+            format_date("2026-01-02T23:15:00-05:00", berlin),
+            "Jan 3, 2026 05:15 CET"
+        );
+        assert_eq!(format_date("in 2 days", berlin), "in 2 days");
+    }
+
+    #[test]
+    fn test_load_invalid_session() {
+        let dir = std::env::temp_dir().join(format!("stash-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("session.json");
+        assert_eq!(load_session(&path), StoredSession::Missing);
+
+        std::fs::write(
+            &path,
+            "{\"url\": \"https://www.fiverr.com\", \"cookies\": [",
+        )
+        .unwrap();
+        assert!(matches!(load_session(&path), StoredSession::Invalid(_)));
+
+        let redacted = r#"{"url": "https://www.fiverr.com", "cookies": [{"name": "session",
+            "value": "<redacted>", "domain": null, "path": null, "secure": true,
+            "http_only": true}]}"#;
+        std::fs::write(&path, redacted).unwrap();
+        assert_eq!(
+            load_session(&path),
+            StoredSession::Invalid("its cookies are redacted".to_string())
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_last_segment() {
+        assert_eq!(
+            last_segment("https://www.fiverr.com/seller/do-logo-design?context=manage"),
+            Some("do-logo-design".to_string())
+        );
+        assert_eq!(
+            last_segment("/seller/do-logo-design/"),
+            Some("do-logo-design".to_string())
+        );
+        assert_eq!(last_segment(""), None);
+        assert!(is_order_id("FO1234ABCD56"));
+        assert!(is_order_id("fo12"));
+        assert!(!is_order_id("FO"));
+        assert!(!is_order_id("alice"));
+        assert!(!is_order_id("FO-12"));
+        assert_eq!(leading_number("3 orders"), 3);
+        assert_eq!(leading_number("-"), 0);
+        assert_eq!(leading_number("1,204 impressions"), 1204);
+    }
+}
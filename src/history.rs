@@ -0,0 +1,184 @@
+use std::{
+    collections::VecDeque,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+use crate::{fiverr::Gig, utils};
+
+/// The numbers of one gig at one scrape. Every scrape of the gigs appends one per gig to
+/// `gig_history.ndjson` in the data directory, so performance can be followed across runs. The
+/// file keeps the latest `MemoryLimits::gig_snapshots` to twice as many, see [`record`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GigSnapshot {
+    /// Seconds since the Unix epoch.
+    pub timestamp: i64,
+    pub gig_id: String,
+    pub impressions: u32,
+    pub clicks: u32,
+    pub orders_in_queue: u32,
+}
+
+impl GigSnapshot {
+    pub fn new(gig: &Gig, timestamp: i64) -> Self {
+        Self {
+            timestamp,
+            gig_id: gig.id.clone(),
+            impressions: gig.impressions,
+            clicks: gig.clicks,
+            orders_in_queue: gig.orders_in_queue,
+        }
+    }
+}
+
+pub fn path(data_dir: &Path) -> PathBuf {
+    data_dir.join("gig_history.ndjson")
+}
+
+/// Add a snapshot of each of `gigs`, taken at `timestamp`, and return the latest `limit`
+/// snapshots as [`load`] does. New snapshots are appended until the file holds twice `limit`;
+/// then it is rewritten with just the latest `limit`, so it doesn't grow without bound.
+pub fn record(
+    data_dir: &Path,
+    gigs: &[Gig],
+    timestamp: i64,
+    limit: usize,
+) -> Result<Vec<GigSnapshot>> {
+    let (mut snapshots, stored) = read(data_dir, limit)?;
+    let new: Vec<GigSnapshot> = gigs
+        .iter()
+        .map(|gig| GigSnapshot::new(gig, timestamp))
+        .collect();
+    let compact = stored + new.len() > 2 * limit;
+    if !compact {
+        write(data_dir, &new, true)?;
+    }
+    for snapshot in new {
+        utils::push_bounded(&mut snapshots, snapshot, limit);
+    }
+    if compact {
+        write(data_dir, snapshots.make_contiguous(), false)?;
+    }
+    Ok(snapshots.into())
+}
+
+/// Append `snapshots` to the file, or replace it with them unless `append`. A replaced file is
+/// written next to it first, so a crash can't leave it cut short.
+fn write(data_dir: &Path, snapshots: &[GigSnapshot], append: bool) -> Result<()> {
+    std::fs::create_dir_all(data_dir)?;
+    let path = path(data_dir);
+    let mut lines = String::new();
+    for snapshot in snapshots {
+        lines.push_str(&serde_json::to_string(snapshot)?);
+        lines.push('\n');
+    }
+    let result = if append {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+    } else {
+        let partial = path.with_extension("ndjson.partial");
+        std::fs::write(&partial, lines).and_then(|()| std::fs::rename(&partial, &path))
+    };
+    result.wrap_err_with(|| format!("Failed to write {}", path.display()))
+}
+
+/// Read the latest `limit` snapshots, oldest first. Lines that don't parse, e.g. one cut off by a
+/// crash, are skipped.
+pub fn load(data_dir: &Path, limit: usize) -> Result<Vec<GigSnapshot>> {
+    Ok(read(data_dir, limit)?.0.into())
+}
+
+/// The latest `limit` snapshots, and how many the file holds in all.
+fn read(data_dir: &Path, limit: usize) -> Result<(VecDeque<GigSnapshot>, usize)> {
+    let path = path(data_dir);
+    let history = match std::fs::read_to_string(&path) {
+        Ok(history) => history,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((VecDeque::new(), 0)),
+        Err(e) => return Err(e).wrap_err_with(|| format!("Failed to read {}", path.display())),
+    };
+    let mut snapshots = VecDeque::new();
+    let mut stored = 0;
+    for line in history.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(snapshot) => {
+                stored += 1;
+                utils::push_bounded(&mut snapshots, snapshot, limit);
+            }
+            Err(e) => log::warn!("Skipping a line of {}: {e}", path.display()),
+        }
+    }
+    Ok((snapshots, stored))
+}
+
+/// The impressions of the gig `gig_id` in every snapshot of it, oldest first.
+pub fn impressions(history: &[GigSnapshot], gig_id: &str) -> Vec<u64> {
+    history
+        .iter()
+        .filter(|snapshot| snapshot.gig_id == gig_id)
+        .map(|snapshot| u64::from(snapshot.impressions))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::fiverr::GigStatus;
+
+    fn gig(id: &str, impressions: u32) -> Gig {
+        Gig {
+            id: id.to_string(),
+            title: id.to_string(),
+            status: GigStatus::Active,
+            price: "$25".to_string(),
+            orders_in_queue: 0,
+            impressions,
+            clicks: 0,
+            edit_url: None,
+            analytics_url: None,
+        }
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let dir = std::env::temp_dir().join(format!("stash-history-test-{}", std::process::id()));
+        assert_eq!(load(&dir, 10).unwrap(), []);
+
+        record(&dir, &[gig("logo", 10), gig("banner", 3)], 1, 10).unwrap();
+        record(&dir, &[gig("logo", 25)], 2, 10).unwrap();
+        let mut file = OpenOptions::new().append(true).open(path(&dir)).unwrap();
+        file.write_all(b"{\"timestamp\": 3, \"gig_").unwrap();
+
+        let history = load(&dir, 10).unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(impressions(&history, "logo"), [10, 25]);
+        assert_eq!(impressions(&history, "banner"), [3]);
+        assert_eq!(impressions(&load(&dir, 2).unwrap(), "logo"), [25]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_record_compacts_the_file() {
+        let dir = std::env::temp_dir().join(format!("stash-history-cap-{}", std::process::id()));
+        let lines = || std::fs::read_to_string(path(&dir)).unwrap().lines().count();
+        for timestamp in 0..4 {
+            let history = record(&dir, &[gig("logo", timestamp as u32)], timestamp, 2).unwrap();
+            assert_eq!(history.last().unwrap().timestamp, timestamp);
+        }
+        assert_eq!(lines(), 4);
+        // The fifth snapshot would make it more than twice the limit.
+        let history = record(&dir, &[gig("logo", 4)], 4, 2).unwrap();
+        assert_eq!(impressions(&history, "logo"), [3, 4]);
+        assert_eq!(lines(), 2);
+        assert_eq!(load(&dir, 10).unwrap(), history);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,20 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+#![allow(unused_variables)]
+
+pub mod action;
+pub mod app;
+pub mod browser;
+pub mod cli;
+pub mod components;
+pub mod config;
+pub mod driver;
+pub mod export;
+pub mod fiverr;
+pub mod history;
+pub mod mode;
+pub mod state;
+pub mod tasks;
+pub mod templates;
+pub mod tui;
+pub mod utils;
@@ -2,34 +2,40 @@
 #![allow(unused_imports)]
 #![allow(unused_variables)]
 
-pub mod action;
-pub mod app;
-pub mod cli;
-pub mod components;
-pub mod config;
-pub mod mode;
-pub mod tui;
-pub mod utils;
-
 use clap::Parser;
-use cli::Cli;
 use color_eyre::eyre::Result;
-
-use crate::{
+use stash::{
     app::App,
-    utils::{initialize_logging, initialize_panic_handler, version},
+    cli::Cli,
+    config::Config,
+    utils::{flush_session_log, initialize_logging, initialize_panic_handler, version},
 };
 
 async fn tokio_main() -> Result<()> {
-    initialize_logging()?;
+    let args = Cli::parse();
+    // Loaded first, it says where to log.
+    let mut config = Config::new(args.config.clone())?;
+    initialize_logging(
+        &config.config.log_files,
+        config.config.log_level_filter()?,
+        config.config.memory_limits.session_log_lines,
+    )?;
+    config.log_load_messages();
 
     initialize_panic_handler()?;
 
-    let args = Cli::parse();
-    let mut app = App::new(args.tick_rate, args.frame_rate)?;
-    app.run().await?;
-
-    Ok(())
+    let mut app = App::new(
+        args.tick_rate,
+        args.frame_rate,
+        config,
+        args.config,
+        args.profile,
+    )?;
+    app.no_color = args.no_color;
+    app.headless = args.headless;
+    let result = app.run().await;
+    flush_session_log();
+    result
 }
 
 #[tokio::main]
@@ -1,7 +1,49 @@
 use serde::{Deserialize, Serialize};
 
+use crate::config::View;
+
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Mode {
     #[default]
     Home,
+    /// The seller's orders.
+    Orders,
+    /// The seller's conversations with buyers.
+    Messages,
+    /// The seller's gigs, see [`crate::components::gigs::GigsComponent`].
+    Gigs,
+    /// The loaded settings, see [`crate::components::settings::SettingsComponent`].
+    Settings,
+}
+
+impl Mode {
+    /// The modes with a screen, in the order of the tab line.
+    pub const SCREENS: [Mode; 5] = [
+        Mode::Home,
+        Mode::Orders,
+        Mode::Messages,
+        Mode::Gigs,
+        Mode::Settings,
+    ];
+
+    /// The scraped view the mode shows, if any.
+    pub fn view(self) -> Option<View> {
+        match self {
+            Mode::Home | Mode::Settings => None,
+            Mode::Orders => Some(View::Orders),
+            Mode::Messages => Some(View::Messages),
+            Mode::Gigs => Some(View::Gigs),
+        }
+    }
+
+    /// Just this mode, as a slice, e.g. for [`crate::components::Component::modes`].
+    pub fn only(self) -> &'static [Mode] {
+        match self {
+            Mode::Home => &[Mode::Home],
+            Mode::Orders => &[Mode::Orders],
+            Mode::Messages => &[Mode::Messages],
+            Mode::Gigs => &[Mode::Gigs],
+            Mode::Settings => &[Mode::Settings],
+        }
+    }
 }
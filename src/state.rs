@@ -0,0 +1,75 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+/// What stash remembers between runs, kept as JSON in the data directory.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    /// Ids of the pinned items of each list, by list name (e.g. `"orders"`).
+    pub pinned: HashMap<String, HashSet<String>>,
+}
+
+impl State {
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join("state.json")
+    }
+
+    /// Read the state file, or start from an empty state if there is none yet.
+    pub fn load(data_dir: &Path) -> Result<Self> {
+        let path = Self::path(data_dir);
+        match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .wrap_err_with(|| format!("Failed to parse {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).wrap_err_with(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(data_dir)?;
+        std::fs::write(Self::path(data_dir), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Load the state, change it with `f` and write it back. Every component only touches its own
+    /// part, so reloading first keeps the others' changes.
+    pub fn update(data_dir: &Path, f: impl FnOnce(&mut State)) -> Result<()> {
+        let mut state = Self::load(data_dir)?;
+        f(&mut state);
+        state.save(data_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_update_keeps_other_lists() {
+        let dir = std::env::temp_dir().join(format!("stash-state-test-{}", std::process::id()));
+        assert_eq!(State::load(&dir).unwrap(), State::default());
+
+        State::update(&dir, |state| {
+            state
+                .pinned
+                .insert("orders".to_string(), HashSet::from(["1".to_string()]));
+        })
+        .unwrap();
+        State::update(&dir, |state| {
+            state
+                .pinned
+                .insert("messages".to_string(), HashSet::from(["bob".to_string()]));
+        })
+        .unwrap();
+        let state = State::load(&dir).unwrap();
+        assert_eq!(state.pinned.len(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,72 @@
+use std::future::Future;
+
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// `Tasks` keeps track of the background work spawned by the app (connecting, scraping, ...) so
+/// that all of it can be cancelled together before the WebDriver session is torn down.
+#[derive(Default)]
+pub struct Tasks {
+    cancellation_token: CancellationToken,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl Tasks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spawn `future` onto the runtime. The future is dropped at its next `.await` once the
+    /// registry is shut down.
+    pub fn spawn<F>(&mut self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let cancellation_token = self.cancellation_token.clone();
+        self.handles.retain(|handle| !handle.is_finished());
+        self.handles.push(tokio::spawn(async move {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => {}
+                _ = future => {}
+            }
+        }));
+    }
+
+    /// Cancel every outstanding task and wait until all of them have stopped.
+    pub async fn shutdown(&mut self) {
+        self.cancellation_token.cancel();
+        for handle in self.handles.drain(..) {
+            if let Err(e) = handle.await {
+                log::error!("Background task failed: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use tokio::time::{timeout, Duration};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown_cancels_pending_tasks() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let mut tasks = Tasks::new();
+        let flag = finished.clone();
+        tasks.spawn(async move {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+            flag.store(true, Ordering::SeqCst);
+        });
+
+        timeout(Duration::from_secs(1), tasks.shutdown())
+            .await
+            .expect("shutdown should not wait for the task to finish");
+        assert!(!finished.load(Ordering::SeqCst));
+    }
+}
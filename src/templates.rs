@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use color_eyre::eyre::{bail, Result};
+
+use crate::fiverr::Order;
+
+/// Values of the variables a template can use, by name.
+pub type Variables = HashMap<&'static str, String>;
+
+/// Substitute the `{name}` variables in `template`. `{{` and `}}` stand for literal braces.
+/// Unknown variables are an error rather than left in, so a typo never reaches a buyer.
+pub fn render(template: &str, variables: &Variables) -> Result<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.as_str().starts_with('{') => {
+                chars.next();
+                rendered.push('{');
+            }
+            '}' if chars.as_str().starts_with('}') => {
+                chars.next();
+                rendered.push('}');
+            }
+            '{' => {
+                let rest = chars.as_str();
+                let Some(end) = rest.find('}') else {
+                    bail!("Unclosed {{ in template");
+                };
+                let name = rest[..end].trim();
+                let Some(value) = variables.get(name) else {
+                    bail!("Unknown template variable {{{name}}}");
+                };
+                rendered.push_str(value);
+                chars = rest[end + 1..].chars();
+            }
+            '}' => bail!("Unmatched }} in template, write }}}} for a literal one"),
+            c => rendered.push(c),
+        }
+    }
+    Ok(rendered)
+}
+
+/// The template that [`render`] turns into `text` whatever the variables, for text that is
+/// rendered again, e.g. a message rendered for an order that is then sent to the buyer.
+pub fn escape(text: &str) -> String {
+    text.replace('{', "{{").replace('}', "}}")
+}
+
+/// The variables for a message to `username` outside of an order.
+pub fn conversation_variables(username: &str) -> Variables {
+    HashMap::from([("buyer_name", username.to_string())])
+}
+
+/// The variables for a message about `order`.
+pub fn order_variables(order: &Order) -> Variables {
+    HashMap::from([
+        ("buyer_name", order.buyer.clone()),
+        ("order_id", order.id.clone()),
+        ("gig_title", order.gig.clone()),
+        ("due_date", order.due_date.clone()),
+        ("amount", order.amount.clone()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let variables = HashMap::from([("buyer_name", "alice".to_string())]);
+        assert_eq!(
+            render("Hi {buyer_name}, {{thanks}}!", &variables).unwrap(),
+            "Hi alice, {thanks}!"
+        );
+        assert_eq!(render("Hi { buyer_name }", &variables).unwrap(), "Hi alice");
+        let err = render("Order {order_id}", &variables).unwrap_err();
+        assert!(err.to_string().contains("{order_id}"));
+        assert!(render("Hi {buyer_name", &variables).is_err());
+        assert!(render("Hi }", &variables).is_err());
+    }
+
+    #[test]
+    fn test_escape() {
+        let text = "Hi {buyer_name}, {{thanks}}!";
+        assert_eq!(render(&escape(text), &Variables::new()).unwrap(), text);
+    }
+}
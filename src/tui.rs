@@ -1,4 +1,6 @@
 use std::{
+  collections::VecDeque,
+  future::Future,
   ops::{Deref, DerefMut},
   time::Duration,
 };
@@ -208,8 +210,11 @@ impl Tui {
     Ok(())
   }
 
+  /// Re-enter the terminal after `suspend`. The screen is cleared and forgotten, so the next draw
+  /// repaints every cell instead of only those that changed since before the suspend.
   pub fn resume(&mut self) -> Result<()> {
     self.enter()?;
+    self.terminal.clear()?;
     Ok(())
   }
 
@@ -218,6 +223,44 @@ impl Tui {
   }
 }
 
+/// Put the terminal back the way the shell expects it, whatever state the app left it in. Unlike
+/// [`Tui::exit`] it needs no `Tui` or runtime, so the panic hook can call it from any thread.
+pub fn restore() -> Result<()> {
+  if crossterm::terminal::is_raw_mode_enabled()? {
+    crossterm::execute!(io(), DisableBracketedPaste, DisableMouseCapture, LeaveAlternateScreen, cursor::Show)?;
+    crossterm::terminal::disable_raw_mode()?;
+  }
+  Ok(())
+}
+
+/// Where `App::run` gets its events from: the terminal, or a script of events in tests.
+pub trait EventSource {
+  /// The next event, or `None` once there won't be any more.
+  fn next(&mut self) -> impl Future<Output = Option<Event>> + Send;
+}
+
+impl EventSource for Tui {
+  fn next(&mut self) -> impl Future<Output = Option<Event>> + Send {
+    Tui::next(self)
+  }
+}
+
+/// Events handed out in order, to drive the app without a terminal.
+#[derive(Default)]
+pub struct ScriptedEvents(VecDeque<Event>);
+
+impl ScriptedEvents {
+  pub fn new(events: impl IntoIterator<Item = Event>) -> Self {
+    Self(events.into_iter().collect())
+  }
+}
+
+impl EventSource for ScriptedEvents {
+  fn next(&mut self) -> impl Future<Output = Option<Event>> + Send {
+    std::future::ready(self.0.pop_front())
+  }
+}
+
 impl Deref for Tui {
   type Target = ratatui::Terminal<Backend<IO>>;
 
@@ -1,11 +1,26 @@
-use std::path::PathBuf;
+use std::{
+  any::Any,
+  cell::Cell,
+  collections::VecDeque,
+  ffi::OsString,
+  fmt,
+  fs::File,
+  io::{self, Write},
+  panic::{self, AssertUnwindSafe},
+  path::{Path, PathBuf},
+  sync::{Mutex, OnceLock},
+};
 
 use color_eyre::eyre::Result;
 use directories::ProjectDirs;
 use lazy_static::lazy_static;
-use tracing::error;
+use tracing::{error, field::Field, Event, Level, Subscriber};
 use tracing_error::ErrorLayer;
-use tracing_subscriber::{self, prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt, Layer};
+use tracing_subscriber::{
+  self, field::Visit, layer::Context, prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt, Layer,
+};
+
+use crate::config::LogFiles;
 
 const VERSION_MESSAGE: &str =
   concat!(env!("CARGO_PKG_VERSION"), "-", env!("VERGEN_GIT_DESCRIBE"), " (", env!("VERGEN_BUILD_DATE"), ")");
@@ -17,9 +32,43 @@ lazy_static! {
   pub static ref CONFIG_FOLDER: Option<PathBuf> =
     std::env::var(format!("{}_CONFIG", PROJECT_NAME.clone())).ok().map(PathBuf::from);
   pub static ref LOG_ENV: String = format!("{}_LOGLEVEL", PROJECT_NAME.clone());
+  /// Overrides `webdriver_backends`, e.g. `chrome` or `chrome,gecko`.
+  pub static ref WEBDRIVER_ENV: String = format!("{}_WEBDRIVER", PROJECT_NAME.clone());
+  /// Runs the browser without a window, like `headless`.
+  pub static ref HEADLESS_ENV: String = format!("{}_HEADLESS", PROJECT_NAME.clone());
   pub static ref LOG_FILE: String = format!("{}.log", env!("CARGO_PKG_NAME"));
 }
 
+/// Where `initialize_logging` writes the log.
+static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// The latest warnings and errors of this run. The TUI's alternate screen swallows anything written
+/// to the terminal while it runs, so they are printed by `flush_session_log` once it's gone.
+static SESSION_LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+thread_local! {
+  /// Set while running code under `catch_panic`, so the panic hook leaves recovery to the caller.
+  static CATCHING_PANIC: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run `f`, turning a panic into an `Err` holding the panic message instead of tearing down the app.
+pub fn catch_panic<R>(f: impl FnOnce() -> R) -> Result<R, String> {
+  let was_catching = CATCHING_PANIC.with(|catching| catching.replace(true));
+  let result = panic::catch_unwind(AssertUnwindSafe(f));
+  CATCHING_PANIC.with(|catching| catching.set(was_catching));
+  result.map_err(|payload| panic_message(payload.as_ref()))
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+  if let Some(s) = payload.downcast_ref::<&str>() {
+    s.to_string()
+  } else if let Some(s) = payload.downcast_ref::<String>() {
+    s.clone()
+  } else {
+    "unknown panic".to_string()
+  }
+}
+
 fn project_directory() -> Option<ProjectDirs> {
   ProjectDirs::from("com", "kdheepak", env!("CARGO_PKG_NAME"))
 }
@@ -33,10 +82,16 @@ pub fn initialize_panic_handler() -> Result<()> {
     .into_hooks();
   eyre_hook.install()?;
   std::panic::set_hook(Box::new(move |panic_info| {
-    if let Ok(mut t) = crate::tui::Tui::new() {
-      if let Err(r) = t.exit() {
-        error!("Unable to exit Terminal: {:?}", r);
-      }
+    if CATCHING_PANIC.with(|catching| catching.get()) {
+      log::error!(
+        "Recovered from panic: {}",
+        strip_ansi_escapes::strip_str(panic_hook.panic_report(panic_info).to_string())
+      );
+      return;
+    }
+    // Leave raw mode and the alternate screen before printing, or the shell stays unusable.
+    if let Err(r) = crate::tui::restore() {
+      error!("Unable to exit Terminal: {:?}", r);
     }
 
     #[cfg(not(debug_assertions))]
@@ -72,6 +127,12 @@ pub fn initialize_panic_handler() -> Result<()> {
   Ok(())
 }
 
+/// Whether the platform's config and data directories are known. They aren't when e.g. `HOME` is
+/// unset, and `get_config_dir`/`get_data_dir` then fall back to directories under `.`.
+pub fn has_project_directory() -> bool {
+  project_directory().is_some()
+}
+
 pub fn get_data_dir() -> PathBuf {
   let directory = if let Some(s) = DATA_FOLDER.clone() {
     s
@@ -94,28 +155,169 @@ pub fn get_config_dir() -> PathBuf {
   directory
 }
 
-pub fn initialize_logging() -> Result<()> {
-  let directory = get_data_dir();
-  std::fs::create_dir_all(directory.clone())?;
+/// The log file of this run, once logging is initialized.
+pub fn log_path() -> Option<&'static Path> {
+  LOG_PATH.get().map(PathBuf::as_path)
+}
+
+/// Start logging messages of `level` and above, unless `RUST_LOG` or `STASH_LOGLEVEL` filter
+/// otherwise, to `stash.log` in the configured directory. The file of the last run, and the
+/// file of this one whenever it grows past `max_size_kb`, is renamed as described at
+/// [`RotatingFile`]. The latest `session_log_lines` warnings and errors are also kept for
+/// `flush_session_log`.
+pub fn initialize_logging(log_files: &LogFiles, level: log::LevelFilter, session_log_lines: usize) -> Result<()> {
+  let mut directory = log_files.dir.clone().unwrap_or_else(get_data_dir);
+  if std::fs::create_dir_all(&directory).is_err() {
+    // Better to log somewhere than to not start at all, e.g. in a read-only working directory.
+    directory = std::env::temp_dir().join(env!("CARGO_PKG_NAME"));
+    std::fs::create_dir_all(&directory)?;
+  }
   let log_path = directory.join(LOG_FILE.clone());
-  let log_file = std::fs::File::create(log_path)?;
+  let log_file = RotatingFile::open(log_path.clone(), log_files.max_size_kb * 1024, log_files.keep)?;
+  let _ = LOG_PATH.set(log_path);
   std::env::set_var(
     "RUST_LOG",
     std::env::var("RUST_LOG")
       .or_else(|_| std::env::var(LOG_ENV.clone()))
-      .unwrap_or_else(|_| format!("{}=info", env!("CARGO_CRATE_NAME"))),
+      .unwrap_or_else(|_| format!("{}={level}", env!("CARGO_CRATE_NAME"))),
   );
   let file_subscriber = tracing_subscriber::fmt::layer()
     .with_file(true)
     .with_line_number(true)
-    .with_writer(log_file)
+    .with_writer(Mutex::new(log_file))
     .with_target(false)
     .with_ansi(false)
     .with_filter(tracing_subscriber::filter::EnvFilter::from_default_env());
-  tracing_subscriber::registry().with(file_subscriber).with(ErrorLayer::default()).init();
+  tracing_subscriber::registry()
+    .with(file_subscriber)
+    .with(SessionLogLayer { lines: session_log_lines })
+    .with(ErrorLayer::default())
+    .init();
   Ok(())
 }
 
+/// A log file that starts over once it reaches `max_bytes`: it is renamed to `<path>.1`, the
+/// older files move up to `<path>.2` and so on, and the one past `<path>.<keep>` is deleted.
+/// Nothing is ever written to the terminal, which belongs to the TUI.
+pub struct RotatingFile {
+  path: PathBuf,
+  file: File,
+  size: u64,
+  max_bytes: u64,
+  keep: usize,
+}
+
+impl RotatingFile {
+  /// Start a new log at `path`, keeping what was there as the first of the old files.
+  pub fn open(path: PathBuf, max_bytes: u64, keep: usize) -> io::Result<Self> {
+    if std::fs::metadata(&path).is_ok_and(|metadata| metadata.len() > 0) {
+      rotate_files(&path, keep)?;
+    }
+    let file = File::create(&path)?;
+    Ok(Self { path, file, size: 0, max_bytes, keep })
+  }
+}
+
+impl Write for RotatingFile {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    if self.size > 0 && self.size + buf.len() as u64 > self.max_bytes {
+      self.file.flush()?;
+      rotate_files(&self.path, self.keep)?;
+      self.file = File::create(&self.path)?;
+      self.size = 0;
+    }
+    let written = self.file.write(buf)?;
+    self.size += written as u64;
+    Ok(written)
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    self.file.flush()
+  }
+}
+
+/// `<path>.<n>`, the `n`th most recent of the old log files.
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+  let mut rotated = OsString::from(path.as_os_str());
+  rotated.push(format!(".{n}"));
+  PathBuf::from(rotated)
+}
+
+/// Shift the old log files one up, dropping the oldest, and make `path` the most recent of them.
+fn rotate_files(path: &Path, keep: usize) -> io::Result<()> {
+  let ignore_missing = |result: io::Result<()>| match result {
+    Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+    _ => Ok(()),
+  };
+  if keep == 0 {
+    return ignore_missing(std::fs::remove_file(path));
+  }
+  ignore_missing(std::fs::remove_file(rotated_path(path, keep)))?;
+  for n in (1..keep).rev() {
+    ignore_missing(std::fs::rename(rotated_path(path, n), rotated_path(path, n + 1)))?;
+  }
+  std::fs::rename(path, rotated_path(path, 1))
+}
+
+/// Records warnings and errors in `SESSION_LOG`.
+struct SessionLogLayer {
+  /// How many it keeps, see `MemoryLimits::session_log_lines`.
+  lines: usize,
+}
+
+impl<S: Subscriber> Layer<S> for SessionLogLayer {
+  fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+    let level = *event.metadata().level();
+    if level > Level::WARN {
+      return;
+    }
+    let mut message = MessageVisitor(String::new());
+    event.record(&mut message);
+    push_session_log(format!("{level:>5} {}", message.0), self.lines);
+  }
+}
+
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+  fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+    if field.name() == "message" {
+      self.0 = format!("{value:?}");
+    }
+  }
+}
+
+fn push_session_log(line: String, cap: usize) {
+  let mut log = SESSION_LOG.lock().unwrap_or_else(|e| e.into_inner());
+  push_bounded(&mut log, line, cap);
+}
+
+/// Append `item` to `items`, dropping the oldest items so that at most `cap` are left. Returns how
+/// many were dropped.
+pub fn push_bounded<T>(items: &mut VecDeque<T>, item: T, cap: usize) -> usize {
+  items.push_back(item);
+  let excess = items.len().saturating_sub(cap);
+  items.drain(..excess);
+  excess
+}
+
+/// Print the warnings and errors of this run to stderr and clear them. Call after the TUI has left
+/// the alternate screen.
+pub fn flush_session_log() {
+  let lines: Vec<String> = SESSION_LOG.lock().unwrap_or_else(|e| e.into_inner()).drain(..).collect();
+  if lines.is_empty() {
+    return;
+  }
+  let mut stderr = std::io::stderr().lock();
+  let _ = writeln!(stderr, "{} logged {} warning(s) or error(s):", env!("CARGO_PKG_NAME"), lines.len());
+  for line in lines {
+    let _ = writeln!(stderr, "  {line}");
+  }
+  if let Some(path) = log_path() {
+    let _ = writeln!(stderr, "See {} for the full log.", path.display());
+  }
+}
+
 /// Similar to the `std::dbg!` macro, but generates `tracing` events rather
 /// than printing to stdout.
 ///
@@ -159,3 +361,28 @@ Config directory: {config_dir_path}
 Data directory: {data_dir_path}"
   )
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_rotating_file() {
+    let dir = std::env::temp_dir().join(format!("stash-log-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("stash.log");
+    std::fs::write(&path, "last run\n").unwrap();
+
+    let mut log = RotatingFile::open(path.clone(), 10, 2).unwrap();
+    assert_eq!(std::fs::read_to_string(rotated_path(&path, 1)).unwrap(), "last run\n");
+    log.write_all(b"first\n").unwrap();
+    log.write_all(b"second\n").unwrap();
+    log.write_all(b"third\n").unwrap();
+    log.flush().unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "third\n");
+    assert_eq!(std::fs::read_to_string(rotated_path(&path, 1)).unwrap(), "second\n");
+    assert_eq!(std::fs::read_to_string(rotated_path(&path, 2)).unwrap(), "first\n");
+    assert!(!rotated_path(&path, 3).exists());
+    std::fs::remove_dir_all(dir).unwrap();
+  }
+}